@@ -0,0 +1,5 @@
+-- Add user-chosen vanity prefix flair, proven over the user's pubkey
+ALTER TABLE users ADD COLUMN vanity_prefix TEXT;
+ALTER TABLE users ADD COLUMN vanity_hash TEXT;
+ALTER TABLE users ADD COLUMN vanity_nonce INTEGER;
+ALTER TABLE users ADD COLUMN vanity_achieved_at DATETIME;
@@ -0,0 +1,4 @@
+-- Decaying reputation built from verified work, used for difficulty discounts
+ALTER TABLE users ADD COLUMN reputation_work REAL NOT NULL DEFAULT 0;
+ALTER TABLE users ADD COLUMN reputation_updated_at DATETIME;
+ALTER TABLE users ADD COLUMN reputation_revoked_at DATETIME;
@@ -0,0 +1,7 @@
+-- Optional proofs linking a pseudonymous key to a main identity
+ALTER TABLE users ADD COLUMN linked_pubkey_hex TEXT;
+ALTER TABLE users ADD COLUMN link_main_sig_hex TEXT;
+ALTER TABLE users ADD COLUMN link_child_sig_hex TEXT;
+ALTER TABLE users ADD COLUMN linked_at DATETIME;
+
+CREATE INDEX IF NOT EXISTS idx_users_linked_pubkey ON users (linked_pubkey_hex);
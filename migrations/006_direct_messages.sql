@@ -0,0 +1,15 @@
+-- Client-side encrypted direct messages between pubkeys, stamped with proof of work
+CREATE TABLE IF NOT EXISTS direct_messages (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    sender_pubkey_hex TEXT NOT NULL,
+    recipient_pubkey_hex TEXT NOT NULL,
+    ciphertext_hex TEXT NOT NULL,
+    pow_nonce INTEGER NOT NULL,
+    pow_hash TEXT NOT NULL,
+    pow_challenge_id TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (pow_challenge_id) REFERENCES pow_challenges (id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_direct_messages_recipient ON direct_messages (recipient_pubkey_hex, id);
+CREATE UNIQUE INDEX IF NOT EXISTS idx_direct_messages_challenge ON direct_messages (pow_challenge_id);
@@ -0,0 +1,23 @@
+-- Reply and quote events per author pubkey, recorded at commit time.
+-- A new thread's opening post can quote too, so the notifying post id may be NULL.
+-- A reply straight to a thread, or a board quote of one, targets its opening post,
+-- which has no post id, and the target may live in another thread.
+CREATE TABLE IF NOT EXISTS notifications (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    recipient_pubkey_hex TEXT NOT NULL,
+    kind TEXT NOT NULL, -- 'reply' or 'quote'
+    thread_id INTEGER NOT NULL,
+    post_id INTEGER, -- NULL when the notifying post is a thread's opening post
+    target_thread_id INTEGER NOT NULL,
+    target_post_id INTEGER, -- NULL for the target thread's opening post
+    actor_pubkey_hex TEXT NOT NULL,
+    read_at DATETIME,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (thread_id) REFERENCES threads (id),
+    FOREIGN KEY (post_id) REFERENCES posts (id),
+    FOREIGN KEY (target_thread_id) REFERENCES threads (id),
+    FOREIGN KEY (target_post_id) REFERENCES posts (id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_notifications_recipient ON notifications (recipient_pubkey_hex, id DESC);
+CREATE INDEX IF NOT EXISTS idx_notifications_unread ON notifications (recipient_pubkey_hex, read_at);
@@ -0,0 +1,2 @@
+-- Bind the target board of a new thread into its challenge
+ALTER TABLE pow_challenges ADD COLUMN board_id INTEGER NOT NULL DEFAULT 0;
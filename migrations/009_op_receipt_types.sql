@@ -0,0 +1,16 @@
+-- Key receipts by (op id, operation) so a begin and its commit can share one op id,
+-- and remember a fingerprint of the request that produced each result
+CREATE TABLE IF NOT EXISTS op_receipts_new (
+    id TEXT NOT NULL,
+    operation_type TEXT NOT NULL,
+    request_hash TEXT,
+    result_json TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (id, operation_type)
+);
+
+INSERT INTO op_receipts_new (id, operation_type, result_json, created_at)
+SELECT id, operation_type, result_json, created_at FROM op_receipts;
+
+DROP TABLE op_receipts;
+ALTER TABLE op_receipts_new RENAME TO op_receipts;
@@ -0,0 +1,2 @@
+-- Threads stop bumping once they reach their board's bump limit (autosage)
+ALTER TABLE boards ADD COLUMN bump_limit INTEGER NOT NULL DEFAULT 300;
@@ -0,0 +1,7 @@
+-- Boards keep a bounded number of live threads; the overflow is archived read-only
+ALTER TABLE boards ADD COLUMN max_threads INTEGER NOT NULL DEFAULT 150;
+
+ALTER TABLE threads ADD COLUMN is_archived BOOLEAN NOT NULL DEFAULT 0;
+ALTER TABLE threads ADD COLUMN archived_at DATETIME;
+
+CREATE INDEX idx_threads_board_archive ON threads(board_id, is_archived, bumped_at);
@@ -0,0 +1,10 @@
+-- One index per server-side sort over a board's live threads, each ending in id
+-- so keyset cursors resolve ties without a scan
+DROP INDEX IF EXISTS idx_threads_board_bumped;
+DROP INDEX IF EXISTS idx_threads_board_archive;
+
+CREATE INDEX IF NOT EXISTS idx_threads_live_bump ON threads (board_id, is_archived, is_pinned, bumped_at, id);
+CREATE INDEX IF NOT EXISTS idx_threads_live_created ON threads (board_id, is_archived, is_pinned, id);
+CREATE INDEX IF NOT EXISTS idx_threads_live_replies ON threads (board_id, is_archived, is_pinned, reply_count, id);
+CREATE INDEX IF NOT EXISTS idx_threads_live_work ON threads (board_id, is_archived, is_pinned, bump_score, id);
+CREATE INDEX IF NOT EXISTS idx_threads_archived ON threads (board_id, is_archived, archived_at);
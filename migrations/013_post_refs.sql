@@ -0,0 +1,18 @@
+-- Quote edges from PostDraft.refs, recorded at commit time.
+-- from_post_id is NULL when the quoting post is a thread's opening post, and
+-- to_post_id when a board quote like >>>/tech/42 points at one.
+CREATE TABLE IF NOT EXISTS post_refs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    from_thread_id INTEGER NOT NULL,
+    from_post_id INTEGER,
+    to_thread_id INTEGER NOT NULL,
+    to_post_id INTEGER,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (from_thread_id) REFERENCES threads (id),
+    FOREIGN KEY (from_post_id) REFERENCES posts (id),
+    FOREIGN KEY (to_thread_id) REFERENCES threads (id),
+    FOREIGN KEY (to_post_id) REFERENCES posts (id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_post_refs_from ON post_refs (from_thread_id);
+CREATE INDEX IF NOT EXISTS idx_post_refs_to ON post_refs (to_thread_id, to_post_id);
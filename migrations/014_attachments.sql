@@ -0,0 +1,29 @@
+-- Per-board upload limits
+ALTER TABLE boards ADD COLUMN max_upload_bytes INTEGER NOT NULL DEFAULT 4194304;
+ALTER TABLE boards ADD COLUMN allowed_mime_types TEXT NOT NULL DEFAULT 'image/png,image/jpeg,image/gif,image/webp';
+
+-- Uploaded files, keyed by the SHA-256 a draft lists in its attachments
+CREATE TABLE IF NOT EXISTS attachments (
+    sha256_hex TEXT PRIMARY KEY NOT NULL,
+    mime_type TEXT NOT NULL,
+    size_bytes INTEGER NOT NULL,
+    original_filename TEXT,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Attachments linked to posts at commit time; post_id is NULL for a thread's opening post
+CREATE TABLE IF NOT EXISTS post_attachments (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    thread_id INTEGER NOT NULL,
+    post_id INTEGER,
+    sha256_hex TEXT NOT NULL,
+    position INTEGER NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (thread_id) REFERENCES threads (id),
+    FOREIGN KEY (post_id) REFERENCES posts (id),
+    FOREIGN KEY (sha256_hex) REFERENCES attachments (sha256_hex)
+);
+
+CREATE INDEX IF NOT EXISTS idx_post_attachments_thread ON post_attachments (thread_id, post_id, position);
+-- Finds files no post links to, for cleanup
+CREATE INDEX IF NOT EXISTS idx_post_attachments_file ON post_attachments (sha256_hex);
@@ -0,0 +1,20 @@
+-- Dimensions of stored (re-encoded) images and their thumbnails
+ALTER TABLE attachments ADD COLUMN width INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE attachments ADD COLUMN height INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE attachments ADD COLUMN thumb_width INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE attachments ADD COLUMN thumb_height INTEGER NOT NULL DEFAULT 0;
+
+-- The first attachment's details, alongside image_path, for listings
+ALTER TABLE threads ADD COLUMN image_width INTEGER;
+ALTER TABLE threads ADD COLUMN image_height INTEGER;
+ALTER TABLE threads ADD COLUMN image_size_bytes INTEGER;
+ALTER TABLE threads ADD COLUMN thumb_path TEXT;
+ALTER TABLE threads ADD COLUMN thumb_width INTEGER;
+ALTER TABLE threads ADD COLUMN thumb_height INTEGER;
+
+ALTER TABLE posts ADD COLUMN image_width INTEGER;
+ALTER TABLE posts ADD COLUMN image_height INTEGER;
+ALTER TABLE posts ADD COLUMN image_size_bytes INTEGER;
+ALTER TABLE posts ADD COLUMN thumb_path TEXT;
+ALTER TABLE posts ADD COLUMN thumb_width INTEGER;
+ALTER TABLE posts ADD COLUMN thumb_height INTEGER;
@@ -0,0 +1,16 @@
+-- Per-board uniqueness mode: refuse posts whose normalized content was posted there before
+ALTER TABLE boards ADD COLUMN unique_content BOOLEAN NOT NULL DEFAULT 0;
+ALTER TABLE boards ADD COLUMN unique_normalization TEXT NOT NULL DEFAULT 'case,whitespace,punctuation';
+
+-- Every content hash seen on a uniqueness board; post_id is NULL for a thread's opening post
+CREATE TABLE IF NOT EXISTS content_hashes (
+    board_id INTEGER NOT NULL,
+    content_hash TEXT NOT NULL,
+    thread_id INTEGER NOT NULL,
+    post_id INTEGER,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (board_id, content_hash),
+    FOREIGN KEY (board_id) REFERENCES boards (id),
+    FOREIGN KEY (thread_id) REFERENCES threads (id),
+    FOREIGN KEY (post_id) REFERENCES posts (id)
+);
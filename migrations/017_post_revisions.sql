@@ -0,0 +1,27 @@
+-- Set when an author edits a post; the post keeps its original proof of work
+ALTER TABLE threads ADD COLUMN edited_at DATETIME;
+ALTER TABLE posts ADD COLUMN edited_at DATETIME;
+
+-- Every version of an edited post. Revision 0 is the original, carrying the post's
+-- own proof; later revisions carry the author's signature and the edit's proof.
+-- post_id is NULL for a thread's opening post.
+CREATE TABLE IF NOT EXISTS post_revisions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    thread_id INTEGER NOT NULL,
+    post_id INTEGER,
+    revision INTEGER NOT NULL,
+    title TEXT,
+    content TEXT NOT NULL,
+    author_pubkey TEXT,
+    signature_hex TEXT,
+    pow_nonce INTEGER,
+    pow_hash TEXT,
+    pow_challenge_id TEXT,
+    pow_difficulty REAL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (thread_id) REFERENCES threads (id),
+    FOREIGN KEY (post_id) REFERENCES posts (id)
+);
+
+-- COALESCE so opening post revisions (NULL post_id) can't repeat a revision number
+CREATE UNIQUE INDEX IF NOT EXISTS idx_post_revisions_target ON post_revisions (thread_id, COALESCE(post_id, 0), revision);
@@ -0,0 +1,22 @@
+-- Set when an author deletes a post; the row stays as a tombstone with its proof of work
+ALTER TABLE threads ADD COLUMN deleted_at DATETIME;
+ALTER TABLE posts ADD COLUMN deleted_at DATETIME;
+
+-- What a tombstoned post said, kept for moderators until purge_after.
+-- post_id is NULL for a thread's opening post.
+CREATE TABLE IF NOT EXISTS deleted_content (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    thread_id INTEGER NOT NULL,
+    post_id INTEGER,
+    title TEXT,
+    content TEXT NOT NULL,
+    image_path TEXT,
+    image_filename TEXT,
+    author_pubkey TEXT,
+    deleted_at DATETIME NOT NULL,
+    purge_after DATETIME NOT NULL,
+    FOREIGN KEY (thread_id) REFERENCES threads (id),
+    FOREIGN KEY (post_id) REFERENCES posts (id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_deleted_content_purge ON deleted_content (purge_after);
@@ -0,0 +1,36 @@
+-- A poll opened with a thread; at most one per thread
+CREATE TABLE IF NOT EXISTS polls (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    thread_id INTEGER NOT NULL UNIQUE,
+    question TEXT NOT NULL,
+    created_at DATETIME NOT NULL,
+    FOREIGN KEY (thread_id) REFERENCES threads (id)
+);
+
+CREATE TABLE IF NOT EXISTS poll_options (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    poll_id INTEGER NOT NULL,
+    position INTEGER NOT NULL,
+    label TEXT NOT NULL,
+    FOREIGN KEY (poll_id) REFERENCES polls (id),
+    UNIQUE (poll_id, position)
+);
+
+-- One signed, mined vote per pubkey per poll
+CREATE TABLE IF NOT EXISTS poll_votes (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    poll_id INTEGER NOT NULL,
+    option_id INTEGER NOT NULL,
+    voter_pubkey TEXT NOT NULL,
+    signature_hex TEXT NOT NULL,
+    pow_nonce INTEGER NOT NULL,
+    pow_hash TEXT NOT NULL,
+    pow_challenge_id TEXT NOT NULL,
+    pow_difficulty REAL NOT NULL,
+    created_at DATETIME NOT NULL,
+    FOREIGN KEY (poll_id) REFERENCES polls (id),
+    FOREIGN KEY (option_id) REFERENCES poll_options (id),
+    UNIQUE (poll_id, voter_pubkey)
+);
+
+CREATE INDEX IF NOT EXISTS idx_poll_votes_option ON poll_votes (option_id);
@@ -0,0 +1,19 @@
+-- Reactions to posts, each carrying its own small proof of work.
+-- post_id is NULL for a thread's opening post.
+CREATE TABLE IF NOT EXISTS reactions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    thread_id INTEGER NOT NULL,
+    post_id INTEGER,
+    kind TEXT NOT NULL,
+    reactor_pubkey TEXT NOT NULL,
+    pow_nonce INTEGER NOT NULL,
+    pow_hash TEXT NOT NULL,
+    pow_challenge_id TEXT NOT NULL,
+    pow_difficulty REAL NOT NULL,
+    created_at DATETIME NOT NULL,
+    FOREIGN KEY (thread_id) REFERENCES threads (id),
+    FOREIGN KEY (post_id) REFERENCES posts (id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_reactions_thread ON reactions (thread_id);
+CREATE INDEX IF NOT EXISTS idx_reactions_reactor ON reactions (thread_id, post_id, reactor_pubkey, created_at);
@@ -0,0 +1,15 @@
+-- Extra work contributed to a thread without posting; each boost adds its
+-- difficulty to the thread's bump_score
+CREATE TABLE IF NOT EXISTS thread_boosts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    thread_id INTEGER NOT NULL,
+    booster_pubkey TEXT NOT NULL,
+    pow_nonce INTEGER NOT NULL,
+    pow_hash TEXT NOT NULL,
+    pow_challenge_id TEXT NOT NULL,
+    pow_difficulty REAL NOT NULL,
+    created_at DATETIME NOT NULL,
+    FOREIGN KEY (thread_id) REFERENCES threads (id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_thread_boosts_thread ON thread_boosts (thread_id, booster_pubkey);
@@ -0,0 +1,4 @@
+-- A proof doesn't name the challenge it was mined for, so the same solved hash
+-- would verify again under a fresh challenge with matching parameters. Each hash
+-- can only be committed once.
+CREATE UNIQUE INDEX IF NOT EXISTS idx_pow_commits_solved_hash ON pow_commits (solved_hash_hex);
@@ -0,0 +1,340 @@
+//! Content-addressed attachment storage. Files live under the upload directory at
+//! `ab/cd/<sha256>.<ext>`, so identical uploads share one file and a draft can
+//! reference an attachment by its hash before the post exists.
+
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+use image::{
+    codecs::{
+        gif::{GifDecoder, GifEncoder, Repeat},
+        jpeg::JpegEncoder,
+        png::PngEncoder,
+        webp::WebPEncoder,
+    },
+    AnimationDecoder, DynamicImage, Frame, ImageDecoder, ImageFormat, ImageReader, Limits,
+};
+use thiserror::Error;
+
+/// Identify a file by its magic bytes; the client's content type isn't trusted
+pub fn sniff_mime(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else {
+        None
+    }
+}
+
+pub fn extension_for(mime_type: &str) -> &'static str {
+    match mime_type {
+        "image/png" => "png",
+        "image/jpeg" => "jpg",
+        "image/gif" => "gif",
+        "image/webp" => "webp",
+        _ => "bin",
+    }
+}
+
+/// Attachments are referenced by lowercase hex SHA-256
+pub fn is_valid_hash(sha256_hex: &str) -> bool {
+    sha256_hex.len() == 64 && sha256_hex.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+/// Path of a file relative to the upload directory, with forward slashes
+pub fn relative_path(sha256_hex: &str, mime_type: &str) -> String {
+    format!(
+        "{}/{}/{}.{}",
+        &sha256_hex[..2],
+        &sha256_hex[2..4],
+        sha256_hex,
+        extension_for(mime_type)
+    )
+}
+
+pub fn storage_path(upload_dir: &Path, sha256_hex: &str, mime_type: &str) -> PathBuf {
+    upload_dir.join(relative_path(sha256_hex, mime_type))
+}
+
+/// URL the file is served from
+pub fn public_url(sha256_hex: &str, mime_type: &str) -> String {
+    format!("/uploads/{}", relative_path(sha256_hex, mime_type))
+}
+
+/// Keep only the last path component of a client-supplied name, capped in length
+pub fn clean_filename(filename: &str) -> Option<String> {
+    let name = filename
+        .rsplit(['/', '\\'])
+        .next()
+        .unwrap_or("")
+        .trim()
+        .chars()
+        .filter(|c| !c.is_control())
+        .take(200)
+        .collect::<String>();
+
+    if name.is_empty() {
+        None
+    } else {
+        Some(name)
+    }
+}
+
+/// Longest side allowed in either dimension, whatever the pixel budget
+pub const MAX_IMAGE_SIDE: u32 = 16_384;
+
+#[derive(Debug, Error)]
+pub enum ImageError {
+    #[error("Unsupported file type")]
+    Unsupported,
+    #[error("Image could not be decoded")]
+    Undecodable,
+    #[error("Image is {width}x{height}, over the limit of {max_pixels} pixels")]
+    TooLarge { width: u32, height: u32, max_pixels: u64 },
+    #[error("Image could not be encoded")]
+    Unencodable,
+}
+
+/// An upload after re-encoding: only pixel data survives, so EXIF, GPS and
+/// comment blocks in the original are gone
+#[derive(Debug)]
+pub struct ProcessedImage {
+    pub mime_type: &'static str,
+    pub bytes: Vec<u8>,
+    pub width: u32,
+    pub height: u32,
+    pub thumbnail: Vec<u8>,
+    pub thumb_width: u32,
+    pub thumb_height: u32,
+}
+
+/// Thumbnails of JPEGs stay JPEG; everything else gets a PNG to keep transparency
+pub fn thumbnail_mime(mime_type: &str) -> &'static str {
+    if mime_type == "image/jpeg" {
+        "image/jpeg"
+    } else {
+        "image/png"
+    }
+}
+
+/// Path of a thumbnail relative to the upload directory, next to its full-size file
+pub fn thumbnail_relative_path(sha256_hex: &str, mime_type: &str) -> String {
+    format!(
+        "{}/{}/{}_s.{}",
+        &sha256_hex[..2],
+        &sha256_hex[2..4],
+        sha256_hex,
+        extension_for(thumbnail_mime(mime_type))
+    )
+}
+
+pub fn thumbnail_storage_path(upload_dir: &Path, sha256_hex: &str, mime_type: &str) -> PathBuf {
+    upload_dir.join(thumbnail_relative_path(sha256_hex, mime_type))
+}
+
+pub fn thumbnail_url(sha256_hex: &str, mime_type: &str) -> String {
+    format!("/uploads/{}", thumbnail_relative_path(sha256_hex, mime_type))
+}
+
+/// Decode an upload and re-encode it in its own format without metadata, plus a
+/// thumbnail no larger than `thumb_side` on either side.
+///
+/// Dimensions are read from the header and checked against `max_pixels` before any
+/// pixel data is decoded, and the decoder's allocations are capped to match, so a
+/// small file claiming a huge canvas is rejected cheaply. GIF frames all count
+/// towards the same budget.
+pub fn process_image(bytes: &[u8], max_pixels: u64, thumb_side: u32) -> Result<ProcessedImage, ImageError> {
+    let mime_type = sniff_mime(bytes).ok_or(ImageError::Unsupported)?;
+    let format = ImageFormat::from_mime_type(mime_type).ok_or(ImageError::Unsupported)?;
+
+    let (width, height) = reader(bytes, format, max_pixels)
+        .into_dimensions()
+        .map_err(|_| ImageError::Undecodable)?;
+    if u64::from(width) * u64::from(height) > max_pixels {
+        return Err(ImageError::TooLarge { width, height, max_pixels });
+    }
+
+    let (image, encoded) = if format == ImageFormat::Gif {
+        let frames = decode_gif_frames(bytes, max_pixels)?;
+        let encoded = encode_gif(&frames)?;
+        let first = frames.into_iter().next().ok_or(ImageError::Undecodable)?;
+        (DynamicImage::ImageRgba8(first.into_buffer()), encoded)
+    } else {
+        let mut decoder = reader(bytes, format, max_pixels)
+            .into_decoder()
+            .map_err(|_| ImageError::Undecodable)?;
+        // Orientation lives in the EXIF block we're about to drop, so apply it to the pixels
+        let orientation = decoder.orientation().map_err(|_| ImageError::Undecodable)?;
+        let mut image = DynamicImage::from_decoder(decoder).map_err(|_| ImageError::Undecodable)?;
+        image.apply_orientation(orientation);
+        let encoded = encode(&image, mime_type)?;
+        (image, encoded)
+    };
+
+    let thumbnail = if image.width() <= thumb_side && image.height() <= thumb_side {
+        image.clone()
+    } else {
+        image.thumbnail(thumb_side, thumb_side)
+    };
+
+    Ok(ProcessedImage {
+        mime_type,
+        bytes: encoded,
+        width: image.width(),
+        height: image.height(),
+        thumb_width: thumbnail.width(),
+        thumb_height: thumbnail.height(),
+        thumbnail: encode(&thumbnail, thumbnail_mime(mime_type))?,
+    })
+}
+
+fn limits(max_pixels: u64) -> Limits {
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_IMAGE_SIDE);
+    limits.max_image_height = Some(MAX_IMAGE_SIDE);
+    // Room for the widest pixel type (16-bit RGBA) at the full budget
+    limits.max_alloc = Some(max_pixels.saturating_mul(8));
+    limits
+}
+
+fn reader(bytes: &[u8], format: ImageFormat, max_pixels: u64) -> ImageReader<Cursor<&[u8]>> {
+    let mut reader = ImageReader::new(Cursor::new(bytes));
+    reader.set_format(format);
+    reader.limits(limits(max_pixels));
+    reader
+}
+
+fn decode_gif_frames(bytes: &[u8], max_pixels: u64) -> Result<Vec<Frame>, ImageError> {
+    let mut decoder = GifDecoder::new(Cursor::new(bytes)).map_err(|_| ImageError::Undecodable)?;
+    decoder.set_limits(limits(max_pixels)).map_err(|_| ImageError::Undecodable)?;
+
+    let mut frames = Vec::new();
+    let mut pixels = 0u64;
+    for frame in decoder.into_frames() {
+        let frame = frame.map_err(|_| ImageError::Undecodable)?;
+        let (width, height) = frame.buffer().dimensions();
+        pixels += u64::from(width) * u64::from(height);
+        if pixels > max_pixels {
+            return Err(ImageError::TooLarge { width, height, max_pixels });
+        }
+        frames.push(frame);
+    }
+
+    Ok(frames)
+}
+
+fn encode_gif(frames: &[Frame]) -> Result<Vec<u8>, ImageError> {
+    let mut out = Vec::new();
+    {
+        let mut encoder = GifEncoder::new(&mut out);
+        encoder.set_repeat(Repeat::Infinite).map_err(|_| ImageError::Unencodable)?;
+        encoder
+            .encode_frames(frames.iter().cloned())
+            .map_err(|_| ImageError::Unencodable)?;
+    }
+    Ok(out)
+}
+
+fn encode(image: &DynamicImage, mime_type: &str) -> Result<Vec<u8>, ImageError> {
+    let mut out = Vec::new();
+    let result = match mime_type {
+        "image/jpeg" => JpegEncoder::new_with_quality(&mut out, 90).encode_image(&image.to_rgb8()),
+        "image/webp" => image.to_rgba8().write_with_encoder(WebPEncoder::new_lossless(&mut out)),
+        _ => image.write_with_encoder(PngEncoder::new(&mut out)),
+    };
+    result.map_err(|_| ImageError::Unencodable)?;
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sniff_mime() {
+        assert_eq!(sniff_mime(b"\x89PNG\r\n\x1a\n...."), Some("image/png"));
+        assert_eq!(sniff_mime(&[0xFF, 0xD8, 0xFF, 0xE0]), Some("image/jpeg"));
+        assert_eq!(sniff_mime(b"GIF89a..."), Some("image/gif"));
+        assert_eq!(sniff_mime(b"RIFF\0\0\0\0WEBPVP8 "), Some("image/webp"));
+        assert_eq!(sniff_mime(b"<svg onload=alert(1)>"), None);
+        assert_eq!(sniff_mime(b""), None);
+    }
+
+    #[test]
+    fn test_paths_are_content_addressed() {
+        let hash = "ab".repeat(32);
+        assert!(is_valid_hash(&hash));
+        assert!(!is_valid_hash(&hash.to_uppercase()));
+        assert!(!is_valid_hash("../etc/passwd"));
+
+        assert_eq!(
+            public_url(&hash, "image/png"),
+            format!("/uploads/ab/ab/{}.png", hash)
+        );
+        assert_eq!(
+            storage_path(Path::new("uploads"), &hash, "image/jpeg"),
+            Path::new("uploads").join(format!("ab/ab/{}.jpg", hash))
+        );
+    }
+
+    #[test]
+    fn test_clean_filename() {
+        assert_eq!(clean_filename("C:\\Users\\me\\cat.png").as_deref(), Some("cat.png"));
+        assert_eq!(clean_filename("../../etc/passwd").as_deref(), Some("passwd"));
+        assert_eq!(clean_filename("dir/"), None);
+    }
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let image = DynamicImage::new_rgb8(width, height);
+        encode(&image, "image/png").unwrap()
+    }
+
+    #[test]
+    fn test_process_image_strips_exif() {
+        let jpeg = encode(&DynamicImage::new_rgb8(8, 8), "image/jpeg").unwrap();
+
+        // Splice an EXIF APP1 segment with a GPS payload in after the SOI marker
+        let mut payload = b"Exif\0\0MM\0\x2a\0\0\0\x08\0\0\0\0\0\0".to_vec();
+        payload.extend_from_slice(b"GPS 51.5N 0.1W");
+        let mut tagged = jpeg[..2].to_vec();
+        tagged.extend_from_slice(&[0xFF, 0xE1]);
+        tagged.extend_from_slice(&((payload.len() + 2) as u16).to_be_bytes());
+        tagged.extend_from_slice(&payload);
+        tagged.extend_from_slice(&jpeg[2..]);
+
+        let processed = process_image(&tagged, 1_000_000, 250).unwrap();
+        assert_eq!(processed.mime_type, "image/jpeg");
+        assert_eq!((processed.width, processed.height), (8, 8));
+        assert!(!processed.bytes.windows(4).any(|w| w == b"Exif"));
+        assert!(!processed.bytes.windows(3).any(|w| w == b"GPS"));
+    }
+
+    #[test]
+    fn test_process_image_bounds_thumbnail() {
+        let processed = process_image(&png(1000, 500), 1_000_000, 250).unwrap();
+        assert_eq!((processed.width, processed.height), (1000, 500));
+        assert_eq!((processed.thumb_width, processed.thumb_height), (250, 125));
+        assert_eq!(sniff_mime(&processed.thumbnail), Some("image/png"));
+
+        // Small images aren't scaled up
+        let processed = process_image(&png(40, 30), 1_000_000, 250).unwrap();
+        assert_eq!((processed.thumb_width, processed.thumb_height), (40, 30));
+    }
+
+    #[test]
+    fn test_process_image_rejects_bombs() {
+        assert!(matches!(
+            process_image(&png(2000, 2000), 1_000_000, 250),
+            Err(ImageError::TooLarge { width: 2000, height: 2000, .. })
+        ));
+        assert!(matches!(
+            process_image(b"\x89PNG\r\n\x1a\ngarbage", 1_000_000, 250),
+            Err(ImageError::Undecodable)
+        ));
+        assert!(matches!(process_image(b"<svg/>", 1_000_000, 250), Err(ImageError::Unsupported)));
+    }
+}
\ No newline at end of file
@@ -6,7 +6,33 @@ pub struct Config {
     pub database_url: String,
     pub port: u16,
     pub pow_default_prefix: String,
+    pub edit_pow_prefix: String,
+    pub poll_vote_pow_prefix: String,
+    pub reaction_pow_prefix: String,
+    pub reaction_cooldown_seconds: i64,
+    pub boost_pow_prefix: String,
     pub pow_challenge_ttl_seconds: u64,
+    pub vanity_min_len: usize,
+    pub vanity_max_len: usize,
+    pub pow_min_prefix_len: usize,
+    pub reputation_half_life_days: f64,
+    pub reputation_work_per_level: f64,
+    pub reputation_min_age_days: i64,
+    pub reputation_max_discount: usize,
+    pub moderator_token: Option<String>,
+    pub auth_max_skew_seconds: i64,
+    pub deleted_retention_days: i64,
+    pub pow_max_clock_skew_seconds: i64,
+    pub dm_max_ciphertext_bytes: usize,
+    pub post_max_title_chars: usize,
+    pub post_max_body_chars: usize,
+    pub post_max_attachments: usize,
+    pub upload_dir: String,
+    pub upload_pow_prefix: String,
+    pub upload_orphan_max_age_hours: i64,
+    pub upload_max_bytes: usize,
+    pub upload_max_pixels: u64,
+    pub thumbnail_max_side: u32,
 }
 
 impl Config {
@@ -20,16 +46,146 @@ impl Config {
             
         let pow_default_prefix = env::var("POW_DEFAULT_PREFIX")
             .unwrap_or_else(|_| "21e8".to_string());
+
+        // Edits only fix up an existing post, so they get a much easier puzzle
+        let edit_pow_prefix = env::var("EDIT_POW_PREFIX")
+            .unwrap_or_else(|_| "21e".to_string());
+
+        let poll_vote_pow_prefix = env::var("POLL_VOTE_POW_PREFIX")
+            .unwrap_or_else(|_| "21e".to_string());
+
+        // Reactions are meant to be cheap; one prefix digit short of a vote
+        let reaction_pow_prefix = env::var("REACTION_POW_PREFIX")
+            .unwrap_or_else(|_| "21".to_string());
+
+        // Minimum gap between one pubkey's reactions to the same post
+        let reaction_cooldown_seconds = env::var("REACTION_COOLDOWN_SECONDS")
+            .unwrap_or_else(|_| "60".to_string())
+            .parse()?;
+
+        let boost_pow_prefix = env::var("BOOST_POW_PREFIX")
+            .unwrap_or_else(|_| "21e8".to_string());
             
         let pow_challenge_ttl_seconds = env::var("POW_CHALLENGE_TTL_SECONDS")
             .unwrap_or_else(|_| "300".to_string())
             .parse()?;
 
+        let vanity_min_len = env::var("VANITY_MIN_LEN")
+            .unwrap_or_else(|_| "3".to_string())
+            .parse()?;
+
+        let vanity_max_len = env::var("VANITY_MAX_LEN")
+            .unwrap_or_else(|_| "8".to_string())
+            .parse()?;
+
+        let pow_min_prefix_len = env::var("POW_MIN_PREFIX_LEN")
+            .unwrap_or_else(|_| "3".to_string())
+            .parse()?;
+
+        let reputation_half_life_days = env::var("REPUTATION_HALF_LIFE_DAYS")
+            .unwrap_or_else(|_| "30".to_string())
+            .parse()?;
+
+        let reputation_work_per_level = env::var("REPUTATION_WORK_PER_LEVEL")
+            .unwrap_or_else(|_| "500".to_string())
+            .parse()?;
+
+        let reputation_min_age_days = env::var("REPUTATION_MIN_AGE_DAYS")
+            .unwrap_or_else(|_| "7".to_string())
+            .parse()?;
+
+        let reputation_max_discount = env::var("REPUTATION_MAX_DISCOUNT")
+            .unwrap_or_else(|_| "1".to_string())
+            .parse()?;
+
+        let moderator_token = env::var("MODERATOR_TOKEN").ok().filter(|t| !t.is_empty());
+
+        let auth_max_skew_seconds = env::var("AUTH_MAX_SKEW_SECONDS")
+            .unwrap_or_else(|_| "300".to_string())
+            .parse()?;
+
+        // How long moderators can still see what a deleted post said
+        let deleted_retention_days = env::var("DELETED_RETENTION_DAYS")
+            .unwrap_or_else(|_| "30".to_string())
+            .parse()?;
+
+        let pow_max_clock_skew_seconds = env::var("POW_MAX_CLOCK_SKEW_SECONDS")
+            .unwrap_or_else(|_| "120".to_string())
+            .parse()?;
+
+        let dm_max_ciphertext_bytes = env::var("DM_MAX_CIPHERTEXT_BYTES")
+            .unwrap_or_else(|_| "8192".to_string())
+            .parse()?;
+
+        let post_max_title_chars = env::var("POST_MAX_TITLE_CHARS")
+            .unwrap_or_else(|_| "200".to_string())
+            .parse()?;
+
+        let post_max_body_chars = env::var("POST_MAX_BODY_CHARS")
+            .unwrap_or_else(|_| "8000".to_string())
+            .parse()?;
+
+        let post_max_attachments = env::var("POST_MAX_ATTACHMENTS")
+            .unwrap_or_else(|_| "4".to_string())
+            .parse()?;
+
+        let upload_dir = env::var("UPLOAD_DIR")
+            .unwrap_or_else(|_| "uploads".to_string());
+
+        // Uploads cost a little work, so storage can't be filled for free
+        let upload_pow_prefix = env::var("UPLOAD_POW_PREFIX")
+            .unwrap_or_else(|_| "21e".to_string());
+
+        // Files no post links to are deleted once they're this old
+        let upload_orphan_max_age_hours = env::var("UPLOAD_ORPHAN_MAX_AGE_HOURS")
+            .unwrap_or_else(|_| "24".to_string())
+            .parse()?;
+
+        // Hard cap on any request body to the upload endpoint; boards set their own lower limits
+        let upload_max_bytes = env::var("UPLOAD_MAX_BYTES")
+            .unwrap_or_else(|_| "16777216".to_string())
+            .parse()?;
+
+        // Decoded size cap, checked from the header before decoding, so small files can't expand into huge canvases
+        let upload_max_pixels = env::var("UPLOAD_MAX_PIXELS")
+            .unwrap_or_else(|_| "40000000".to_string())
+            .parse()?;
+
+        let thumbnail_max_side = env::var("THUMBNAIL_MAX_SIDE")
+            .unwrap_or_else(|_| "250".to_string())
+            .parse()?;
+
         Ok(Config {
             database_url,
             port,
             pow_default_prefix,
+            edit_pow_prefix,
+            poll_vote_pow_prefix,
+            reaction_pow_prefix,
+            reaction_cooldown_seconds,
+            boost_pow_prefix,
             pow_challenge_ttl_seconds,
+            vanity_min_len,
+            vanity_max_len,
+            pow_min_prefix_len,
+            reputation_half_life_days,
+            reputation_work_per_level,
+            reputation_min_age_days,
+            reputation_max_discount,
+            moderator_token,
+            auth_max_skew_seconds,
+            deleted_retention_days,
+            pow_max_clock_skew_seconds,
+            dm_max_ciphertext_bytes,
+            post_max_title_chars,
+            post_max_body_chars,
+            post_max_attachments,
+            upload_dir,
+            upload_pow_prefix,
+            upload_orphan_max_age_hours,
+            upload_max_bytes,
+            upload_max_pixels,
+            thumbnail_max_side,
         })
     }
 }
\ No newline at end of file
@@ -1,6 +1,9 @@
-use crate::{error::Result, models::*};
-use chrono::{DateTime, Utc};
-use sqlx::{Pool, Sqlite};
+use crate::{
+    error::{AppError, Result},
+    models::*,
+};
+use chrono::{DateTime, TimeZone, Utc};
+use sqlx::{Executor, Pool, Sqlite};
 
 pub type DbPool = Pool<Sqlite>;
 
@@ -8,6 +11,17 @@ pub struct BoardRepository;
 pub struct ThreadRepository;
 pub struct PostRepository;
 pub struct PowRepository;
+pub struct UserRepository;
+pub struct DirectMessageRepository;
+pub struct NotificationRepository;
+pub struct PostRefRepository;
+pub struct AttachmentRepository;
+pub struct ContentHashRepository;
+pub struct RevisionRepository;
+pub struct DeletedContentRepository;
+pub struct PollRepository;
+pub struct ReactionRepository;
+pub struct BoostRepository;
 
 impl BoardRepository {
     pub async fn list_active(pool: &DbPool) -> Result<Vec<Board>> {
@@ -15,7 +29,9 @@ impl BoardRepository {
             Board,
             r#"
             SELECT id, slug, name, description, is_active, 
-                   thread_count, post_count, created_at, updated_at
+                   thread_count, post_count, bump_limit, max_threads,
+                   max_upload_bytes, allowed_mime_types, unique_content, unique_normalization,
+                   created_at, updated_at
             FROM boards 
             WHERE is_active = 1
             ORDER BY name
@@ -27,40 +43,319 @@ impl BoardRepository {
         Ok(boards)
     }
 
-    pub async fn find_by_slug(pool: &DbPool, slug: &str) -> Result<Option<Board>> {
+    pub async fn find_by_slug<'e, E>(executor: E, slug: &str) -> Result<Option<Board>>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
         let board = sqlx::query_as!(
             Board,
             r#"
             SELECT id, slug, name, description, is_active,
-                   thread_count, post_count, created_at, updated_at
+                   thread_count, post_count, bump_limit, max_threads,
+                   max_upload_bytes, allowed_mime_types, unique_content, unique_normalization,
+                   created_at, updated_at
             FROM boards 
             WHERE slug = ? AND is_active = 1
             "#,
             slug
         )
-        .fetch_optional(pool)
+        .fetch_optional(executor)
+        .await?;
+
+        Ok(board)
+    }
+
+    /// Active boards with any of the given slugs, in one query
+    pub async fn find_by_slugs<'e, E>(executor: E, slugs: &[String]) -> Result<Vec<Board>>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let slugs_json = serde_json::to_string(slugs)?;
+        let boards = sqlx::query_as!(
+            Board,
+            r#"
+            SELECT id AS "id!: i64", slug, name, description, is_active,
+                   thread_count AS "thread_count!: i32", post_count AS "post_count!: i32",
+                   bump_limit AS "bump_limit!: i32", max_threads AS "max_threads!: i32",
+                   max_upload_bytes, allowed_mime_types, unique_content, unique_normalization,
+                   created_at AS "created_at!: DateTime<Utc>",
+                   updated_at AS "updated_at!: DateTime<Utc>"
+            FROM boards
+            WHERE slug IN (SELECT value FROM json_each(?)) AND is_active = 1
+            "#,
+            slugs_json
+        )
+        .fetch_all(executor)
+        .await?;
+
+        Ok(boards)
+    }
+
+    pub async fn find_by_id<'e, E>(executor: E, id: i64) -> Result<Option<Board>>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let board = sqlx::query_as!(
+            Board,
+            r#"
+            SELECT id AS "id!: i64", slug, name, description, is_active,
+                   thread_count AS "thread_count!: i32", post_count AS "post_count!: i32",
+                   bump_limit AS "bump_limit!: i32", max_threads AS "max_threads!: i32",
+                   max_upload_bytes, allowed_mime_types, unique_content, unique_normalization,
+                   created_at AS "created_at!: DateTime<Utc>",
+                   updated_at AS "updated_at!: DateTime<Utc>"
+            FROM boards 
+            WHERE id = ?
+            "#,
+            id
+        )
+        .fetch_optional(executor)
         .await?;
 
         Ok(board)
     }
+
+    /// Count a new thread, whose opening post also counts towards the board's posts
+    pub async fn record_thread<'e, E>(executor: E, board_id: i64) -> Result<()>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let now = Utc::now();
+
+        sqlx::query!(
+            r#"
+            UPDATE boards
+            SET thread_count = thread_count + 1, post_count = post_count + 1, updated_at = ?
+            WHERE id = ?
+            "#,
+            now,
+            board_id
+        )
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn record_post<'e, E>(executor: E, board_id: i64) -> Result<()>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let now = Utc::now();
+
+        sqlx::query!(
+            r#"
+            UPDATE boards
+            SET post_count = post_count + 1, updated_at = ?
+            WHERE id = ?
+            "#,
+            now,
+            board_id
+        )
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Take a deleted post back out of the board's count
+    pub async fn remove_post<'e, E>(executor: E, board_id: i64) -> Result<()>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let now = Utc::now();
+
+        sqlx::query!(
+            r#"
+            UPDATE boards
+            SET post_count = MAX(post_count - 1, 0), updated_at = ?
+            WHERE id = ?
+            "#,
+            now,
+            board_id
+        )
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
 }
 
 impl ThreadRepository {
-    pub async fn list_by_board(pool: &DbPool, board_id: i64, limit: i64) -> Result<Vec<Thread>> {
+    /// One page of a board's live threads in the given sort, starting after `cursor`.
+    /// Each sort has a matching index, so deep pages cost the same as the first.
+    pub async fn list_page(
+        pool: &DbPool,
+        board_id: i64,
+        sort: ThreadSort,
+        cursor: Option<ThreadCursor>,
+        limit: i64,
+    ) -> Result<Vec<Thread>> {
+        // Without a cursor, start above every row: is_pinned is only ever 0 or 1
+        let (pinned, key, id) = match cursor {
+            Some(c) => (c.is_pinned as i64, c.key, c.id),
+            None => (2, i64::MAX, i64::MAX),
+        };
+
+        let threads = match sort {
+            ThreadSort::Bump => {
+                let bumped_at = Utc.timestamp_nanos(key);
+                sqlx::query_as!(
+                    Thread,
+                    r#"
+                    SELECT id AS "id!: i64", board_id, title, content, author_name, author_pubkey,
+                           image_path, image_filename, image_width, image_height, image_size_bytes,
+                           thumb_path, thumb_width, thumb_height,
+                           reply_count AS "reply_count!: i32", is_pinned, is_locked, is_archived,
+                           archived_at AS "archived_at?: DateTime<Utc>",
+                           bump_score AS "bump_score!: i32",
+                           bumped_at AS "bumped_at!: DateTime<Utc>", pow_nonce, pow_hash,
+                           pow_challenge_id, pow_difficulty,
+                           pow_verified_at AS "pow_verified_at?: DateTime<Utc>",
+                           edited_at AS "edited_at?: DateTime<Utc>",
+                           deleted_at AS "deleted_at?: DateTime<Utc>",
+                           created_at AS "created_at!: DateTime<Utc>",
+                           updated_at AS "updated_at!: DateTime<Utc>"
+                    FROM threads
+                    WHERE board_id = ? AND is_archived = 0 AND (is_pinned, bumped_at, id) < (?, ?, ?)
+                    ORDER BY is_pinned DESC, bumped_at DESC, id DESC
+                    LIMIT ?
+                    "#,
+                    board_id,
+                    pinned,
+                    bumped_at,
+                    id,
+                    limit
+                )
+                .fetch_all(pool)
+                .await?
+            }
+            ThreadSort::Created => {
+                sqlx::query_as!(
+                    Thread,
+                    r#"
+                    SELECT id AS "id!: i64", board_id, title, content, author_name, author_pubkey,
+                           image_path, image_filename, image_width, image_height, image_size_bytes,
+                           thumb_path, thumb_width, thumb_height,
+                           reply_count AS "reply_count!: i32", is_pinned, is_locked, is_archived,
+                           archived_at AS "archived_at?: DateTime<Utc>",
+                           bump_score AS "bump_score!: i32",
+                           bumped_at AS "bumped_at!: DateTime<Utc>", pow_nonce, pow_hash,
+                           pow_challenge_id, pow_difficulty,
+                           pow_verified_at AS "pow_verified_at?: DateTime<Utc>",
+                           edited_at AS "edited_at?: DateTime<Utc>",
+                           deleted_at AS "deleted_at?: DateTime<Utc>",
+                           created_at AS "created_at!: DateTime<Utc>",
+                           updated_at AS "updated_at!: DateTime<Utc>"
+                    FROM threads
+                    WHERE board_id = ? AND is_archived = 0 AND (is_pinned, id) < (?, ?)
+                    ORDER BY is_pinned DESC, id DESC
+                    LIMIT ?
+                    "#,
+                    board_id,
+                    pinned,
+                    id,
+                    limit
+                )
+                .fetch_all(pool)
+                .await?
+            }
+            ThreadSort::Replies => {
+                sqlx::query_as!(
+                    Thread,
+                    r#"
+                    SELECT id AS "id!: i64", board_id, title, content, author_name, author_pubkey,
+                           image_path, image_filename, image_width, image_height, image_size_bytes,
+                           thumb_path, thumb_width, thumb_height,
+                           reply_count AS "reply_count!: i32", is_pinned, is_locked, is_archived,
+                           archived_at AS "archived_at?: DateTime<Utc>",
+                           bump_score AS "bump_score!: i32",
+                           bumped_at AS "bumped_at!: DateTime<Utc>", pow_nonce, pow_hash,
+                           pow_challenge_id, pow_difficulty,
+                           pow_verified_at AS "pow_verified_at?: DateTime<Utc>",
+                           edited_at AS "edited_at?: DateTime<Utc>",
+                           deleted_at AS "deleted_at?: DateTime<Utc>",
+                           created_at AS "created_at!: DateTime<Utc>",
+                           updated_at AS "updated_at!: DateTime<Utc>"
+                    FROM threads
+                    WHERE board_id = ? AND is_archived = 0 AND (is_pinned, reply_count, id) < (?, ?, ?)
+                    ORDER BY is_pinned DESC, reply_count DESC, id DESC
+                    LIMIT ?
+                    "#,
+                    board_id,
+                    pinned,
+                    key,
+                    id,
+                    limit
+                )
+                .fetch_all(pool)
+                .await?
+            }
+            ThreadSort::Work => {
+                sqlx::query_as!(
+                    Thread,
+                    r#"
+                    SELECT id AS "id!: i64", board_id, title, content, author_name, author_pubkey,
+                           image_path, image_filename, image_width, image_height, image_size_bytes,
+                           thumb_path, thumb_width, thumb_height,
+                           reply_count AS "reply_count!: i32", is_pinned, is_locked, is_archived,
+                           archived_at AS "archived_at?: DateTime<Utc>",
+                           bump_score AS "bump_score!: i32",
+                           bumped_at AS "bumped_at!: DateTime<Utc>", pow_nonce, pow_hash,
+                           pow_challenge_id, pow_difficulty,
+                           pow_verified_at AS "pow_verified_at?: DateTime<Utc>",
+                           edited_at AS "edited_at?: DateTime<Utc>",
+                           deleted_at AS "deleted_at?: DateTime<Utc>",
+                           created_at AS "created_at!: DateTime<Utc>",
+                           updated_at AS "updated_at!: DateTime<Utc>"
+                    FROM threads
+                    WHERE board_id = ? AND is_archived = 0 AND (is_pinned, bump_score, id) < (?, ?, ?)
+                    ORDER BY is_pinned DESC, bump_score DESC, id DESC
+                    LIMIT ?
+                    "#,
+                    board_id,
+                    pinned,
+                    key,
+                    id,
+                    limit
+                )
+                .fetch_all(pool)
+                .await?
+            }
+        };
+
+        Ok(threads)
+    }
+
+    /// Archived threads of a board, most recently archived first
+    pub async fn list_archived(
+        pool: &DbPool,
+        board_id: i64,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<Thread>> {
         let threads = sqlx::query_as!(
             Thread,
             r#"
-            SELECT id, board_id, title, content, author_name, author_pubkey,
-                   image_path, image_filename, reply_count, is_pinned, is_locked,
-                   bump_score, bumped_at, pow_nonce, pow_hash, pow_challenge_id,
-                   pow_difficulty, pow_verified_at, created_at, updated_at
+            SELECT id AS "id!: i64", board_id, title, content, author_name, author_pubkey,
+                   image_path, image_filename, image_width, image_height, image_size_bytes,
+                   thumb_path, thumb_width, thumb_height, reply_count AS "reply_count!: i32",
+                   is_pinned, is_locked, is_archived, archived_at AS "archived_at?: DateTime<Utc>",
+                   bump_score AS "bump_score!: i32", bumped_at AS "bumped_at!: DateTime<Utc>",
+                   pow_nonce, pow_hash, pow_challenge_id, pow_difficulty,
+                   pow_verified_at AS "pow_verified_at?: DateTime<Utc>",
+                   edited_at AS "edited_at?: DateTime<Utc>",
+                   deleted_at AS "deleted_at?: DateTime<Utc>",
+                   created_at AS "created_at!: DateTime<Utc>",
+                   updated_at AS "updated_at!: DateTime<Utc>"
             FROM threads
-            WHERE board_id = ?
-            ORDER BY is_pinned DESC, bumped_at DESC
-            LIMIT ?
+            WHERE board_id = ? AND is_archived = 1
+            ORDER BY archived_at DESC, id DESC
+            LIMIT ? OFFSET ?
             "#,
             board_id,
-            limit
+            limit,
+            offset
         )
         .fetch_all(pool)
         .await?;
@@ -68,27 +363,93 @@ impl ThreadRepository {
         Ok(threads)
     }
 
-    pub async fn find_by_id(pool: &DbPool, id: i64) -> Result<Option<Thread>> {
+    pub async fn count_archived(pool: &DbPool, board_id: i64) -> Result<i64> {
+        let count = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) AS "count!: i64" FROM threads WHERE board_id = ? AND is_archived = 1"#,
+            board_id
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(count)
+    }
+
+    /// Archive the least recently bumped live threads beyond the board's capacity.
+    /// Pinned threads are exempt and don't count against it.
+    pub async fn archive_overflow<'e, E>(executor: E, board_id: i64) -> Result<u64>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let now = Utc::now();
+
+        let result = sqlx::query!(
+            r#"
+            UPDATE threads
+            SET is_archived = 1, archived_at = ?, updated_at = ?
+            WHERE id IN (
+                SELECT id FROM threads
+                WHERE board_id = ? AND is_archived = 0 AND is_pinned = 0
+                ORDER BY bumped_at DESC, id DESC
+                LIMIT -1 OFFSET (SELECT max_threads FROM boards WHERE id = ?)
+            )
+            "#,
+            now,
+            now,
+            board_id,
+            board_id
+        )
+        .execute(executor)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Boards of any of the given threads, in one query
+    pub async fn locate_many<'e, E>(executor: E, ids: &[i64]) -> Result<Vec<QuoteLocation>>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let ids_json = serde_json::to_string(ids)?;
+        let locations = sqlx::query_as!(
+            QuoteLocation,
+            r#"
+            SELECT id AS "id!: i64", id AS "thread_id!: i64", board_id
+            FROM threads
+            WHERE id IN (SELECT value FROM json_each(?))
+            "#,
+            ids_json
+        )
+        .fetch_all(executor)
+        .await?;
+
+        Ok(locations)
+    }
+
+    pub async fn find_by_id<'e, E>(executor: E, id: i64) -> Result<Option<Thread>>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
         let thread = sqlx::query_as!(
             Thread,
             r#"
             SELECT id, board_id, title, content, author_name, author_pubkey,
-                   image_path, image_filename, reply_count, is_pinned, is_locked,
-                   bump_score, bumped_at, pow_nonce, pow_hash, pow_challenge_id,
-                   pow_difficulty, pow_verified_at, created_at, updated_at
+                   image_path, image_filename, image_width, image_height, image_size_bytes,
+                   thumb_path, thumb_width, thumb_height, reply_count, is_pinned, is_locked,
+                   is_archived, archived_at, bump_score, bumped_at, pow_nonce, pow_hash, pow_challenge_id,
+                   pow_difficulty, pow_verified_at, edited_at, deleted_at, created_at, updated_at
             FROM threads
             WHERE id = ?
             "#,
             id
         )
-        .fetch_optional(pool)
+        .fetch_optional(executor)
         .await?;
 
         Ok(thread)
     }
 
-    pub async fn create(
-        pool: &DbPool,
+    pub async fn create<'e, E>(
+        executor: E,
         board_id: i64,
         title: &str,
         content: &str,
@@ -97,16 +458,21 @@ impl ThreadRepository {
         pow_nonce: Option<i64>,
         pow_hash: Option<&str>,
         pow_challenge_id: Option<&str>,
-    ) -> Result<i64> {
+        pow_difficulty: f64,
+    ) -> Result<i64>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
         let now = Utc::now();
+        let bump_score = pow_difficulty as i64;
         
         let result = sqlx::query!(
             r#"
             INSERT INTO threads (
                 board_id, title, content, author_name, author_pubkey,
-                pow_nonce, pow_hash, pow_challenge_id, pow_verified_at,
-                bumped_at, created_at, updated_at
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                pow_nonce, pow_hash, pow_challenge_id, pow_difficulty, pow_verified_at,
+                bump_score, bumped_at, created_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
             board_id,
             title,
@@ -116,26 +482,261 @@ impl ThreadRepository {
             pow_nonce,
             pow_hash,
             pow_challenge_id,
+            pow_difficulty,
             now,
+            bump_score,
             now,
             now,
             now
         )
-        .execute(pool)
+        .execute(executor)
         .await?;
 
         Ok(result.last_insert_rowid())
     }
+
+    /// Add boosted work to a thread's score and bump it like a reply would. Boosts
+    /// don't count as replies, but stop bumping once the board's bump limit is reached.
+    pub async fn record_boost<'e, E>(executor: E, thread_id: i64, pow_difficulty: f64) -> Result<()>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let now = Utc::now();
+        let work = pow_difficulty as i64;
+
+        sqlx::query!(
+            r#"
+            UPDATE threads
+            SET bump_score = bump_score + ?,
+                bumped_at = CASE
+                    WHEN reply_count < (SELECT bump_limit FROM boards WHERE boards.id = threads.board_id)
+                    THEN ?
+                    ELSE bumped_at
+                END,
+                updated_at = ?
+            WHERE id = ?
+            "#,
+            work,
+            now,
+            now,
+            thread_id
+        )
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Count a reply against its thread and add the reply's work to its score.
+    /// The thread is bumped unless the reply is sage or the board's bump limit is reached.
+    pub async fn record_reply<'e, E>(
+        executor: E,
+        thread_id: i64,
+        pow_difficulty: f64,
+        sage: bool,
+    ) -> Result<()>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let now = Utc::now();
+        let work = pow_difficulty as i64;
+        let bump = !sage;
+
+        sqlx::query!(
+            r#"
+            UPDATE threads
+            SET reply_count = reply_count + 1,
+                bump_score = bump_score + ?,
+                bumped_at = CASE
+                    WHEN ? AND reply_count < (SELECT bump_limit FROM boards WHERE boards.id = threads.board_id)
+                    THEN ?
+                    ELSE bumped_at
+                END,
+                updated_at = ?
+            WHERE id = ?
+            "#,
+            work,
+            bump,
+            now,
+            now,
+            thread_id
+        )
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Copy the first attachment's location and dimensions onto the row
+    pub async fn set_image<'e, E>(
+        executor: E,
+        id: i64,
+        attachment: &Attachment,
+    ) -> Result<()>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let image_path = attachment.url();
+        let thumb_path = attachment.thumbnail_url();
+
+        sqlx::query!(
+            r#"
+            UPDATE threads
+            SET image_path = ?, image_filename = ?, image_width = ?, image_height = ?,
+                image_size_bytes = ?, thumb_path = ?, thumb_width = ?, thumb_height = ?
+            WHERE id = ?
+            "#,
+            image_path,
+            attachment.original_filename,
+            attachment.width,
+            attachment.height,
+            attachment.size_bytes,
+            thumb_path,
+            attachment.thumb_width,
+            attachment.thumb_height,
+            id
+        )
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Undo a deleted reply's `record_reply` counts. The thread keeps its bump time.
+    pub async fn remove_reply<'e, E>(executor: E, thread_id: i64, pow_difficulty: f64) -> Result<()>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let now = Utc::now();
+        let work = pow_difficulty as i64;
+
+        sqlx::query!(
+            r#"
+            UPDATE threads
+            SET reply_count = MAX(reply_count - 1, 0),
+                bump_score = MAX(bump_score - ?, 0),
+                updated_at = ?
+            WHERE id = ?
+            "#,
+            work,
+            now,
+            thread_id
+        )
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Clear an opening post's text and image, keeping its proof of work
+    pub async fn tombstone<'e, E>(executor: E, id: i64) -> Result<()>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let now = Utc::now();
+
+        sqlx::query!(
+            r#"
+            UPDATE threads
+            SET title = '[deleted]', content = '', image_path = NULL, image_filename = NULL,
+                image_width = NULL, image_height = NULL, image_size_bytes = NULL,
+                thumb_path = NULL, thumb_width = NULL, thumb_height = NULL,
+                deleted_at = ?, updated_at = ?
+            WHERE id = ?
+            "#,
+            now,
+            now,
+            id
+        )
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Replace an opening post's text with an author's edit
+    pub async fn edit<'e, E>(executor: E, id: i64, title: &str, content: &str) -> Result<()>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let now = Utc::now();
+
+        sqlx::query!(
+            r#"
+            UPDATE threads
+            SET title = ?, content = ?, edited_at = ?, updated_at = ?
+            WHERE id = ?
+            "#,
+            title,
+            content,
+            now,
+            now,
+            id
+        )
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
 }
 
 impl PostRepository {
+    /// Threads and boards of any of the given posts, in one query
+    pub async fn locate_many<'e, E>(executor: E, ids: &[i64]) -> Result<Vec<QuoteLocation>>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let ids_json = serde_json::to_string(ids)?;
+        let locations = sqlx::query_as!(
+            QuoteLocation,
+            r#"
+            SELECT posts.id AS "id!: i64", posts.thread_id, threads.board_id
+            FROM posts
+            JOIN threads ON threads.id = posts.thread_id
+            WHERE posts.id IN (SELECT value FROM json_each(?))
+            "#,
+            ids_json
+        )
+        .fetch_all(executor)
+        .await?;
+
+        Ok(locations)
+    }
+
+    pub async fn find_by_id<'e, E>(executor: E, id: i64) -> Result<Option<Post>>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let post = sqlx::query_as!(
+            Post,
+            r#"
+            SELECT id AS "id!: i64", thread_id, parent_id, content, author_name, author_pubkey,
+                   image_path, image_filename, image_width, image_height, image_size_bytes,
+                   thumb_path, thumb_width, thumb_height, pow_nonce, pow_hash, pow_challenge_id,
+                   pow_difficulty, pow_verified_at AS "pow_verified_at?: DateTime<Utc>",
+                   edited_at AS "edited_at?: DateTime<Utc>",
+                   deleted_at AS "deleted_at?: DateTime<Utc>",
+                   created_at AS "created_at!: DateTime<Utc>",
+                   updated_at AS "updated_at!: DateTime<Utc>"
+            FROM posts
+            WHERE id = ?
+            "#,
+            id
+        )
+        .fetch_optional(executor)
+        .await?;
+
+        Ok(post)
+    }
+
     pub async fn list_by_thread(pool: &DbPool, thread_id: i64) -> Result<Vec<Post>> {
         let posts = sqlx::query_as!(
             Post,
             r#"
             SELECT id, thread_id, parent_id, content, author_name, author_pubkey,
-                   image_path, image_filename, pow_nonce, pow_hash, pow_challenge_id,
-                   pow_difficulty, pow_verified_at, created_at, updated_at
+                   image_path, image_filename, image_width, image_height, image_size_bytes,
+                   thumb_path, thumb_width, thumb_height, pow_nonce, pow_hash, pow_challenge_id,
+                   pow_difficulty, pow_verified_at, edited_at, deleted_at, created_at, updated_at
             FROM posts
             WHERE thread_id = ?
             ORDER BY created_at ASC
@@ -148,8 +749,8 @@ impl PostRepository {
         Ok(posts)
     }
 
-    pub async fn create(
-        pool: &DbPool,
+    pub async fn create<'e, E>(
+        executor: E,
         thread_id: i64,
         parent_id: Option<i64>,
         content: &str,
@@ -158,16 +759,20 @@ impl PostRepository {
         pow_nonce: Option<i64>,
         pow_hash: Option<&str>,
         pow_challenge_id: Option<&str>,
-    ) -> Result<i64> {
+        pow_difficulty: f64,
+    ) -> Result<i64>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
         let now = Utc::now();
         
         let result = sqlx::query!(
             r#"
             INSERT INTO posts (
                 thread_id, parent_id, content, author_name, author_pubkey,
-                pow_nonce, pow_hash, pow_challenge_id, pow_verified_at,
+                pow_nonce, pow_hash, pow_challenge_id, pow_difficulty, pow_verified_at,
                 created_at, updated_at
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
             thread_id,
             parent_id,
@@ -177,69 +782,161 @@ impl PostRepository {
             pow_nonce,
             pow_hash,
             pow_challenge_id,
+            pow_difficulty,
             now,
             now,
             now
         )
-        .execute(pool)
+        .execute(executor)
         .await?;
 
         Ok(result.last_insert_rowid())
     }
-}
 
-impl PowRepository {
-    pub async fn create_challenge(pool: &DbPool, challenge: &PowChallenge) -> Result<()> {
+    /// Copy the first attachment's location and dimensions onto the row
+    pub async fn set_image<'e, E>(
+        executor: E,
+        id: i64,
+        attachment: &Attachment,
+    ) -> Result<()>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let image_path = attachment.url();
+        let thumb_path = attachment.thumbnail_url();
+
         sqlx::query!(
             r#"
-            INSERT INTO pow_challenges (
-                id, user_pubkey_hex, scope, thread_id, parent_id,
-                post_bytes_hash, required_prefix_hex, challenge_version,
-                canonical_bytes, expires_at, created_at
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            UPDATE posts
+            SET image_path = ?, image_filename = ?, image_width = ?, image_height = ?,
+                image_size_bytes = ?, thumb_path = ?, thumb_width = ?, thumb_height = ?
+            WHERE id = ?
             "#,
-            challenge.id,
-            challenge.user_pubkey_hex,
-            challenge.scope,
-            challenge.thread_id,
-            challenge.parent_id,
-            challenge.post_bytes_hash,
-            challenge.required_prefix_hex,
-            challenge.challenge_version,
-            challenge.canonical_bytes,
-            challenge.expires_at,
-            challenge.created_at
+            image_path,
+            attachment.original_filename,
+            attachment.width,
+            attachment.height,
+            attachment.size_bytes,
+            thumb_path,
+            attachment.thumb_width,
+            attachment.thumb_height,
+            id
         )
-        .execute(pool)
+        .execute(executor)
         .await?;
 
         Ok(())
     }
 
-    pub async fn find_challenge(pool: &DbPool, id: &str) -> Result<Option<PowChallenge>> {
-        let challenge = sqlx::query_as!(
-            PowChallenge,
+    /// Clear a reply's text and image, keeping its proof of work
+    pub async fn tombstone<'e, E>(executor: E, id: i64) -> Result<()>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let now = Utc::now();
+
+        sqlx::query!(
             r#"
-            SELECT id, user_pubkey_hex, scope, thread_id, parent_id,
-                   post_bytes_hash, required_prefix_hex, challenge_version,
-                   canonical_bytes, expires_at, created_at
-            FROM pow_challenges
+            UPDATE posts
+            SET content = '', image_path = NULL, image_filename = NULL,
+                image_width = NULL, image_height = NULL, image_size_bytes = NULL,
+                thumb_path = NULL, thumb_width = NULL, thumb_height = NULL,
+                deleted_at = ?, updated_at = ?
             WHERE id = ?
             "#,
+            now,
+            now,
             id
         )
-        .fetch_optional(pool)
+        .execute(executor)
         .await?;
 
-        Ok(challenge)
+        Ok(())
     }
 
-    pub async fn create_commit(pool: &DbPool, commit: &PowCommit) -> Result<()> {
+    /// Replace a reply's text with an author's edit
+    pub async fn edit<'e, E>(executor: E, id: i64, content: &str) -> Result<()>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let now = Utc::now();
+
         sqlx::query!(
             r#"
-            INSERT INTO pow_commits (
-                id, challenge_id, nonce_u64, miner_version, timestamp_i64,
-                solved_hash_hex, thread_id, post_id, verified, created_at
+            UPDATE posts
+            SET content = ?, edited_at = ?, updated_at = ?
+            WHERE id = ?
+            "#,
+            content,
+            now,
+            now,
+            id
+        )
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+}
+
+impl PowRepository {
+    pub async fn create_challenge(pool: &DbPool, challenge: &PowChallenge) -> Result<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO pow_challenges (
+                id, user_pubkey_hex, scope, board_id, thread_id, parent_id,
+                post_bytes_hash, required_prefix_hex, challenge_version,
+                canonical_bytes, expires_at, created_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+            challenge.id,
+            challenge.user_pubkey_hex,
+            challenge.scope,
+            challenge.board_id,
+            challenge.thread_id,
+            challenge.parent_id,
+            challenge.post_bytes_hash,
+            challenge.required_prefix_hex,
+            challenge.challenge_version,
+            challenge.canonical_bytes,
+            challenge.expires_at,
+            challenge.created_at
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn find_challenge(pool: &DbPool, id: &str) -> Result<Option<PowChallenge>> {
+        let challenge = sqlx::query_as!(
+            PowChallenge,
+            r#"
+            SELECT id, user_pubkey_hex, scope, board_id, thread_id, parent_id,
+                   post_bytes_hash, required_prefix_hex, challenge_version,
+                   canonical_bytes, expires_at, created_at
+            FROM pow_challenges
+            WHERE id = ?
+            "#,
+            id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(challenge)
+    }
+
+    /// Record a verified proof. A solved hash can only be committed once, so a proof
+    /// that races past `find_spent_commit` still comes back as ChallengeUsed.
+    pub async fn create_commit<'e, E>(executor: E, commit: &PowCommit) -> Result<()>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query!(
+            r#"
+            INSERT INTO pow_commits (
+                id, challenge_id, nonce_u64, miner_version, timestamp_i64,
+                solved_hash_hex, thread_id, post_id, verified, created_at
             ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
             commit.id,
@@ -253,43 +950,1228 @@ impl PowRepository {
             commit.verified,
             commit.created_at
         )
-        .execute(pool)
-        .await?;
+        .execute(executor)
+        .await
+        .map_err(|error| match error.as_database_error() {
+            Some(database_error) if database_error.is_unique_violation() => AppError::ChallengeUsed,
+            _ => AppError::Database(error),
+        })?;
 
         Ok(())
     }
 
-    pub async fn create_op_receipt(pool: &DbPool, receipt: &OpReceipt) -> Result<()> {
+    /// A commit that already spent this challenge or this solved hash. Either one makes
+    /// a new commit a replay: a proof doesn't name its challenge, so the same hash would
+    /// otherwise verify again under any fresh challenge with matching parameters.
+    pub async fn find_spent_commit<'e, E>(
+        executor: E,
+        challenge_id: &str,
+        solved_hash_hex: &str,
+    ) -> Result<Option<String>>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let commit_id = sqlx::query_scalar!(
+            r#"
+            SELECT id AS "id!" FROM pow_commits
+            WHERE challenge_id = ? OR solved_hash_hex = ?
+            LIMIT 1
+            "#,
+            challenge_id,
+            solved_hash_hex
+        )
+        .fetch_optional(executor)
+        .await?;
+
+        Ok(commit_id)
+    }
+
+    pub async fn create_op_receipt<'e, E>(executor: E, receipt: &OpReceipt) -> Result<()>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
         sqlx::query!(
             r#"
             INSERT OR REPLACE INTO op_receipts (
-                id, operation_type, result_json, created_at
-            ) VALUES (?, ?, ?, ?)
+                id, operation_type, request_hash, result_json, created_at
+            ) VALUES (?, ?, ?, ?, ?)
             "#,
             receipt.id,
             receipt.operation_type,
+            receipt.request_hash,
             receipt.result_json,
             receipt.created_at
         )
-        .execute(pool)
+        .execute(executor)
         .await?;
 
         Ok(())
     }
 
-    pub async fn find_op_receipt(pool: &DbPool, id: &str) -> Result<Option<OpReceipt>> {
+    pub async fn find_op_receipt(
+        pool: &DbPool,
+        id: &str,
+        operation_type: &str,
+    ) -> Result<Option<OpReceipt>> {
         let receipt = sqlx::query_as!(
             OpReceipt,
             r#"
-            SELECT id, operation_type, result_json, created_at
+            SELECT id, operation_type, request_hash, result_json, created_at
             FROM op_receipts
-            WHERE id = ?
+            WHERE id = ? AND operation_type = ?
             "#,
-            id
+            id,
+            operation_type
         )
         .fetch_optional(pool)
         .await?;
 
         Ok(receipt)
     }
+}
+
+impl UserRepository {
+    pub async fn find_by_pubkey<'e, E>(executor: E, pubkey_hex: &str) -> Result<Option<User>>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let user = sqlx::query_as!(
+            User,
+            r#"
+            SELECT id AS "id!: i64", pubkey_hex, btc_address, personal_21e8_hash,
+                   personal_21e8_nonce,
+                   personal_21e8_achieved_at AS "personal_21e8_achieved_at?: DateTime<Utc>",
+                   vanity_prefix, vanity_hash, vanity_nonce,
+                   vanity_achieved_at AS "vanity_achieved_at?: DateTime<Utc>",
+                   post_count AS "post_count!: i32", thread_count AS "thread_count!: i32",
+                   total_pow_difficulty, reputation_work,
+                   reputation_updated_at AS "reputation_updated_at?: DateTime<Utc>",
+                   reputation_revoked_at AS "reputation_revoked_at?: DateTime<Utc>",
+                   linked_pubkey_hex, link_main_sig_hex, link_child_sig_hex,
+                   linked_at AS "linked_at?: DateTime<Utc>",
+                   created_at AS "created_at!: DateTime<Utc>",
+                   updated_at AS "updated_at!: DateTime<Utc>"
+            FROM users
+            WHERE pubkey_hex = ?
+            "#,
+            pubkey_hex
+        )
+        .fetch_optional(executor)
+        .await?;
+
+        Ok(user)
+    }
+
+    pub async fn set_vanity_flair(
+        pool: &DbPool,
+        pubkey_hex: &str,
+        vanity_prefix: &str,
+        vanity_hash: &str,
+        vanity_nonce: i64,
+    ) -> Result<()> {
+        let now = Utc::now();
+
+        sqlx::query!(
+            r#"
+            INSERT INTO users (
+                pubkey_hex, vanity_prefix, vanity_hash, vanity_nonce,
+                vanity_achieved_at, created_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT (pubkey_hex) DO UPDATE SET
+                vanity_prefix = excluded.vanity_prefix,
+                vanity_hash = excluded.vanity_hash,
+                vanity_nonce = excluded.vanity_nonce,
+                vanity_achieved_at = excluded.vanity_achieved_at,
+                updated_at = excluded.updated_at
+            "#,
+            pubkey_hex,
+            vanity_prefix,
+            vanity_hash,
+            vanity_nonce,
+            now,
+            now,
+            now
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn record_work<'e, E>(
+        executor: E,
+        pubkey_hex: &str,
+        difficulty: f64,
+        reputation_work: f64,
+        is_thread: bool,
+    ) -> Result<()>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let now = Utc::now();
+        let (thread_count, post_count) = if is_thread { (1, 0) } else { (0, 1) };
+
+        sqlx::query!(
+            r#"
+            INSERT INTO users (
+                pubkey_hex, post_count, thread_count, total_pow_difficulty,
+                reputation_work, reputation_updated_at, created_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT (pubkey_hex) DO UPDATE SET
+                post_count = users.post_count + excluded.post_count,
+                thread_count = users.thread_count + excluded.thread_count,
+                total_pow_difficulty = users.total_pow_difficulty + excluded.total_pow_difficulty,
+                reputation_work = excluded.reputation_work,
+                reputation_updated_at = excluded.reputation_updated_at,
+                updated_at = excluded.updated_at
+            "#,
+            pubkey_hex,
+            post_count,
+            thread_count,
+            difficulty,
+            reputation_work,
+            now,
+            now,
+            now
+        )
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn revoke_reputation(pool: &DbPool, pubkey_hex: &str) -> Result<bool> {
+        let now = Utc::now();
+
+        let result = sqlx::query!(
+            r#"
+            UPDATE users
+            SET reputation_work = 0, reputation_updated_at = ?, reputation_revoked_at = ?, updated_at = ?
+            WHERE pubkey_hex = ?
+            "#,
+            now,
+            now,
+            now,
+            pubkey_hex
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Whether any pseudonym is linked to this key as its main identity
+    /// Flairs of any of the given keys, in one query. A linked pseudonym without a
+    /// flair of its own shows its main identity's; keys with no flair are left out.
+    pub async fn find_flairs<'e, E>(executor: E, pubkeys: &[String]) -> Result<Vec<Flair>>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let pubkeys_json = serde_json::to_string(pubkeys)?;
+        let flairs = sqlx::query_as!(
+            Flair,
+            r#"
+            SELECT pubkey_hex AS "pubkey_hex!: String", vanity_prefix AS "vanity_prefix!: String",
+                   vanity_hash AS "vanity_hash!: String"
+            FROM (
+                SELECT users.pubkey_hex,
+                       CASE WHEN users.vanity_prefix IS NULL THEN main.vanity_prefix ELSE users.vanity_prefix END AS vanity_prefix,
+                       CASE WHEN users.vanity_prefix IS NULL THEN main.vanity_hash ELSE users.vanity_hash END AS vanity_hash
+                FROM users
+                LEFT JOIN users AS main ON main.pubkey_hex = users.linked_pubkey_hex
+                WHERE users.pubkey_hex IN (SELECT value FROM json_each(?))
+            )
+            WHERE vanity_prefix IS NOT NULL AND vanity_hash IS NOT NULL
+            "#,
+            pubkeys_json
+        )
+        .fetch_all(executor)
+        .await?;
+
+        Ok(flairs)
+    }
+
+    pub async fn has_pseudonyms<'e, E>(executor: E, pubkey_hex: &str) -> Result<bool>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let count = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) AS "count!: i64" FROM users WHERE linked_pubkey_hex = ?"#,
+            pubkey_hex
+        )
+        .fetch_one(executor)
+        .await?;
+
+        Ok(count > 0)
+    }
+
+    /// Link a pseudonymous key to a main identity; a key can only be linked once
+    pub async fn link_pseudonym<'e, E>(
+        executor: E,
+        child_pubkey_hex: &str,
+        main_pubkey_hex: &str,
+        main_sig_hex: &str,
+        child_sig_hex: &str,
+    ) -> Result<bool>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let now = Utc::now();
+
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO users (
+                pubkey_hex, linked_pubkey_hex, link_main_sig_hex, link_child_sig_hex,
+                linked_at, created_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT (pubkey_hex) DO UPDATE SET
+                linked_pubkey_hex = excluded.linked_pubkey_hex,
+                link_main_sig_hex = excluded.link_main_sig_hex,
+                link_child_sig_hex = excluded.link_child_sig_hex,
+                linked_at = excluded.linked_at,
+                updated_at = excluded.updated_at
+            WHERE users.linked_pubkey_hex IS NULL
+            "#,
+            child_pubkey_hex,
+            main_pubkey_hex,
+            main_sig_hex,
+            child_sig_hex,
+            now,
+            now,
+            now
+        )
+        .execute(executor)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+impl DirectMessageRepository {
+    pub async fn create<'e, E>(
+        executor: E,
+        sender_pubkey_hex: &str,
+        recipient_pubkey_hex: &str,
+        ciphertext_hex: &str,
+        pow_nonce: i64,
+        pow_hash: &str,
+        pow_challenge_id: &str,
+    ) -> Result<i64>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let now = Utc::now();
+
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO direct_messages (
+                sender_pubkey_hex, recipient_pubkey_hex, ciphertext_hex,
+                pow_nonce, pow_hash, pow_challenge_id, created_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?)
+            "#,
+            sender_pubkey_hex,
+            recipient_pubkey_hex,
+            ciphertext_hex,
+            pow_nonce,
+            pow_hash,
+            pow_challenge_id,
+            now
+        )
+        .execute(executor)
+        .await?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    pub async fn list_for_recipient(
+        pool: &DbPool,
+        recipient_pubkey_hex: &str,
+        after_id: i64,
+        limit: i64,
+    ) -> Result<Vec<DirectMessage>> {
+        let messages = sqlx::query_as!(
+            DirectMessage,
+            r#"
+            SELECT id AS "id!: i64", sender_pubkey_hex, recipient_pubkey_hex, ciphertext_hex,
+                   pow_nonce, pow_hash, pow_challenge_id,
+                   created_at AS "created_at!: DateTime<Utc>"
+            FROM direct_messages
+            WHERE recipient_pubkey_hex = ? AND id > ?
+            ORDER BY id ASC
+            LIMIT ?
+            "#,
+            recipient_pubkey_hex,
+            after_id,
+            limit
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(messages)
+    }
+}
+
+impl NotificationRepository {
+    pub async fn create<'e, E>(
+        executor: E,
+        recipient_pubkey_hex: &str,
+        kind: &str,
+        thread_id: i64,
+        post_id: Option<i64>,
+        target_thread_id: i64,
+        target_post_id: Option<i64>,
+        actor_pubkey_hex: &str,
+    ) -> Result<i64>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let now = Utc::now();
+
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO notifications (
+                recipient_pubkey_hex, kind, thread_id, post_id, target_thread_id,
+                target_post_id, actor_pubkey_hex, created_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+            recipient_pubkey_hex,
+            kind,
+            thread_id,
+            post_id,
+            target_thread_id,
+            target_post_id,
+            actor_pubkey_hex,
+            now
+        )
+        .execute(executor)
+        .await?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    pub async fn list_for_recipient(
+        pool: &DbPool,
+        recipient_pubkey_hex: &str,
+        limit: i64,
+    ) -> Result<Vec<Notification>> {
+        let notifications = sqlx::query_as!(
+            Notification,
+            r#"
+            SELECT id AS "id!: i64", recipient_pubkey_hex, kind, thread_id, post_id,
+                   target_thread_id, target_post_id, actor_pubkey_hex,
+                   read_at AS "read_at?: DateTime<Utc>", created_at AS "created_at!: DateTime<Utc>"
+            FROM notifications
+            WHERE recipient_pubkey_hex = ?
+            ORDER BY id DESC
+            LIMIT ?
+            "#,
+            recipient_pubkey_hex,
+            limit
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(notifications)
+    }
+
+    pub async fn count_unread(pool: &DbPool, recipient_pubkey_hex: &str) -> Result<i64> {
+        let count = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) FROM notifications
+            WHERE recipient_pubkey_hex = ? AND read_at IS NULL
+            "#,
+            recipient_pubkey_hex
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(count as i64)
+    }
+
+    pub async fn mark_read(pool: &DbPool, recipient_pubkey_hex: &str, up_to_id: i64) -> Result<u64> {
+        let now = Utc::now();
+
+        let result = sqlx::query!(
+            r#"
+            UPDATE notifications
+            SET read_at = ?
+            WHERE recipient_pubkey_hex = ? AND id <= ? AND read_at IS NULL
+            "#,
+            now,
+            recipient_pubkey_hex,
+            up_to_id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+impl PostRefRepository {
+    pub async fn create<'e, E>(
+        executor: E,
+        from_thread_id: i64,
+        from_post_id: Option<i64>,
+        to_thread_id: i64,
+        to_post_id: Option<i64>,
+    ) -> Result<i64>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let now = Utc::now();
+
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO post_refs (from_thread_id, from_post_id, to_thread_id, to_post_id, created_at)
+            VALUES (?, ?, ?, ?, ?)
+            "#,
+            from_thread_id,
+            from_post_id,
+            to_thread_id,
+            to_post_id,
+            now
+        )
+        .execute(executor)
+        .await?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    /// Every edge that starts or ends in a thread, oldest first
+    pub async fn list_for_thread(pool: &DbPool, thread_id: i64) -> Result<Vec<PostRef>> {
+        let refs = sqlx::query_as!(
+            PostRef,
+            r#"
+            SELECT id AS "id!: i64", from_thread_id, from_post_id, to_thread_id, to_post_id,
+                   created_at AS "created_at!: DateTime<Utc>"
+            FROM post_refs
+            WHERE from_thread_id = ? OR to_thread_id = ?
+            ORDER BY id ASC
+            "#,
+            thread_id,
+            thread_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(refs)
+    }
+}
+
+
+impl AttachmentRepository {
+    pub async fn find<'e, E>(executor: E, sha256_hex: &str) -> Result<Option<Attachment>>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let attachment = sqlx::query_as!(
+            Attachment,
+            r#"
+            SELECT sha256_hex, mime_type, size_bytes, original_filename, width, height,
+                   thumb_width, thumb_height, created_at AS "created_at!: DateTime<Utc>"
+            FROM attachments
+            WHERE sha256_hex = ?
+            "#,
+            sha256_hex
+        )
+        .fetch_optional(executor)
+        .await?;
+
+        Ok(attachment)
+    }
+
+    /// Record an uploaded file; re-uploading the same bytes keeps the first record
+    pub async fn create<'e, E>(executor: E, attachment: &Attachment) -> Result<()>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query!(
+            r#"
+            INSERT OR IGNORE INTO attachments (
+                sha256_hex, mime_type, size_bytes, original_filename,
+                width, height, thumb_width, thumb_height, created_at
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+            attachment.sha256_hex,
+            attachment.mime_type,
+            attachment.size_bytes,
+            attachment.original_filename,
+            attachment.width,
+            attachment.height,
+            attachment.thumb_width,
+            attachment.thumb_height,
+            attachment.created_at
+        )
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Files uploaded before the cutoff that no post links to
+    pub async fn list_orphaned(pool: &DbPool, uploaded_before: DateTime<Utc>) -> Result<Vec<Attachment>> {
+        let attachments = sqlx::query_as!(
+            Attachment,
+            r#"
+            SELECT sha256_hex, mime_type, size_bytes, original_filename, width, height,
+                   thumb_width, thumb_height, created_at AS "created_at!: DateTime<Utc>"
+            FROM attachments
+            WHERE created_at < ?
+              AND NOT EXISTS (
+                  SELECT 1 FROM post_attachments WHERE post_attachments.sha256_hex = attachments.sha256_hex
+              )
+            "#,
+            uploaded_before
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(attachments)
+    }
+
+    /// Drop a file's record if still no post links to it; false if a post took it meanwhile
+    pub async fn delete_orphan<'e, E>(executor: E, sha256_hex: &str) -> Result<bool>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM attachments
+            WHERE sha256_hex = ?
+              AND NOT EXISTS (
+                  SELECT 1 FROM post_attachments WHERE post_attachments.sha256_hex = attachments.sha256_hex
+              )
+            "#,
+            sha256_hex
+        )
+        .execute(executor)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn link<'e, E>(
+        executor: E,
+        thread_id: i64,
+        post_id: Option<i64>,
+        sha256_hex: &str,
+        position: i64,
+    ) -> Result<()>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let now = Utc::now();
+
+        sqlx::query!(
+            r#"
+            INSERT INTO post_attachments (thread_id, post_id, sha256_hex, position, created_at)
+            VALUES (?, ?, ?, ?, ?)
+            "#,
+            thread_id,
+            post_id,
+            sha256_hex,
+            position,
+            now
+        )
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Detach a deleted post's files; the files stay, since other posts may share them
+    pub async fn unlink<'e, E>(executor: E, thread_id: i64, post_id: Option<i64>) -> Result<()>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query!(
+            r#"
+            DELETE FROM post_attachments
+            WHERE thread_id = ? AND post_id IS ?
+            "#,
+            thread_id,
+            post_id
+        )
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Hashes of the files linked to one post, in draft order
+    pub async fn hashes_for_post<'e, E>(executor: E, thread_id: i64, post_id: Option<i64>) -> Result<Vec<String>>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let hashes = sqlx::query_scalar!(
+            r#"
+            SELECT sha256_hex FROM post_attachments
+            WHERE thread_id = ? AND post_id IS ?
+            ORDER BY position
+            "#,
+            thread_id,
+            post_id
+        )
+        .fetch_all(executor)
+        .await?;
+
+        Ok(hashes)
+    }
+
+    /// Attachments of a thread's opening post and replies, in post order
+    pub async fn list_for_thread(pool: &DbPool, thread_id: i64) -> Result<Vec<PostAttachment>> {
+        let attachments = sqlx::query_as!(
+            PostAttachment,
+            r#"
+            SELECT pa.thread_id, pa.post_id, pa.position,
+                   a.sha256_hex AS "sha256_hex!", a.mime_type AS "mime_type!",
+                   a.size_bytes AS "size_bytes!", a.original_filename,
+                   a.width AS "width!", a.height AS "height!",
+                   a.thumb_width AS "thumb_width!", a.thumb_height AS "thumb_height!"
+            FROM post_attachments pa
+            JOIN attachments a ON a.sha256_hex = pa.sha256_hex
+            WHERE pa.thread_id = ?
+            ORDER BY pa.post_id, pa.position
+            "#,
+            thread_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(attachments)
+    }
+}
+
+impl ContentHashRepository {
+    pub async fn exists<'e, E>(executor: E, board_id: i64, content_hash: &str) -> Result<bool>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let row = sqlx::query!(
+            r#"
+            SELECT thread_id FROM content_hashes
+            WHERE board_id = ? AND content_hash = ?
+            "#,
+            board_id,
+            content_hash
+        )
+        .fetch_optional(executor)
+        .await?;
+
+        Ok(row.is_some())
+    }
+
+    /// Whether a content hash was recorded for this very post
+    pub async fn owned_by<'e, E>(
+        executor: E,
+        board_id: i64,
+        content_hash: &str,
+        thread_id: i64,
+        post_id: Option<i64>,
+    ) -> Result<bool>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let row = sqlx::query!(
+            r#"
+            SELECT thread_id FROM content_hashes
+            WHERE board_id = ? AND content_hash = ? AND thread_id = ? AND post_id IS ?
+            "#,
+            board_id,
+            content_hash,
+            thread_id,
+            post_id
+        )
+        .fetch_optional(executor)
+        .await?;
+
+        Ok(row.is_some())
+    }
+
+    pub async fn record<'e, E>(
+        executor: E,
+        board_id: i64,
+        content_hash: &str,
+        thread_id: i64,
+        post_id: Option<i64>,
+    ) -> Result<()>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let now = Utc::now();
+
+        sqlx::query!(
+            r#"
+            INSERT INTO content_hashes (board_id, content_hash, thread_id, post_id, created_at)
+            VALUES (?, ?, ?, ?, ?)
+            "#,
+            board_id,
+            content_hash,
+            thread_id,
+            post_id,
+            now
+        )
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+}
+
+impl RevisionRepository {
+    /// Number the next revision of a post would get; 0 if it has never been edited
+    pub async fn next_revision<'e, E>(executor: E, thread_id: i64, post_id: Option<i64>) -> Result<i64>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let row = sqlx::query!(
+            r#"
+            SELECT COALESCE(MAX(revision) + 1, 0) AS "next!: i64"
+            FROM post_revisions
+            WHERE thread_id = ? AND post_id IS ?
+            "#,
+            thread_id,
+            post_id
+        )
+        .fetch_one(executor)
+        .await?;
+
+        Ok(row.next)
+    }
+
+    pub async fn create<'e, E>(executor: E, revision: &PostRevision) -> Result<()>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query!(
+            r#"
+            INSERT INTO post_revisions (
+                thread_id, post_id, revision, title, content, author_pubkey, signature_hex,
+                pow_nonce, pow_hash, pow_challenge_id, pow_difficulty, created_at
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+            revision.thread_id,
+            revision.post_id,
+            revision.revision,
+            revision.title,
+            revision.content,
+            revision.author_pubkey,
+            revision.signature_hex,
+            revision.pow_nonce,
+            revision.pow_hash,
+            revision.pow_challenge_id,
+            revision.pow_difficulty,
+            revision.created_at
+        )
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Blank the text of a deleted post's revisions, keeping their signatures and proofs
+    pub async fn redact<'e, E>(executor: E, thread_id: i64, post_id: Option<i64>) -> Result<()>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query!(
+            r#"
+            UPDATE post_revisions
+            SET title = NULL, content = ''
+            WHERE thread_id = ? AND post_id IS ?
+            "#,
+            thread_id,
+            post_id
+        )
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Revisions of one post, oldest first
+    pub async fn list_for_post(pool: &DbPool, thread_id: i64, post_id: Option<i64>) -> Result<Vec<PostRevision>> {
+        let revisions = sqlx::query_as!(
+            PostRevision,
+            r#"
+            SELECT id AS "id!: i64", thread_id, post_id, revision, title, content, author_pubkey,
+                   signature_hex, pow_nonce, pow_hash, pow_challenge_id, pow_difficulty,
+                   created_at AS "created_at!: DateTime<Utc>"
+            FROM post_revisions
+            WHERE thread_id = ? AND post_id IS ?
+            ORDER BY revision ASC
+            "#,
+            thread_id,
+            post_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(revisions)
+    }
+}
+
+impl DeletedContentRepository {
+    pub async fn create<'e, E>(executor: E, deleted: &DeletedContent) -> Result<()>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query!(
+            r#"
+            INSERT INTO deleted_content (
+                thread_id, post_id, title, content, image_path, image_filename,
+                author_pubkey, deleted_at, purge_after
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+            deleted.thread_id,
+            deleted.post_id,
+            deleted.title,
+            deleted.content,
+            deleted.image_path,
+            deleted.image_filename,
+            deleted.author_pubkey,
+            deleted.deleted_at,
+            deleted.purge_after
+        )
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Drop deleted content past its retention window
+    pub async fn purge_expired<'e, E>(executor: E) -> Result<u64>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let now = Utc::now();
+
+        let result = sqlx::query!(
+            r#"
+            DELETE FROM deleted_content
+            WHERE purge_after <= ?
+            "#,
+            now
+        )
+        .execute(executor)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Retained deleted content, newest deletion first
+    pub async fn list(pool: &DbPool, limit: i64, offset: i64) -> Result<Vec<DeletedContent>> {
+        let deleted = sqlx::query_as!(
+            DeletedContent,
+            r#"
+            SELECT id AS "id!: i64", thread_id, post_id, title, content, image_path,
+                   image_filename, author_pubkey, deleted_at AS "deleted_at!: DateTime<Utc>",
+                   purge_after AS "purge_after!: DateTime<Utc>"
+            FROM deleted_content
+            ORDER BY deleted_at DESC, id DESC
+            LIMIT ? OFFSET ?
+            "#,
+            limit,
+            offset
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(deleted)
+    }
+}
+
+impl PollRepository {
+    pub async fn create<'e, E>(executor: E, thread_id: i64, question: &str) -> Result<i64>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let now = Utc::now();
+
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO polls (thread_id, question, created_at)
+            VALUES (?, ?, ?)
+            "#,
+            thread_id,
+            question,
+            now
+        )
+        .execute(executor)
+        .await?;
+
+        Ok(result.last_insert_rowid())
+    }
+
+    pub async fn add_option<'e, E>(executor: E, poll_id: i64, position: i64, label: &str) -> Result<()>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query!(
+            r#"
+            INSERT INTO poll_options (poll_id, position, label)
+            VALUES (?, ?, ?)
+            "#,
+            poll_id,
+            position,
+            label
+        )
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn find_by_id<'e, E>(executor: E, id: i64) -> Result<Option<Poll>>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let poll = sqlx::query_as!(
+            Poll,
+            r#"
+            SELECT id AS "id!: i64", thread_id, question,
+                   created_at AS "created_at!: DateTime<Utc>"
+            FROM polls
+            WHERE id = ?
+            "#,
+            id
+        )
+        .fetch_optional(executor)
+        .await?;
+
+        Ok(poll)
+    }
+
+    pub async fn find_by_thread(pool: &DbPool, thread_id: i64) -> Result<Option<Poll>> {
+        let poll = sqlx::query_as!(
+            Poll,
+            r#"
+            SELECT id AS "id!: i64", thread_id, question,
+                   created_at AS "created_at!: DateTime<Utc>"
+            FROM polls
+            WHERE thread_id = ?
+            "#,
+            thread_id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(poll)
+    }
+
+    pub async fn find_option<'e, E>(executor: E, poll_id: i64, option_id: i64) -> Result<Option<PollOption>>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let option = sqlx::query_as!(
+            PollOption,
+            r#"
+            SELECT id, poll_id, position, label
+            FROM poll_options
+            WHERE poll_id = ? AND id = ?
+            "#,
+            poll_id,
+            option_id
+        )
+        .fetch_optional(executor)
+        .await?;
+
+        Ok(option)
+    }
+
+    pub async fn has_voted<'e, E>(executor: E, poll_id: i64, voter_pubkey: &str) -> Result<bool>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let row = sqlx::query!(
+            r#"
+            SELECT id FROM poll_votes
+            WHERE poll_id = ? AND voter_pubkey = ?
+            "#,
+            poll_id,
+            voter_pubkey
+        )
+        .fetch_optional(executor)
+        .await?;
+
+        Ok(row.is_some())
+    }
+
+    pub async fn record_vote<'e, E>(executor: E, vote: &PollVote) -> Result<()>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query!(
+            r#"
+            INSERT INTO poll_votes (
+                poll_id, option_id, voter_pubkey, signature_hex,
+                pow_nonce, pow_hash, pow_challenge_id, pow_difficulty, created_at
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+            vote.poll_id,
+            vote.option_id,
+            vote.voter_pubkey,
+            vote.signature_hex,
+            vote.pow_nonce,
+            vote.pow_hash,
+            vote.pow_challenge_id,
+            vote.pow_difficulty,
+            vote.created_at
+        )
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Every option of a poll in order, with its voter count and summed vote difficulty
+    pub async fn tally(pool: &DbPool, poll_id: i64) -> Result<Vec<PollTally>> {
+        let tally = sqlx::query_as!(
+            PollTally,
+            r#"
+            SELECT o.id AS "option_id!: i64", o.position AS "position!: i64", o.label AS "label!: String",
+                   COUNT(v.id) AS "voters!: i64",
+                   COALESCE(SUM(v.pow_difficulty), 0.0) AS "work!: f64"
+            FROM poll_options o
+            LEFT JOIN poll_votes v ON v.option_id = o.id
+            WHERE o.poll_id = ?
+            GROUP BY o.id
+            ORDER BY o.position ASC
+            "#,
+            poll_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(tally)
+    }
+}
+
+impl ReactionRepository {
+    pub async fn create<'e, E>(executor: E, reaction: &Reaction) -> Result<()>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query!(
+            r#"
+            INSERT INTO reactions (
+                thread_id, post_id, kind, reactor_pubkey,
+                pow_nonce, pow_hash, pow_challenge_id, pow_difficulty, created_at
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+            reaction.thread_id,
+            reaction.post_id,
+            reaction.kind,
+            reaction.reactor_pubkey,
+            reaction.pow_nonce,
+            reaction.pow_hash,
+            reaction.pow_challenge_id,
+            reaction.pow_difficulty,
+            reaction.created_at
+        )
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Whether a pubkey has reacted to a post since `since`
+    pub async fn reacted_since<'e, E>(
+        executor: E,
+        thread_id: i64,
+        post_id: Option<i64>,
+        reactor_pubkey: &str,
+        since: DateTime<Utc>,
+    ) -> Result<bool>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        let row = sqlx::query!(
+            r#"
+            SELECT id FROM reactions
+            WHERE thread_id = ? AND post_id IS ? AND reactor_pubkey = ? AND created_at > ?
+            LIMIT 1
+            "#,
+            thread_id,
+            post_id,
+            reactor_pubkey,
+            since
+        )
+        .fetch_optional(executor)
+        .await?;
+
+        Ok(row.is_some())
+    }
+
+    pub async fn list_for_thread(pool: &DbPool, thread_id: i64) -> Result<Vec<Reaction>> {
+        let reactions = sqlx::query_as!(
+            Reaction,
+            r#"
+            SELECT id AS "id!: i64", thread_id, post_id, kind, reactor_pubkey, pow_nonce, pow_hash,
+                   pow_challenge_id, pow_difficulty, created_at AS "created_at!: DateTime<Utc>"
+            FROM reactions
+            WHERE thread_id = ?
+            ORDER BY id ASC
+            "#,
+            thread_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(reactions)
+    }
+}
+
+impl BoostRepository {
+    pub async fn create<'e, E>(executor: E, boost: &ThreadBoost) -> Result<()>
+    where
+        E: Executor<'e, Database = Sqlite>,
+    {
+        sqlx::query!(
+            r#"
+            INSERT INTO thread_boosts (
+                thread_id, booster_pubkey, pow_nonce, pow_hash, pow_challenge_id, pow_difficulty, created_at
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            "#,
+            boost.thread_id,
+            boost.booster_pubkey,
+            boost.pow_nonce,
+            boost.pow_hash,
+            boost.pow_challenge_id,
+            boost.pow_difficulty,
+            boost.created_at
+        )
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Everyone who boosted a thread, most work first
+    pub async fn contributors(pool: &DbPool, thread_id: i64) -> Result<Vec<BoostContributor>> {
+        let contributors = sqlx::query_as!(
+            BoostContributor,
+            r#"
+            SELECT booster_pubkey AS "booster_pubkey!: String",
+                   COUNT(*) AS "boosts!: i64",
+                   SUM(pow_difficulty) AS "work!: f64"
+            FROM thread_boosts
+            WHERE thread_id = ?
+            GROUP BY booster_pubkey
+            ORDER BY SUM(pow_difficulty) DESC, MIN(id) ASC
+            "#,
+            thread_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(contributors)
+    }
 }
\ No newline at end of file
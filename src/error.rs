@@ -22,9 +22,57 @@ pub enum AppError {
     #[error("Challenge not found")]
     ChallengeNotFound,
     
+    #[error("Challenge already used")]
+    ChallengeUsed,
+    
+    #[error("Operation id reused with a different request")]
+    OpConflict,
+    
     #[error("Invalid public key")]
     InvalidPublicKey,
     
+    #[error("Unauthorized")]
+    Unauthorized,
+    
+    #[error("Invalid signature")]
+    InvalidSignature,
+    
+    #[error("Thread not found")]
+    ThreadNotFound,
+    
+    #[error("Thread is locked")]
+    ThreadLocked,
+    
+    #[error("Thread is archived")]
+    ThreadArchived,
+    
+    #[error("Parent post not found")]
+    ParentNotFound,
+    
+    #[error("Parent post belongs to a different thread")]
+    ParentNotInThread,
+    
+    #[error("Quoted post not found")]
+    RefNotFound,
+    
+    #[error("This content was already posted on this board")]
+    DuplicateContent,
+    
+    #[error("Post was deleted")]
+    PostDeleted,
+    
+    #[error("Already voted in this poll")]
+    AlreadyVoted,
+    
+    #[error("Reacted to this post too recently")]
+    ReactionRateLimited,
+    
+    #[error("Timestamp must be in seconds")]
+    TimestampUnit,
+    
+    #[error("Timestamp is too far from server time")]
+    TimestampSkew,
+    
     #[error("Validation error: {0}")]
     Validation(String),
     
@@ -43,7 +91,23 @@ impl IntoResponse for AppError {
             AppError::InvalidProofOfWork => (StatusCode::BAD_REQUEST, "Invalid proof of work"),
             AppError::ChallengeExpired => (StatusCode::BAD_REQUEST, "Challenge expired"),
             AppError::ChallengeNotFound => (StatusCode::NOT_FOUND, "Challenge not found"),
+            AppError::ChallengeUsed => (StatusCode::CONFLICT, "Challenge already used"),
+            AppError::OpConflict => (StatusCode::CONFLICT, "Operation id reused with a different request"),
             AppError::InvalidPublicKey => (StatusCode::UNAUTHORIZED, "Invalid public key"),
+            AppError::Unauthorized => (StatusCode::UNAUTHORIZED, "Unauthorized"),
+            AppError::InvalidSignature => (StatusCode::UNAUTHORIZED, "Invalid signature"),
+            AppError::ThreadNotFound => (StatusCode::NOT_FOUND, "Thread not found"),
+            AppError::ThreadLocked => (StatusCode::FORBIDDEN, "Thread is locked"),
+            AppError::ThreadArchived => (StatusCode::FORBIDDEN, "Thread is archived and read-only"),
+            AppError::ParentNotFound => (StatusCode::NOT_FOUND, "Parent post not found"),
+            AppError::ParentNotInThread => (StatusCode::BAD_REQUEST, "Parent post belongs to a different thread"),
+            AppError::RefNotFound => (StatusCode::NOT_FOUND, "Quoted post not found"),
+            AppError::DuplicateContent => (StatusCode::CONFLICT, "This content was already posted on this board"),
+            AppError::PostDeleted => (StatusCode::GONE, "Post was deleted"),
+            AppError::AlreadyVoted => (StatusCode::CONFLICT, "Already voted in this poll"),
+            AppError::ReactionRateLimited => (StatusCode::TOO_MANY_REQUESTS, "Reacted to this post too recently"),
+            AppError::TimestampUnit => (StatusCode::BAD_REQUEST, "Timestamp must be in seconds"),
+            AppError::TimestampSkew => (StatusCode::BAD_REQUEST, "Timestamp is too far from server time"),
             AppError::Validation(ref msg) => (StatusCode::BAD_REQUEST, msg.as_str()),
             AppError::NotFound => (StatusCode::NOT_FOUND, "Not found"),
             AppError::Internal => (StatusCode::INTERNAL_SERVER_ERROR, "Internal server error"),
@@ -1,15 +1,30 @@
-use axum::{extract::State, Json};
-use serde::{Deserialize, Serialize};
+use axum::{extract::{Path, Query, State}, Json};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::json;
 use uuid::Uuid;
 use sha2::Digest;
+use sqlx::SqliteConnection;
 
 use crate::{
+    attachments::is_valid_hash,
     config::Config,
-    db::{DbPool, PowRepository},
+    db::{
+        AttachmentRepository, BoardRepository, ContentHashRepository, DbPool, NotificationRepository,
+        PollRepository, PostRefRepository, PostRepository, PowRepository, ThreadRepository, UserRepository,
+    },
     error::{AppError, Result},
-    models::{OpReceipt, PowChallenge, PowCommit},
-    pow::{canonical_bytes_v1, verify_proof_v1, CanonicalParams, PostDraft, ProofOfWork},
+    identity::{auth_message, flair_message, parse_pubkey, verify_link_proof, verify_signature},
+    handlers::quotes::{resolve_links, resolve_quotes},
+    markup::{escape_html, render_post_with, Quote, QuoteTarget},
+    models::{Attachment, Board, OpReceipt, PostRef, Thread, PowChallenge, PowCommit},
+    polls::poll_errors,
+    pow::{
+        calculate_pow_difficulty, canonical_bytes_v1, is_valid_vanity_pattern, sha256_hex,
+        verify_personal_vanity, verify_proof_v1, CanonicalParams, PostDraft, ProofOfWork,
+    },
+    reputation::ReputationPolicy,
+    tree::{build_reply_tree, ReplyNode, SiblingOrder, DEFAULT_TREE_DEPTH, MAX_TREE_DEPTH},
+    uniqueness::content_hash,
 };
 
 #[derive(Serialize)]
@@ -23,9 +38,13 @@ pub struct PowParams {
 #[derive(Deserialize)]
 pub struct ThreadBeginRequest {
     pub client_op_id: Uuid,
+    pub board_id: i64,
     pub post_draft: PostDraft,
     pub user_pubkey_hex: String,
     pub timestamp_i64: i64,
+    /// Signature over `auth_message("discount", ..)`, required for a reputation discount
+    #[serde(default)]
+    pub discount_signature_hex: Option<String>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -48,7 +67,7 @@ pub struct ThreadCommitRequest {
     pub user_pubkey_hex: String,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize)]
 pub struct ThreadCommitResponse {
     pub thread_id: i64,
 }
@@ -61,6 +80,9 @@ pub struct ReplyBeginRequest {
     pub thread_id: i64,
     pub parent_id: Option<i64>,
     pub timestamp_i64: i64,
+    /// Signature over `auth_message("discount", ..)`, required for a reputation discount
+    #[serde(default)]
+    pub discount_signature_hex: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -74,11 +96,403 @@ pub struct ReplyCommitRequest {
     pub parent_id: Option<i64>,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize)]
 pub struct ReplyCommitResponse {
     pub post_id: i64,
 }
 
+#[derive(Deserialize)]
+pub struct VanityFlairRequest {
+    pub user_pubkey_hex: String,
+    pub pattern: String,
+    pub nonce_u64: u64,
+    pub timestamp_i64: i64,
+    /// Signature over `flair_message`, so only the key's owner can set its flair
+    pub signature_hex: String,
+}
+
+#[derive(Serialize)]
+pub struct VanityFlairResponse {
+    pub user_pubkey_hex: String,
+    pub vanity_prefix: String,
+    pub vanity_hash: String,
+}
+
+/// Signature by a key over a fresh timestamp, proving the caller owns it
+#[derive(Deserialize)]
+pub struct SignedAuth {
+    pub user_pubkey_hex: String,
+    pub timestamp_i64: i64, // seconds
+    pub signature_hex: String,
+}
+
+impl SignedAuth {
+    pub fn verify(&self, purpose: &str) -> Result<()> {
+        let config = Config::new().unwrap();
+
+        let skew = (chrono::Utc::now().timestamp() - self.timestamp_i64).abs();
+        if skew > config.auth_max_skew_seconds {
+            return Err(AppError::Validation("Authentication timestamp is stale".to_string()));
+        }
+
+        let message = auth_message(purpose, &self.user_pubkey_hex, self.timestamp_i64);
+        if !verify_signature(&self.user_pubkey_hex, &message, &self.signature_hex) {
+            return Err(AppError::InvalidSignature);
+        }
+
+        Ok(())
+    }
+}
+
+/// Look up the target board of a new thread, which must still be accepting threads
+pub(crate) async fn require_active_board(pool: &DbPool, board_id: i64) -> Result<Board> {
+    let board = BoardRepository::find_by_id(pool, board_id)
+        .await?
+        .ok_or(AppError::NotFound)?;
+
+    if !board.is_active {
+        return Err(AppError::Validation("Board is not accepting new threads".to_string()));
+    }
+
+    Ok(board)
+}
+
+/// Largest plausible unix timestamp in seconds; millisecond values are far above it
+const MAX_TIMESTAMP_SECONDS: i64 = 100_000_000_000;
+
+/// Check a client timestamp is in seconds and close to server time, allowing
+/// `max_age_seconds` for time spent mining since it was taken
+pub(crate) fn validate_timestamp(timestamp_i64: i64, max_age_seconds: i64) -> Result<()> {
+    if timestamp_i64 > MAX_TIMESTAMP_SECONDS {
+        return Err(AppError::TimestampUnit);
+    }
+
+    let config = Config::new().unwrap();
+    let now = chrono::Utc::now().timestamp();
+    let skew = config.pow_max_clock_skew_seconds;
+
+    if timestamp_i64 > now + skew || timestamp_i64 < now - skew - max_age_seconds {
+        return Err(AppError::TimestampSkew);
+    }
+
+    Ok(())
+}
+
+/// Check a reply's thread exists and is open (not locked or archived), and that its parent is a post in that thread.
+/// Foreign keys aren't enforced by SQLite here, so this is the only guard.
+pub(crate) async fn validate_reply_target(
+    conn: &mut SqliteConnection,
+    thread_id: i64,
+    parent_id: Option<i64>,
+) -> Result<Thread> {
+    let thread = ThreadRepository::find_by_id(&mut *conn, thread_id)
+        .await?
+        .ok_or(AppError::ThreadNotFound)?;
+
+    if thread.is_locked {
+        return Err(AppError::ThreadLocked);
+    }
+
+    if thread.is_archived {
+        return Err(AppError::ThreadArchived);
+    }
+
+    if let Some(parent_id) = parent_id {
+        let parent = PostRepository::find_by_id(&mut *conn, parent_id)
+            .await?
+            .ok_or(AppError::ParentNotFound)?;
+
+        if parent.thread_id != thread.id {
+            return Err(AppError::ParentNotInThread);
+        }
+    }
+
+    Ok(thread)
+}
+
+/// Fingerprint of everything a commit asks for, to tell an honest retry from op id reuse
+pub(crate) fn commit_request_hash(request: serde_json::Value) -> Result<String> {
+    Ok(sha256_hex(&serde_json::to_vec(&request)?))
+}
+
+/// Return the stored result of an earlier commit with the same op id, if it was the same request
+pub(crate) fn replay_commit<T: DeserializeOwned>(receipt: &OpReceipt, request_hash: &str) -> Result<T> {
+    if receipt.request_hash.as_deref() != Some(request_hash) {
+        return Err(AppError::OpConflict);
+    }
+
+    serde_json::from_str(&receipt.result_json).map_err(|_| AppError::Internal)
+}
+
+/// Required prefix for a key, discounted by its standing under the reputation policy.
+/// The discount only applies when the caller proves it owns the key by signing
+/// `auth_message("discount", ..)` over the begin timestamp; anyone else pays the default.
+pub(crate) async fn required_prefix_for(
+    pool: &DbPool,
+    config: &Config,
+    pubkey_hex: &str,
+    timestamp_i64: i64,
+    discount_signature_hex: Option<&str>,
+) -> Result<String> {
+    let Some(signature_hex) = discount_signature_hex else {
+        return Ok(config.pow_default_prefix.clone());
+    };
+    let message = auth_message("discount", pubkey_hex, timestamp_i64);
+    if !verify_signature(pubkey_hex, &message, signature_hex) {
+        return Err(AppError::InvalidSignature);
+    }
+
+    let user = UserRepository::find_by_pubkey(pool, pubkey_hex).await?;
+    let policy = ReputationPolicy::from_config(config);
+
+    Ok(policy.required_prefix(&config.pow_default_prefix, user.as_ref(), chrono::Utc::now()))
+}
+
+/// Credit verified work to the author's totals and decaying reputation
+async fn record_user_work(
+    conn: &mut SqliteConnection,
+    pubkey_hex: &str,
+    difficulty: f64,
+    is_thread: bool,
+) -> Result<()> {
+    let config = Config::new().unwrap();
+    let policy = ReputationPolicy::from_config(&config);
+
+    let user = UserRepository::find_by_pubkey(&mut *conn, pubkey_hex).await?;
+    let reputation_work = policy.accrue(user.as_ref(), difficulty, chrono::Utc::now());
+
+    UserRepository::record_work(&mut *conn, pubkey_hex, difficulty, reputation_work, is_thread).await
+}
+
+#[derive(Deserialize)]
+pub struct LinkPseudonymRequest {
+    pub main_pubkey_hex: String,
+    pub child_pubkey_hex: String,
+    pub main_sig_hex: String,
+    pub child_sig_hex: String,
+}
+
+#[derive(Serialize)]
+pub struct LinkPseudonymResponse {
+    pub main_pubkey_hex: String,
+    pub child_pubkey_hex: String,
+    pub linked_at: String,
+}
+
+/// Tell the authors of any quoted posts or threads about a new post, and for a reply
+/// the author of its parent post, or of the thread for a direct reply. A new thread's
+/// opening post (no post id) only notifies its quotes. Board quotes can reach other threads.
+async fn record_notifications(
+    conn: &mut SqliteConnection,
+    thread_id: i64,
+    post_id: Option<i64>,
+    actor_pubkey_hex: &str,
+    parent_id: Option<i64>,
+    quoted: &[QuoteTarget],
+) -> Result<()> {
+    let mut targets = Vec::with_capacity(quoted.len() + 1);
+    if post_id.is_some() {
+        targets.push((QuoteTarget { thread_id, post_id: parent_id }, "reply"));
+    }
+    for target in quoted {
+        if !targets.iter().any(|(seen, _)| seen == target) {
+            targets.push((*target, "quote"));
+        }
+    }
+
+    for (target, kind) in targets {
+        let author = match target.post_id {
+            Some(id) => PostRepository::find_by_id(&mut *conn, id)
+                .await?
+                .and_then(|post| post.author_pubkey),
+            None => ThreadRepository::find_by_id(&mut *conn, target.thread_id)
+                .await?
+                .and_then(|thread| thread.author_pubkey),
+        };
+        let Some(recipient) = author else {
+            continue;
+        };
+        if recipient == actor_pubkey_hex {
+            continue;
+        }
+
+        NotificationRepository::create(
+            &mut *conn,
+            &recipient,
+            kind,
+            thread_id,
+            post_id,
+            target.thread_id,
+            target.post_id,
+            actor_pubkey_hex,
+        )
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Everything wrong with a draft's own content, independent of where it's posted
+fn draft_errors(config: &Config, is_thread: bool, post_draft: &PostDraft) -> Vec<String> {
+    let mut errors = Vec::new();
+    let title_chars = post_draft.title.chars().count();
+    let body_chars = post_draft.body.chars().count();
+
+    if is_thread && post_draft.title.trim().is_empty() {
+        errors.push("Threads need a title".to_string());
+    }
+    if title_chars > config.post_max_title_chars {
+        errors.push(format!("Title is {} characters, the limit is {}", title_chars, config.post_max_title_chars));
+    }
+    if post_draft.body.trim().is_empty() && post_draft.attachments.is_empty() {
+        errors.push("Post body is empty".to_string());
+    }
+    if body_chars > config.post_max_body_chars {
+        errors.push(format!("Body is {} characters, the limit is {}", body_chars, config.post_max_body_chars));
+    }
+
+    match post_draft.parse_refs() {
+        None => errors.push("Refs must be post ids or board quotes".to_string()),
+        Some(ids) if ids.len() > MAX_REFS => errors.push(format!("At most {} refs per post", MAX_REFS)),
+        Some(_) => {}
+    }
+
+    if post_draft.attachments.len() > config.post_max_attachments {
+        errors.push(format!("At most {} attachments per post", config.post_max_attachments));
+    }
+    if post_draft.attachments.iter().any(|hash| !is_valid_hash(hash)) {
+        errors.push("Attachments must be the SHA-256 returned by the upload endpoint".to_string());
+    }
+
+    match &post_draft.poll {
+        Some(_) if !is_thread => errors.push("Polls can only open a thread".to_string()),
+        Some(poll) => errors.extend(poll_errors(poll)),
+        None => {}
+    }
+
+    errors
+}
+
+/// Why an uploaded file can't go on a board, if it can't
+fn attachment_error(board: &Board, attachment: &Attachment) -> Option<String> {
+    if !board.accepts_mime_type(&attachment.mime_type) {
+        Some(format!("/{}/ doesn't accept {}", board.slug, attachment.mime_type))
+    } else if attachment.size_bytes > board.max_upload_bytes {
+        Some(format!("/{}/ accepts files up to {} bytes", board.slug, board.max_upload_bytes))
+    } else {
+        None
+    }
+}
+
+/// Check every attachment in a draft was uploaded and suits the board it's being posted to.
+/// Files are shared across boards by hash, so the upload-time check alone isn't enough.
+async fn validate_attachments(
+    conn: &mut SqliteConnection,
+    board: &Board,
+    post_draft: &PostDraft,
+) -> Result<Vec<Attachment>> {
+    let mut attachments = Vec::with_capacity(post_draft.attachments.len());
+
+    for sha256_hex in &post_draft.attachments {
+        let attachment = AttachmentRepository::find(&mut *conn, sha256_hex)
+            .await?
+            .ok_or_else(|| AppError::Validation(format!("Attachment {} was never uploaded", sha256_hex)))?;
+        if let Some(error) = attachment_error(board, &attachment) {
+            return Err(AppError::Validation(error));
+        }
+        attachments.push(attachment);
+    }
+
+    Ok(attachments)
+}
+
+/// On a board in uniqueness mode, the draft's content hash, refused if the board has seen it.
+/// Checked at begin so nobody mines for a post that can't land, and again inside the commit.
+pub(crate) async fn check_unique_content(
+    conn: &mut SqliteConnection,
+    board: &Board,
+    post_draft: &PostDraft,
+) -> Result<Option<String>> {
+    if !board.unique_content {
+        return Ok(None);
+    }
+
+    let hash = content_hash(&board.normalization(), &post_draft.body, &post_draft.attachments);
+    if ContentHashRepository::exists(&mut *conn, board.id, &hash).await? {
+        return Err(AppError::DuplicateContent);
+    }
+
+    Ok(Some(hash))
+}
+
+/// Link a new post's attachments to it, in draft order
+async fn link_attachments(
+    conn: &mut SqliteConnection,
+    thread_id: i64,
+    post_id: Option<i64>,
+    attachments: &[Attachment],
+) -> Result<()> {
+    for (position, attachment) in attachments.iter().enumerate() {
+        AttachmentRepository::link(&mut *conn, thread_id, post_id, &attachment.sha256_hex, position as i64).await?;
+    }
+
+    if let Some(first) = attachments.first() {
+        match post_id {
+            Some(post_id) => PostRepository::set_image(&mut *conn, post_id, first).await?,
+            None => ThreadRepository::set_image(&mut *conn, thread_id, first).await?,
+        }
+    }
+
+    Ok(())
+}
+
+pub(crate) fn validate_draft(config: &Config, is_thread: bool, post_draft: &PostDraft) -> Result<()> {
+    match draft_errors(config, is_thread, post_draft).into_iter().next() {
+        Some(error) => Err(AppError::Validation(error)),
+        None => Ok(()),
+    }
+}
+
+const MAX_REFS: usize = 32;
+
+/// Parse a draft's refs and check each one resolves, returning where they point.
+/// Refs are hashed into the proof, so a bad one fails the whole commit rather than being dropped.
+async fn validate_refs(conn: &mut SqliteConnection, post_draft: &PostDraft) -> Result<Vec<QuoteTarget>> {
+    let quotes = post_draft
+        .parse_refs()
+        .ok_or_else(|| AppError::Validation("Refs must be post ids or board quotes".to_string()))?;
+
+    if quotes.len() > MAX_REFS {
+        return Err(AppError::Validation(format!("At most {} refs per post", MAX_REFS)));
+    }
+
+    let targets = resolve_quotes(&mut *conn, &quotes).await?;
+    let mut quoted = Vec::with_capacity(quotes.len());
+    for quote in &quotes {
+        let target = *targets.get(quote).ok_or(AppError::RefNotFound)?;
+        // ">>>/b/5" and ">>5" may name the same post; keep one edge
+        if !quoted.contains(&target) {
+            quoted.push(target);
+        }
+    }
+
+    Ok(quoted)
+}
+
+/// Store the quote edges from a new post (or a thread's opening post, when `from_post_id` is None)
+async fn record_refs(
+    conn: &mut SqliteConnection,
+    from_thread_id: i64,
+    from_post_id: Option<i64>,
+    quoted: &[QuoteTarget],
+) -> Result<()> {
+    for target in quoted {
+        PostRefRepository::create(&mut *conn, from_thread_id, from_post_id, target.thread_id, target.post_id).await?;
+    }
+
+    Ok(())
+}
+
 pub async fn pow_params(State(_pool): State<DbPool>) -> Result<Json<PowParams>> {
     let config = Config::new().unwrap();
     
@@ -95,23 +509,27 @@ pub async fn thread_begin(
     Json(req): Json<ThreadBeginRequest>,
 ) -> Result<Json<ThreadBeginResponse>> {
     // Check for existing operation receipt
-    if let Some(receipt) = PowRepository::find_op_receipt(&pool, &req.client_op_id.to_string()).await? {
+    if let Some(receipt) = PowRepository::find_op_receipt(&pool, &req.client_op_id.to_string(), "thread_begin").await? {
         let response: ThreadBeginResponse = serde_json::from_str(&receipt.result_json)
             .map_err(|_| AppError::Internal)?;
         return Ok(Json(response));
     }
 
     // Validate public key format
-    if req.user_pubkey_hex.len() != 66 || !req.user_pubkey_hex.starts_with("02") && !req.user_pubkey_hex.starts_with("03") {
-        return Err(AppError::InvalidPublicKey);
-    }
+    parse_pubkey(&req.user_pubkey_hex).ok_or(AppError::InvalidPublicKey)?;
+
+    validate_timestamp(req.timestamp_i64, 0)?;
+    let board = require_active_board(&pool, req.board_id).await?;
 
     let config = Config::new().unwrap();
+    validate_draft(&config, true, &req.post_draft)?;
+    check_unique_content(&mut *pool.acquire().await?, &board, &req.post_draft).await?;
     
     // Create canonical parameters
     let canonical_params = CanonicalParams {
         user_pubkey_hex: req.user_pubkey_hex.clone(),
         scope: "t".to_string(), // 't' for thread
+        board_id: board.id as u64,
         thread_id: 0, // New thread
         parent_id: 0,
         timestamp_i64: req.timestamp_i64,
@@ -125,14 +543,24 @@ pub async fn thread_begin(
     let post_json = serde_json::to_string(&req.post_draft)?;
     let post_bytes_hash = sha2::Sha256::digest(post_json.as_bytes()).to_vec();
 
+    let required_prefix = required_prefix_for(
+        &pool,
+        &config,
+        &req.user_pubkey_hex,
+        req.timestamp_i64,
+        req.discount_signature_hex.as_deref(),
+    )
+    .await?;
+
     // Create challenge
     let challenge = PowChallenge::new(
         req.user_pubkey_hex,
         "thread".to_string(),
+        board.id,
         0,
         0,
         post_bytes_hash.clone(),
-        config.pow_default_prefix.clone(),
+        required_prefix,
         canonical_bytes.clone(),
         config.pow_challenge_ttl_seconds,
     );
@@ -154,6 +582,7 @@ pub async fn thread_begin(
     let receipt = OpReceipt::new(
         req.client_op_id.to_string(),
         "thread_begin".to_string(),
+        None,
         serde_json::to_string(&response)?,
     );
     PowRepository::create_op_receipt(&pool, &receipt).await?;
@@ -165,6 +594,21 @@ pub async fn thread_commit(
     State(pool): State<DbPool>,
     Json(req): Json<ThreadCommitRequest>,
 ) -> Result<Json<ThreadCommitResponse>> {
+    parse_pubkey(&req.user_pubkey_hex).ok_or(AppError::InvalidPublicKey)?;
+
+    let op_id = req.op_id.to_string();
+    let request_hash = commit_request_hash(json!({
+        "challenge_id": req.challenge_id,
+        "post_draft": req.post_draft,
+        "proof": req.proof,
+        "user_pubkey_hex": req.user_pubkey_hex,
+    }))?;
+
+    // A retried commit gets the thread it already created
+    if let Some(receipt) = PowRepository::find_op_receipt(&pool, &op_id, "thread_commit").await? {
+        return replay_commit(&receipt, &request_hash).map(Json);
+    }
+
     // Find challenge
     let challenge = PowRepository::find_challenge(&pool, &req.challenge_id)
         .await?
@@ -175,10 +619,20 @@ pub async fn thread_commit(
         return Err(AppError::ChallengeExpired);
     }
 
-    // Verify the proof
+    // Cheaper challenges from other scopes can't be spent on a thread
+    if challenge.scope != "thread" || challenge.user_pubkey_hex != req.user_pubkey_hex {
+        return Err(AppError::ChallengeNotFound);
+    }
+
+    let config = Config::new().unwrap();
+    validate_timestamp(req.proof.timestamp_i64, config.pow_challenge_ttl_seconds as i64)?;
+    validate_draft(&config, true, &req.post_draft)?;
+
+    // Verify the proof against the board bound at begin time
     let canonical_params = CanonicalParams {
         user_pubkey_hex: req.user_pubkey_hex.clone(),
         scope: "t".to_string(),
+        board_id: challenge.board_id as u64,
         thread_id: 0,
         parent_id: 0,
         timestamp_i64: req.proof.timestamp_i64,
@@ -195,10 +649,26 @@ pub async fn thread_commit(
         return Err(AppError::InvalidProofOfWork);
     }
 
-    // Create thread (assuming board_id = 1 for now)
-    let thread_id = crate::db::ThreadRepository::create(
-        &pool,
-        1, // Default board
+    let board = require_active_board(&pool, challenge.board_id).await?;
+    let difficulty = calculate_pow_difficulty(&solved_hash);
+
+    // Content, counters and the commit record land together or not at all
+    let mut tx = pool.begin().await?;
+
+    if PowRepository::find_spent_commit(&mut *tx, &req.challenge_id, &solved_hash)
+        .await?
+        .is_some()
+    {
+        return Err(AppError::ChallengeUsed);
+    }
+
+    let quoted = validate_refs(&mut tx, &req.post_draft).await?;
+    let attachments = validate_attachments(&mut tx, &board, &req.post_draft).await?;
+    let unique_hash = check_unique_content(&mut tx, &board, &req.post_draft).await?;
+
+    let thread_id = ThreadRepository::create(
+        &mut *tx,
+        board.id,
         &req.post_draft.title,
         &req.post_draft.body,
         None, // author_name
@@ -206,9 +676,26 @@ pub async fn thread_commit(
         Some(req.proof.nonce_u64 as i64),
         Some(&solved_hash),
         Some(&req.challenge_id),
+        difficulty,
     )
     .await?;
 
+    BoardRepository::record_thread(&mut *tx, board.id).await?;
+    ThreadRepository::archive_overflow(&mut *tx, board.id).await?;
+    record_refs(&mut tx, thread_id, None, &quoted).await?;
+    record_notifications(&mut tx, thread_id, None, &req.user_pubkey_hex, None, &quoted).await?;
+    link_attachments(&mut tx, thread_id, None, &attachments).await?;
+    if let Some(hash) = &unique_hash {
+        ContentHashRepository::record(&mut *tx, board.id, hash, thread_id, None).await?;
+    }
+    if let Some(poll) = &req.post_draft.poll {
+        let poll_id = PollRepository::create(&mut *tx, thread_id, poll.question.trim()).await?;
+        for (position, label) in poll.options.iter().enumerate() {
+            PollRepository::add_option(&mut *tx, poll_id, position as i64, label.trim()).await?;
+        }
+    }
+    record_user_work(&mut tx, &req.user_pubkey_hex, difficulty, true).await?;
+
     // Create commit record
     let commit = PowCommit {
         id: Uuid::new_v4().to_string(),
@@ -222,9 +709,20 @@ pub async fn thread_commit(
         verified: true,
         created_at: chrono::Utc::now(),
     };
-    PowRepository::create_commit(&pool, &commit).await?;
+    PowRepository::create_commit(&mut *tx, &commit).await?;
+
+    let response = ThreadCommitResponse { thread_id };
+    let receipt = OpReceipt::new(
+        op_id,
+        "thread_commit".to_string(),
+        Some(request_hash),
+        serde_json::to_string(&response)?,
+    );
+    PowRepository::create_op_receipt(&mut *tx, &receipt).await?;
 
-    Ok(Json(ThreadCommitResponse { thread_id }))
+    tx.commit().await?;
+
+    Ok(Json(response))
 }
 
 pub async fn reply_begin(
@@ -232,21 +730,29 @@ pub async fn reply_begin(
     Json(req): Json<ReplyBeginRequest>,
 ) -> Result<Json<ThreadBeginResponse>> {
     // Similar to thread_begin but with different scope and thread_id
-    if let Some(receipt) = PowRepository::find_op_receipt(&pool, &req.client_op_id.to_string()).await? {
+    if let Some(receipt) = PowRepository::find_op_receipt(&pool, &req.client_op_id.to_string(), "reply_begin").await? {
         let response: ThreadBeginResponse = serde_json::from_str(&receipt.result_json)
             .map_err(|_| AppError::Internal)?;
         return Ok(Json(response));
     }
 
-    if req.user_pubkey_hex.len() != 66 || !req.user_pubkey_hex.starts_with("02") && !req.user_pubkey_hex.starts_with("03") {
-        return Err(AppError::InvalidPublicKey);
-    }
+    parse_pubkey(&req.user_pubkey_hex).ok_or(AppError::InvalidPublicKey)?;
+
+    validate_timestamp(req.timestamp_i64, 0)?;
+    let mut conn = pool.acquire().await?;
+    let thread = validate_reply_target(&mut conn, req.thread_id, req.parent_id).await?;
+    let board = BoardRepository::find_by_id(&mut *conn, thread.board_id)
+        .await?
+        .ok_or(AppError::NotFound)?;
 
     let config = Config::new().unwrap();
+    validate_draft(&config, false, &req.post_draft)?;
+    check_unique_content(&mut conn, &board, &req.post_draft).await?;
     
     let canonical_params = CanonicalParams {
         user_pubkey_hex: req.user_pubkey_hex.clone(),
         scope: "r".to_string(), // 'r' for reply
+        board_id: 0,
         thread_id: req.thread_id as u64,
         parent_id: req.parent_id.unwrap_or(0) as u64,
         timestamp_i64: req.timestamp_i64,
@@ -257,13 +763,23 @@ pub async fn reply_begin(
     let post_json = serde_json::to_string(&req.post_draft)?;
     let post_bytes_hash = sha2::Sha256::digest(post_json.as_bytes()).to_vec();
 
+    let required_prefix = required_prefix_for(
+        &pool,
+        &config,
+        &req.user_pubkey_hex,
+        req.timestamp_i64,
+        req.discount_signature_hex.as_deref(),
+    )
+    .await?;
+
     let challenge = PowChallenge::new(
         req.user_pubkey_hex,
         "reply".to_string(),
+        0,
         req.thread_id,
         req.parent_id.unwrap_or(0),
         post_bytes_hash.clone(),
-        config.pow_default_prefix.clone(),
+        required_prefix,
         canonical_bytes.clone(),
         config.pow_challenge_ttl_seconds,
     );
@@ -283,6 +799,7 @@ pub async fn reply_begin(
     let receipt = OpReceipt::new(
         req.client_op_id.to_string(),
         "reply_begin".to_string(),
+        None,
         serde_json::to_string(&response)?,
     );
     PowRepository::create_op_receipt(&pool, &receipt).await?;
@@ -294,6 +811,23 @@ pub async fn reply_commit(
     State(pool): State<DbPool>,
     Json(req): Json<ReplyCommitRequest>,
 ) -> Result<Json<ReplyCommitResponse>> {
+    parse_pubkey(&req.user_pubkey_hex).ok_or(AppError::InvalidPublicKey)?;
+
+    let op_id = req.op_id.to_string();
+    let request_hash = commit_request_hash(json!({
+        "challenge_id": req.challenge_id,
+        "parent_id": req.parent_id,
+        "post_draft": req.post_draft,
+        "proof": req.proof,
+        "thread_id": req.thread_id,
+        "user_pubkey_hex": req.user_pubkey_hex,
+    }))?;
+
+    // A retried commit gets the post it already created
+    if let Some(receipt) = PowRepository::find_op_receipt(&pool, &op_id, "reply_commit").await? {
+        return replay_commit(&receipt, &request_hash).map(Json);
+    }
+
     let challenge = PowRepository::find_challenge(&pool, &req.challenge_id)
         .await?
         .ok_or(AppError::ChallengeNotFound)?;
@@ -302,9 +836,23 @@ pub async fn reply_commit(
         return Err(AppError::ChallengeExpired);
     }
 
+    // Cheaper challenges from other scopes, or for another target, can't be spent on this reply
+    if challenge.scope != "reply"
+        || challenge.user_pubkey_hex != req.user_pubkey_hex
+        || challenge.thread_id != req.thread_id
+        || challenge.parent_id != req.parent_id.unwrap_or(0)
+    {
+        return Err(AppError::ChallengeNotFound);
+    }
+
+    let config = Config::new().unwrap();
+    validate_timestamp(req.proof.timestamp_i64, config.pow_challenge_ttl_seconds as i64)?;
+    validate_draft(&config, false, &req.post_draft)?;
+
     let canonical_params = CanonicalParams {
         user_pubkey_hex: req.user_pubkey_hex.clone(),
         scope: "r".to_string(),
+        board_id: 0,
         thread_id: req.thread_id as u64,
         parent_id: req.parent_id.unwrap_or(0) as u64,
         timestamp_i64: req.proof.timestamp_i64,
@@ -321,9 +869,30 @@ pub async fn reply_commit(
         return Err(AppError::InvalidProofOfWork);
     }
 
-    let post_id = crate::db::PostRepository::create(
-        &pool,
-        req.thread_id,
+    let difficulty = calculate_pow_difficulty(&solved_hash);
+
+    // Content, counters and the commit record land together or not at all
+    let mut tx = pool.begin().await?;
+
+    if PowRepository::find_spent_commit(&mut *tx, &req.challenge_id, &solved_hash)
+        .await?
+        .is_some()
+    {
+        return Err(AppError::ChallengeUsed);
+    }
+
+    // Re-check inside the transaction: the thread may have been locked while mining
+    let thread = validate_reply_target(&mut tx, req.thread_id, req.parent_id).await?;
+    let quoted = validate_refs(&mut tx, &req.post_draft).await?;
+    let board = BoardRepository::find_by_id(&mut *tx, thread.board_id)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    let attachments = validate_attachments(&mut tx, &board, &req.post_draft).await?;
+    let unique_hash = check_unique_content(&mut tx, &board, &req.post_draft).await?;
+
+    let post_id = PostRepository::create(
+        &mut *tx,
+        thread.id,
         req.parent_id,
         &req.post_draft.body,
         None,
@@ -331,6 +900,25 @@ pub async fn reply_commit(
         Some(req.proof.nonce_u64 as i64),
         Some(&solved_hash),
         Some(&req.challenge_id),
+        difficulty,
+    )
+    .await?;
+
+    ThreadRepository::record_reply(&mut *tx, thread.id, difficulty, req.post_draft.sage).await?;
+    BoardRepository::record_post(&mut *tx, thread.board_id).await?;
+    record_user_work(&mut tx, &req.user_pubkey_hex, difficulty, false).await?;
+    record_refs(&mut tx, thread.id, Some(post_id), &quoted).await?;
+    link_attachments(&mut tx, thread.id, Some(post_id), &attachments).await?;
+    if let Some(hash) = &unique_hash {
+        ContentHashRepository::record(&mut *tx, board.id, hash, thread.id, Some(post_id)).await?;
+    }
+    record_notifications(
+        &mut tx,
+        req.thread_id,
+        Some(post_id),
+        &req.user_pubkey_hex,
+        req.parent_id,
+        &quoted,
     )
     .await?;
 
@@ -346,7 +934,350 @@ pub async fn reply_commit(
         verified: true,
         created_at: chrono::Utc::now(),
     };
-    PowRepository::create_commit(&pool, &commit).await?;
+    PowRepository::create_commit(&mut *tx, &commit).await?;
+
+    let response = ReplyCommitResponse { post_id };
+    let receipt = OpReceipt::new(
+        op_id,
+        "reply_commit".to_string(),
+        Some(request_hash),
+        serde_json::to_string(&response)?,
+    );
+    PowRepository::create_op_receipt(&mut *tx, &receipt).await?;
+
+    tx.commit().await?;
+
+    Ok(Json(response))
+}
+
+#[derive(Deserialize)]
+pub struct PreviewRequest {
+    /// Target board of a new thread; leave unset and give `thread_id` for a reply
+    pub board_id: Option<i64>,
+    pub thread_id: Option<i64>,
+    pub parent_id: Option<i64>,
+    pub post_draft: PostDraft,
+    pub user_pubkey_hex: String,
+    /// The timestamp the client will send to begin; defaults to now
+    pub timestamp_i64: Option<i64>,
+    /// The discount signature the client will send to begin, if any
+    #[serde(default)]
+    pub discount_signature_hex: Option<String>,
+}
 
-    Ok(Json(ReplyCommitResponse { post_id }))
+#[derive(Serialize)]
+pub struct PreviewRef {
+    /// The id as quoted: a post, or for a board quote possibly a thread
+    pub post_id: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub board: Option<String>,
+    pub thread_id: Option<i64>,
+    pub found: bool,
+}
+
+#[derive(Serialize)]
+pub struct PreviewResponse {
+    /// True when begin and commit would accept this draft as it stands
+    pub ok: bool,
+    pub errors: Vec<String>,
+    pub title_html: String,
+    pub body_html: String,
+    pub refs: Vec<PreviewRef>,
+    pub timestamp_i64: i64,
+    pub required_prefix_hex: String,
+    pub post_bytes_hash: String,
+    pub canonical_bytes: String,
+    pub canonical_hash: String,
+}
+
+/// Dry run of begin: render the draft and report every problem begin or commit would raise,
+/// along with the bytes a miner would work on. Creates no challenge and stores nothing.
+pub async fn preview(
+    State(pool): State<DbPool>,
+    Json(req): Json<PreviewRequest>,
+) -> Result<Json<PreviewResponse>> {
+    parse_pubkey(&req.user_pubkey_hex).ok_or(AppError::InvalidPublicKey)?;
+
+    let config = Config::new().unwrap();
+    let timestamp_i64 = req.timestamp_i64.unwrap_or_else(|| chrono::Utc::now().timestamp());
+    let is_thread = req.thread_id.is_none();
+
+    let mut errors = draft_errors(&config, is_thread, &req.post_draft);
+    if let Err(error) = validate_timestamp(timestamp_i64, 0) {
+        errors.push(error.to_string());
+    }
+
+    let mut conn = pool.acquire().await?;
+    let mut board = None;
+    let canonical_params = match (req.thread_id, req.board_id) {
+        (Some(thread_id), _) => {
+            match validate_reply_target(&mut conn, thread_id, req.parent_id).await {
+                Ok(thread) => board = BoardRepository::find_by_id(&mut *conn, thread.board_id).await?,
+                Err(error) => errors.push(error.to_string()),
+            }
+            CanonicalParams {
+                user_pubkey_hex: req.user_pubkey_hex.clone(),
+                scope: "r".to_string(),
+                board_id: 0,
+                thread_id: thread_id as u64,
+                parent_id: req.parent_id.unwrap_or(0) as u64,
+                timestamp_i64,
+                post_draft: req.post_draft.clone(),
+            }
+        }
+        (None, Some(board_id)) => {
+            match require_active_board(&pool, board_id).await {
+                Ok(found) => board = Some(found),
+                Err(error) => errors.push(error.to_string()),
+            }
+            CanonicalParams {
+                user_pubkey_hex: req.user_pubkey_hex.clone(),
+                scope: "t".to_string(),
+                board_id: board_id as u64,
+                thread_id: 0,
+                parent_id: 0,
+                timestamp_i64,
+                post_draft: req.post_draft.clone(),
+            }
+        }
+        (None, None) => {
+            return Err(AppError::Validation("Preview needs a board_id or a thread_id".to_string()));
+        }
+    };
+
+    let quotes = req.post_draft.parse_refs().unwrap_or_default();
+    let targets = resolve_quotes(&mut conn, &quotes).await?;
+    let mut refs = Vec::new();
+    for quote in quotes {
+        let target = targets.get(&quote).copied();
+        let board = match &quote {
+            Quote::Post(_) => None,
+            Quote::Board { slug, .. } => Some(slug.clone()),
+        };
+        if target.is_none() {
+            match &board {
+                Some(slug) => errors.push(format!(">>>/{}/{} does not exist", slug, quote.id())),
+                None => errors.push(format!(">>{} does not exist", quote.id())),
+            }
+        }
+        refs.push(PreviewRef {
+            post_id: quote.id(),
+            board,
+            thread_id: target.map(|target| target.thread_id),
+            found: target.is_some(),
+        });
+    }
+    let links = resolve_links(&pool, req.thread_id, std::iter::once(req.post_draft.body.as_str())).await?;
+
+    if let Some(board) = &board {
+        if let Err(error) = check_unique_content(&mut conn, board, &req.post_draft).await {
+            errors.push(error.to_string());
+        }
+        for sha256_hex in req.post_draft.attachments.iter().filter(|hash| is_valid_hash(hash)) {
+            match AttachmentRepository::find(&mut *conn, sha256_hex).await? {
+                Some(attachment) => errors.extend(attachment_error(board, &attachment)),
+                None => errors.push(format!("Attachment {} was never uploaded", sha256_hex)),
+            }
+        }
+    }
+
+    let canonical_bytes = canonical_bytes_v1(&canonical_params);
+    let post_json = serde_json::to_string(&req.post_draft)?;
+    let post_bytes_hash = sha2::Sha256::digest(post_json.as_bytes()).to_vec();
+    let required_prefix_hex = required_prefix_for(
+        &pool,
+        &config,
+        &req.user_pubkey_hex,
+        timestamp_i64,
+        req.discount_signature_hex.as_deref(),
+    )
+    .await?;
+
+    Ok(Json(PreviewResponse {
+        ok: errors.is_empty(),
+        errors,
+        title_html: escape_html(&req.post_draft.title),
+        body_html: render_post_with(&req.post_draft.body, &links),
+        refs,
+        timestamp_i64,
+        required_prefix_hex,
+        post_bytes_hash: hex::encode(&post_bytes_hash),
+        canonical_hash: sha256_hex(&canonical_bytes),
+        canonical_bytes: hex::encode(&canonical_bytes),
+    }))
+}
+
+#[derive(Serialize)]
+pub struct ThreadGraphResponse {
+    pub thread_id: i64,
+    pub post_ids: Vec<i64>,
+    /// Quote edges starting or ending in this thread, including ones that cross into other threads
+    pub edges: Vec<PostRef>,
+}
+
+pub async fn thread_graph(
+    State(pool): State<DbPool>,
+    Path(thread_id): Path<i64>,
+) -> Result<Json<ThreadGraphResponse>> {
+    ThreadRepository::find_by_id(&pool, thread_id)
+        .await?
+        .ok_or(AppError::ThreadNotFound)?;
+
+    let post_ids = PostRepository::list_by_thread(&pool, thread_id)
+        .await?
+        .into_iter()
+        .map(|post| post.id)
+        .collect();
+    let edges = PostRefRepository::list_for_thread(&pool, thread_id).await?;
+
+    Ok(Json(ThreadGraphResponse {
+        thread_id,
+        post_ids,
+        edges,
+    }))
+}
+
+#[derive(Deserialize)]
+pub struct TreeQuery {
+    #[serde(default)]
+    pub order: SiblingOrder,
+    pub depth: Option<u32>,
+    /// Show only the subtree under this post
+    pub root: Option<i64>,
+}
+
+impl TreeQuery {
+    pub fn max_depth(&self) -> u32 {
+        self.depth.unwrap_or(DEFAULT_TREE_DEPTH).clamp(1, MAX_TREE_DEPTH)
+    }
+}
+
+#[derive(Serialize)]
+pub struct ThreadTreeResponse {
+    pub thread_id: i64,
+    pub order: SiblingOrder,
+    pub max_depth: u32,
+    pub root: Option<i64>,
+    pub nodes: Vec<ReplyNode>,
+}
+
+pub async fn thread_tree(
+    State(pool): State<DbPool>,
+    Path(thread_id): Path<i64>,
+    Query(query): Query<TreeQuery>,
+) -> Result<Json<ThreadTreeResponse>> {
+    ThreadRepository::find_by_id(&pool, thread_id)
+        .await?
+        .ok_or(AppError::ThreadNotFound)?;
+
+    let posts = PostRepository::list_by_thread(&pool, thread_id).await?;
+    let max_depth = query.max_depth();
+
+    Ok(Json(ThreadTreeResponse {
+        thread_id,
+        order: query.order,
+        max_depth,
+        root: query.root,
+        nodes: build_reply_tree(posts, query.order, max_depth, query.root),
+    }))
+}
+
+pub async fn vanity_flair(
+    State(pool): State<DbPool>,
+    Json(req): Json<VanityFlairRequest>,
+) -> Result<Json<VanityFlairResponse>> {
+    parse_pubkey(&req.user_pubkey_hex).ok_or(AppError::InvalidPublicKey)?;
+
+    let config = Config::new().unwrap();
+
+    if !is_valid_vanity_pattern(&req.pattern, config.vanity_min_len, config.vanity_max_len) {
+        return Err(AppError::Validation(format!(
+            "Vanity pattern must be {}-{} lowercase hex characters",
+            config.vanity_min_len, config.vanity_max_len
+        )));
+    }
+
+    let (is_valid, vanity_hash) =
+        verify_personal_vanity(&req.user_pubkey_hex, &req.pattern, req.nonce_u64);
+
+    if !is_valid {
+        return Err(AppError::InvalidProofOfWork);
+    }
+
+    validate_timestamp(req.timestamp_i64, 0)?;
+    let message = flair_message(&req.user_pubkey_hex, &req.pattern, req.nonce_u64, req.timestamp_i64);
+    if !verify_signature(&req.user_pubkey_hex, &message, &req.signature_hex) {
+        return Err(AppError::InvalidSignature);
+    }
+
+    UserRepository::set_vanity_flair(
+        &pool,
+        &req.user_pubkey_hex,
+        &req.pattern,
+        &vanity_hash,
+        req.nonce_u64 as i64,
+    )
+    .await?;
+
+    Ok(Json(VanityFlairResponse {
+        user_pubkey_hex: req.user_pubkey_hex,
+        vanity_prefix: req.pattern,
+        vanity_hash,
+    }))
+}
+
+pub async fn link_pseudonym(
+    State(pool): State<DbPool>,
+    Json(req): Json<LinkPseudonymRequest>,
+) -> Result<Json<LinkPseudonymResponse>> {
+    if parse_pubkey(&req.main_pubkey_hex).is_none() || parse_pubkey(&req.child_pubkey_hex).is_none() {
+        return Err(AppError::InvalidPublicKey);
+    }
+
+    if !verify_link_proof(
+        &req.main_pubkey_hex,
+        &req.child_pubkey_hex,
+        &req.main_sig_hex,
+        &req.child_sig_hex,
+    ) {
+        return Err(AppError::InvalidSignature);
+    }
+
+    // Checked and linked in one transaction, so two crossing links can't chain keys
+    let mut tx = pool.begin().await?;
+
+    // Links are a single hop: a pseudonym can't act as someone's main identity
+    if let Some(main) = UserRepository::find_by_pubkey(&mut *tx, &req.main_pubkey_hex).await? {
+        if main.linked_pubkey_hex.is_some() {
+            return Err(AppError::Validation(
+                "Main identity is itself a linked pseudonym".to_string(),
+            ));
+        }
+    }
+    if UserRepository::has_pseudonyms(&mut *tx, &req.child_pubkey_hex).await? {
+        return Err(AppError::Validation(
+            "Pseudonym is itself the main identity of other keys".to_string(),
+        ));
+    }
+
+    let linked = UserRepository::link_pseudonym(
+        &mut *tx,
+        &req.child_pubkey_hex,
+        &req.main_pubkey_hex,
+        &req.main_sig_hex,
+        &req.child_sig_hex,
+    )
+    .await?;
+
+    if !linked {
+        return Err(AppError::Validation("Key is already linked".to_string()));
+    }
+
+    tx.commit().await?;
+
+    Ok(Json(LinkPseudonymResponse {
+        main_pubkey_hex: req.main_pubkey_hex,
+        child_pubkey_hex: req.child_pubkey_hex,
+        linked_at: chrono::Utc::now().to_rfc3339(),
+    }))
 }
\ No newline at end of file
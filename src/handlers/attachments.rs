@@ -0,0 +1,303 @@
+use axum::{
+    body::Bytes,
+    extract::{Path, Query, State},
+    Json,
+};
+use chrono::{Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::Digest;
+use std::path::Path as FsPath;
+use uuid::Uuid;
+
+use crate::{
+    attachments::{clean_filename, is_valid_hash, process_image, sniff_mime, storage_path, thumbnail_storage_path},
+    config::Config,
+    db::{AttachmentRepository, DbPool, PowRepository},
+    error::{AppError, Result},
+    handlers::api::{require_active_board, validate_timestamp, ThreadBeginResponse},
+    identity::parse_pubkey,
+    models::{Attachment, OpReceipt, PowChallenge, PowCommit},
+    pow::{canonical_bytes_v1, sha256_hex, verify_proof_v1, CanonicalParams, PostDraft, MINER_VERSION},
+};
+
+#[derive(Deserialize)]
+pub struct UploadBeginRequest {
+    pub client_op_id: Uuid,
+    pub user_pubkey_hex: String,
+    /// SHA-256 of the file exactly as it will be sent, before any cleaning
+    pub file_sha256_hex: String,
+    pub timestamp_i64: i64,
+}
+
+/// The proof for an upload rides in the query string, since the body is the file
+#[derive(Deserialize)]
+pub struct UploadQuery {
+    pub filename: Option<String>,
+    pub challenge_id: String,
+    pub user_pubkey_hex: String,
+    pub nonce_u64: u64,
+    pub timestamp_i64: i64,
+}
+
+#[derive(Serialize)]
+pub struct UploadResponse {
+    /// Goes into `PostDraft.attachments`
+    pub sha256_hex: String,
+    pub mime_type: String,
+    pub size_bytes: i64,
+    pub width: i64,
+    pub height: i64,
+    pub url: String,
+    pub thumbnail_url: String,
+}
+
+fn canonical_params(pubkey_hex: &str, board_id: i64, file_sha256_hex: &str, timestamp_i64: i64) -> CanonicalParams {
+    CanonicalParams {
+        user_pubkey_hex: pubkey_hex.to_string(),
+        scope: "u".to_string(), // 'u' for upload
+        board_id: board_id as u64,
+        thread_id: 0,
+        parent_id: 0,
+        timestamp_i64,
+        post_draft: PostDraft::upload(file_sha256_hex),
+    }
+}
+
+/// Issue the challenge for uploading one file to a board
+pub async fn upload_begin(
+    State(pool): State<DbPool>,
+    Path(board_id): Path<i64>,
+    Json(req): Json<UploadBeginRequest>,
+) -> Result<Json<ThreadBeginResponse>> {
+    if let Some(receipt) = PowRepository::find_op_receipt(&pool, &req.client_op_id.to_string(), "upload_begin").await? {
+        let response: ThreadBeginResponse = serde_json::from_str(&receipt.result_json)
+            .map_err(|_| AppError::Internal)?;
+        return Ok(Json(response));
+    }
+
+    parse_pubkey(&req.user_pubkey_hex).ok_or(AppError::InvalidPublicKey)?;
+    validate_timestamp(req.timestamp_i64, 0)?;
+    if !is_valid_hash(&req.file_sha256_hex) {
+        return Err(AppError::Validation("File hash must be 64 lowercase hex characters".to_string()));
+    }
+    let board = require_active_board(&pool, board_id).await?;
+
+    let config = Config::new().unwrap();
+    let canonical_params = canonical_params(&req.user_pubkey_hex, board.id, &req.file_sha256_hex, req.timestamp_i64);
+    let canonical_bytes = canonical_bytes_v1(&canonical_params);
+    let post_json = serde_json::to_string(&canonical_params.post_draft)?;
+    let post_bytes_hash = sha2::Sha256::digest(post_json.as_bytes()).to_vec();
+
+    let challenge = PowChallenge::new(
+        req.user_pubkey_hex,
+        "upload".to_string(),
+        board.id,
+        0,
+        0,
+        post_bytes_hash.clone(),
+        config.upload_pow_prefix,
+        canonical_bytes.clone(),
+        config.pow_challenge_ttl_seconds,
+    );
+    PowRepository::create_challenge(&pool, &challenge).await?;
+
+    let response = ThreadBeginResponse {
+        challenge_id: challenge.id.clone(),
+        required_prefix_hex: challenge.required_prefix_hex,
+        challenge_version: 1,
+        op_id: req.client_op_id,
+        expires_at: challenge.expires_at.to_rfc3339(),
+        post_bytes_hash: hex::encode(&post_bytes_hash),
+        canonical_bytes: hex::encode(&canonical_bytes),
+    };
+
+    let receipt = OpReceipt::new(
+        req.client_op_id.to_string(),
+        "upload_begin".to_string(),
+        None,
+        serde_json::to_string(&response)?,
+    );
+    PowRepository::create_op_receipt(&pool, &receipt).await?;
+
+    Ok(Json(response))
+}
+
+/// Store a file for a post on a board. The raw request body is the file, paid for
+/// by a proof over its hash from `upload_begin`.
+/// Images are re-encoded without metadata before storing, and the stored bytes
+/// are what's content-addressed, so sending the same image twice is harmless.
+pub async fn upload(
+    State(pool): State<DbPool>,
+    Path(board_id): Path<i64>,
+    Query(query): Query<UploadQuery>,
+    body: Bytes,
+) -> Result<Json<UploadResponse>> {
+    let board = require_active_board(&pool, board_id).await?;
+
+    let challenge = PowRepository::find_challenge(&pool, &query.challenge_id)
+        .await?
+        .ok_or(AppError::ChallengeNotFound)?;
+
+    if challenge.is_expired() {
+        return Err(AppError::ChallengeExpired);
+    }
+
+    // An upload challenge only pays for a file on the board it was issued for
+    if challenge.scope != "upload"
+        || challenge.user_pubkey_hex != query.user_pubkey_hex
+        || challenge.board_id != board.id
+    {
+        return Err(AppError::ChallengeNotFound);
+    }
+
+    let config = Config::new().unwrap();
+    validate_timestamp(query.timestamp_i64, config.pow_challenge_ttl_seconds as i64)?;
+
+    // The proof binds the bytes as sent, so it only verifies for the file it was mined for
+    let canonical_params =
+        canonical_params(&query.user_pubkey_hex, board.id, &sha256_hex(&body), query.timestamp_i64);
+    let (is_valid, solved_hash) =
+        verify_proof_v1(&canonical_params, query.nonce_u64, &challenge.required_prefix_hex);
+
+    if !is_valid {
+        return Err(AppError::InvalidProofOfWork);
+    }
+
+    if body.is_empty() {
+        return Err(AppError::Validation("Upload is empty".to_string()));
+    }
+    if body.len() as i64 > board.max_upload_bytes {
+        return Err(AppError::Validation(format!(
+            "File is {} bytes, /{}/ accepts up to {}",
+            body.len(),
+            board.slug,
+            board.max_upload_bytes
+        )));
+    }
+
+    let mime_type = sniff_mime(&body)
+        .ok_or_else(|| AppError::Validation("Unsupported file type".to_string()))?;
+    if !board.accepts_mime_type(mime_type) {
+        return Err(AppError::Validation(format!("/{}/ doesn't accept {}", board.slug, mime_type)));
+    }
+
+    let (max_pixels, thumb_side) = (config.upload_max_pixels, config.thumbnail_max_side);
+    let image = tokio::task::spawn_blocking(move || process_image(&body, max_pixels, thumb_side))
+        .await
+        .map_err(|_| AppError::Internal)?
+        .map_err(|error| AppError::Validation(error.to_string()))?;
+
+    // Re-encoding can grow a file, and commit checks the stored size against the board
+    if image.bytes.len() as i64 > board.max_upload_bytes {
+        return Err(AppError::Validation(format!(
+            "File is {} bytes once cleaned, /{}/ accepts up to {}",
+            image.bytes.len(),
+            board.slug,
+            board.max_upload_bytes
+        )));
+    }
+
+    let sha256_hex = sha256_hex(&image.bytes);
+
+    // The file's record and the spent proof land together or not at all
+    let mut tx = pool.begin().await?;
+
+    if PowRepository::find_spent_commit(&mut *tx, &query.challenge_id, &solved_hash)
+        .await?
+        .is_some()
+    {
+        return Err(AppError::ChallengeUsed);
+    }
+
+    let attachment = match AttachmentRepository::find(&mut *tx, &sha256_hex).await? {
+        Some(attachment) => attachment,
+        None => {
+            let upload_dir = FsPath::new(&config.upload_dir);
+            write_file(&storage_path(upload_dir, &sha256_hex, mime_type), &image.bytes).await?;
+            write_file(&thumbnail_storage_path(upload_dir, &sha256_hex, mime_type), &image.thumbnail).await?;
+
+            let attachment = Attachment {
+                sha256_hex: sha256_hex.clone(),
+                mime_type: mime_type.to_string(),
+                size_bytes: image.bytes.len() as i64,
+                original_filename: query.filename.as_deref().and_then(clean_filename),
+                width: image.width as i64,
+                height: image.height as i64,
+                thumb_width: image.thumb_width as i64,
+                thumb_height: image.thumb_height as i64,
+                created_at: Utc::now(),
+            };
+            AttachmentRepository::create(&mut *tx, &attachment).await?;
+            attachment
+        }
+    };
+
+    let commit = PowCommit {
+        id: Uuid::new_v4().to_string(),
+        challenge_id: query.challenge_id,
+        nonce_u64: query.nonce_u64 as i64,
+        miner_version: MINER_VERSION as i32,
+        timestamp_i64: query.timestamp_i64,
+        solved_hash_hex: solved_hash,
+        thread_id: None,
+        post_id: None,
+        verified: true,
+        created_at: Utc::now(),
+    };
+    PowRepository::create_commit(&mut *tx, &commit).await?;
+
+    tx.commit().await?;
+
+    Ok(Json(UploadResponse {
+        url: attachment.url(),
+        thumbnail_url: attachment.thumbnail_url(),
+        sha256_hex: attachment.sha256_hex,
+        mime_type: attachment.mime_type,
+        size_bytes: attachment.size_bytes,
+        width: attachment.width,
+        height: attachment.height,
+    }))
+}
+
+/// Write through a temporary file so a file at its final path is always complete
+async fn write_file(path: &FsPath, bytes: &[u8]) -> Result<()> {
+    let parent = path.parent().ok_or(AppError::Internal)?;
+    tokio::fs::create_dir_all(parent).await.map_err(|_| AppError::Internal)?;
+
+    let partial = path.with_extension(format!("{}.part", uuid::Uuid::new_v4()));
+    tokio::fs::write(&partial, bytes).await.map_err(|_| AppError::Internal)?;
+    tokio::fs::rename(&partial, path).await.map_err(|_| AppError::Internal)?;
+
+    Ok(())
+}
+
+/// Delete files no post has linked to since before the configured age. The record
+/// goes first, so a file is only removed once no draft can claim it.
+pub async fn purge_orphans(pool: &DbPool) -> Result<u64> {
+    let config = Config::new().unwrap();
+    let uploaded_before = Utc::now() - Duration::hours(config.upload_orphan_max_age_hours);
+    let upload_dir = FsPath::new(&config.upload_dir);
+
+    let mut purged = 0;
+    for attachment in AttachmentRepository::list_orphaned(pool, uploaded_before).await? {
+        if !AttachmentRepository::delete_orphan(pool, &attachment.sha256_hex).await? {
+            continue;
+        }
+
+        let sha256_hex = &attachment.sha256_hex;
+        let mime_type = &attachment.mime_type;
+        for path in [
+            storage_path(upload_dir, sha256_hex, mime_type),
+            thumbnail_storage_path(upload_dir, sha256_hex, mime_type),
+        ] {
+            match tokio::fs::remove_file(&path).await {
+                Ok(()) => {}
+                Err(error) if error.kind() == std::io::ErrorKind::NotFound => {}
+                Err(_) => return Err(AppError::Internal),
+            }
+        }
+        purged += 1;
+    }
+
+    Ok(purged)
+}
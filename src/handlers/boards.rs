@@ -1,10 +1,90 @@
-use axum::{extract::{Path, State}, response::Html};
+use axum::{extract::{Path, Query, State}, response::Html};
+use serde::Deserialize;
 
 use crate::{
     db::{BoardRepository, DbPool, ThreadRepository},
     error::{AppError, Result},
+    markup::{escape_html, render_post, MARKUP_CSS},
+    models::{Thread, ThreadCursor, ThreadSort},
 };
 
+const BOARD_PAGE_SIZE: i64 = 50;
+const CATALOG_PAGE_SIZE: i64 = 150;
+
+#[derive(Deserialize)]
+pub struct BoardPageQuery {
+    #[serde(default)]
+    pub sort: ThreadSort,
+    pub cursor: Option<String>,
+}
+
+/// Fetch one page of live threads plus the cursor for the next page, if there is one
+async fn load_page(
+    pool: &DbPool,
+    board_id: i64,
+    query: &BoardPageQuery,
+    page_size: i64,
+) -> Result<(Vec<Thread>, Option<String>)> {
+    let cursor = match query.cursor.as_deref() {
+        Some(raw) => Some(
+            ThreadCursor::decode(raw).ok_or_else(|| AppError::Validation("Invalid cursor".to_string()))?,
+        ),
+        None => None,
+    };
+
+    // One extra row tells us whether another page follows
+    let mut threads = ThreadRepository::list_page(pool, board_id, query.sort, cursor, page_size + 1).await?;
+    let next = if threads.len() as i64 > page_size {
+        threads.truncate(page_size as usize);
+        threads.last().map(|last| ThreadCursor::after(last, query.sort).encode())
+    } else {
+        None
+    };
+
+    Ok((threads, next))
+}
+
+/// Sort links for a board view; picking a sort starts again from the first page
+fn sort_links(base: &str, current: ThreadSort) -> String {
+    ThreadSort::ALL
+        .iter()
+        .map(|sort| format!(
+            r#"<a class="sort-btn{}" href="{}?sort={}">{}</a>"#,
+            if *sort == current { " active" } else { "" },
+            base,
+            sort.as_str(),
+            sort.label()
+        ))
+        .collect::<Vec<_>>()
+        .join("\n        ")
+}
+
+fn next_link(base: &str, sort: ThreadSort, next: Option<&str>) -> String {
+    match next {
+        Some(cursor) => format!(
+            r#"<a class="sort-btn" href="{}?sort={}&cursor={}">Next page &raquo;</a>"#,
+            base,
+            sort.as_str(),
+            cursor
+        ),
+        None => String::new(),
+    }
+}
+
+/// The opening post's thumbnail, linking to the thread, sized up front so the page doesn't reflow
+fn thumbnail(thread: &Thread) -> String {
+    match (&thread.thumb_path, thread.thumb_width, thread.thumb_height) {
+        (Some(path), Some(width), Some(height)) => format!(
+            r#"<a href="/threads/{}"><img class="thumb" src="{}" width="{}" height="{}" alt="" loading="lazy"></a>"#,
+            thread.id,
+            escape_html(path),
+            width,
+            height
+        ),
+        _ => String::new(),
+    }
+}
+
 pub async fn list(State(pool): State<DbPool>) -> Result<Html<String>> {
     let boards = BoardRepository::list_active(&pool).await?;
     
@@ -100,10 +180,10 @@ pub async fn list(State(pool): State<DbPool>) -> Result<Html<String>> {
                         {} threads, {} posts
                     </div>
                 </div>"#,
-                board.slug,
-                board.slug,
-                board.name,
-                board.description.as_deref().unwrap_or("No description"),
+                escape_html(&board.slug),
+                escape_html(&board.slug),
+                escape_html(&board.name),
+                escape_html(board.description.as_deref().unwrap_or("No description")),
                 board.thread_count,
                 board.post_count
             ))
@@ -114,12 +194,17 @@ pub async fn list(State(pool): State<DbPool>) -> Result<Html<String>> {
     Ok(Html(html))
 }
 
-pub async fn show(State(pool): State<DbPool>, Path(slug): Path<String>) -> Result<Html<String>> {
+pub async fn show(
+    State(pool): State<DbPool>,
+    Path(slug): Path<String>,
+    Query(query): Query<BoardPageQuery>,
+) -> Result<Html<String>> {
     let board = BoardRepository::find_by_slug(&pool, &slug)
         .await?
         .ok_or(AppError::NotFound)?;
     
-    let threads = ThreadRepository::list_by_board(&pool, board.id, 50).await?;
+    let (threads, next) = load_page(&pool, board.id, &query, BOARD_PAGE_SIZE).await?;
+    let base = format!("/boards/{}", escape_html(&board.slug));
     
     let html = format!(
         r#"<!DOCTYPE html>
@@ -165,6 +250,13 @@ pub async fn show(State(pool): State<DbPool>, Path(slug): Path<String>) -> Resul
             max-height: 100px;
             overflow: hidden;
         }}
+        .thread .thumb {{
+            float: left;
+            margin: 0 12px 6px 0;
+            max-width: 125px;
+            height: auto;
+            border-radius: 4px;
+        }}
         a {{ 
             color: #2DD2C1; 
             text-decoration: none; 
@@ -228,6 +320,7 @@ pub async fn show(State(pool): State<DbPool>, Path(slug): Path<String>) -> Resul
             border-radius: 4px;
             cursor: pointer;
             font-family: 'Courier New', monospace;
+            text-decoration: none;
         }}
         .sort-btn:hover, .sort-btn.active {{
             background-color: #2DD2C1;
@@ -249,11 +342,9 @@ pub async fn show(State(pool): State<DbPool>, Path(slug): Path<String>) -> Resul
         .pow-easy {{ background-color: #4CAF50; color: white; }}
         .pow-medium {{ background-color: #FF9800; color: white; }}
         .pow-hard {{ background-color: #F44336; color: white; }}
+        {}
     </style>
     <script>
-        let currentSort = 'newest';
-        let threadsData = [];
-        
         function calculatePowDifficulty(hash) {{
             if (!hash || hash === 'pending') return 0;
             let leadingZeros = 0;
@@ -270,34 +361,6 @@ pub async fn show(State(pool): State<DbPool>, Path(slug): Path<String>) -> Resul
             return 'pow-easy';
         }}
         
-        function sortThreads(sortType) {{
-            currentSort = sortType;
-            document.querySelectorAll('.sort-btn').forEach(btn => btn.classList.remove('active'));
-            document.querySelector(`[data-sort="${{sortType}}"]`).classList.add('active');
-            
-            const container = document.getElementById('threads-container');
-            const threads = Array.from(container.querySelectorAll('.thread'));
-            
-            threads.sort((a, b) => {{
-                switch(sortType) {{
-                    case 'pow':
-                        const diffA = calculatePowDifficulty(a.dataset.powHash);
-                        const diffB = calculatePowDifficulty(b.dataset.powHash);
-                        return diffB - diffA;
-                    case 'replies':
-                        const repliesA = parseInt(a.dataset.replies) || 0;
-                        const repliesB = parseInt(b.dataset.replies) || 0;
-                        return repliesB - repliesA;
-                    case 'oldest':
-                        return new Date(a.dataset.created) - new Date(b.dataset.created);
-                    default: // newest
-                        return new Date(b.dataset.created) - new Date(a.dataset.created);
-                }}
-            }});
-            
-            threads.forEach(thread => container.appendChild(thread));
-        }}
-        
         function highlightPowThreads() {{
             document.querySelectorAll('.thread').forEach(thread => {{
                 const powHash = thread.dataset.powHash;
@@ -329,35 +392,44 @@ pub async fn show(State(pool): State<DbPool>, Path(slug): Path<String>) -> Resul
         <div class="nav">
             <a href="/">Home</a>
             <a href="/boards">Boards</a>
+            <a href="/boards/{}/catalog">Catalog</a>
+            <a href="/boards/{}/archive">Archive</a>
             <a href="/threads/new/{}" class="new-thread-btn">New Thread</a>
         </div>
     </div>
     
     <div class="board-header">
-        <strong>Board Rules:</strong> All posts require proof-of-work. Be respectful. Hover over content to see PoW difficulty.
+        <strong>Board Rules:</strong> All posts require proof-of-work. Be respectful. Hover over content to see PoW difficulty.{}
     </div>
     
     <div class="sort-controls">
         <strong>Sort by:</strong>
-        <button class="sort-btn active" data-sort="newest" onclick="sortThreads('newest')">Newest</button>
-        <button class="sort-btn" data-sort="pow" onclick="sortThreads('pow')">PoW Difficulty</button>
-        <button class="sort-btn" data-sort="replies" onclick="sortThreads('replies')">Replies</button>
-        <button class="sort-btn" data-sort="oldest" onclick="sortThreads('oldest')">Oldest</button>
+        {}
     </div>
     
     <h3>Threads ({})</h3>
     <div id="threads-container">
     {}
     </div>
+    <div class="sort-controls">{}</div>
 </body>
 </html>"#,
-        board.slug,
-        board.name,
-        board.slug,
-        board.name,
-        board.description.as_deref().unwrap_or("No description"),
+        escape_html(&board.slug),
+        escape_html(&board.name),
+        MARKUP_CSS,
+        escape_html(&board.slug),
+        escape_html(&board.name),
+        escape_html(board.description.as_deref().unwrap_or("No description")),
+        escape_html(&board.slug),
+        escape_html(&board.slug),
         board.id,
-        threads.len(),
+        if board.unique_content {
+            " <strong>Unique mode:</strong> reposting anything already posted here is refused before you mine."
+        } else {
+            ""
+        },
+        sort_links(&base, query.sort),
+        board.thread_count,
         if threads.is_empty() {
             "<p>No threads yet. Be the first to post!</p>".to_string()
         } else {
@@ -372,17 +444,18 @@ pub async fn show(State(pool): State<DbPool>, Path(slug): Path<String>) -> Resul
                     let difficulty_class = if difficulty >= 15 { "pow-hard" } else if difficulty >= 8 { "pow-medium" } else { "pow-easy" };
                     
                     format!(
-                    r#"<div class="thread" data-pow-hash="{}" data-replies="{}" data-created="{}">
+                    r#"<div class="thread" data-pow-hash="{}" data-replies="{}" data-created="{}" data-bumped="{}" data-pinned="{}">
                         <div class="thread-header">
                             <div class="thread-title">
                                 <a href="/threads/{}">{}</a>
                                 <span class="pow-difficulty {} " style="display: none;">PoW: {}</span>
                             </div>
                             <div class="thread-meta">
-                                {} replies • {}
+                                {} replies • {} • bumped {}
                                 <div class="pow-indicator">Hash: {}</div>
                             </div>
                         </div>
+                        {}
                         <div class="thread-content">
                             {}
                         </div>
@@ -390,19 +463,306 @@ pub async fn show(State(pool): State<DbPool>, Path(slug): Path<String>) -> Resul
                     pow_hash,
                     thread.reply_count,
                     thread.created_at.to_rfc3339(),
+                    thread.bumped_at.to_rfc3339(),
+                    thread.is_pinned as u8,
                     thread.id,
-                    thread.title,
+                    escape_html(&thread.title),
                     difficulty_class,
                     difficulty,
                     thread.reply_count,
                     thread.created_at.format("%Y-%m-%d %H:%M"),
+                    thread.bumped_at.format("%Y-%m-%d %H:%M"),
                     pow_hash.chars().take(12).collect::<String>(),
-                    thread.content.chars().take(200).collect::<String>()
+                    thumbnail(thread),
+                    render_post(&thread.content.chars().take(200).collect::<String>())
                 )})
                 .collect::<Vec<_>>()
                 .join("\n")
-        }
+        },
+        next_link(&base, query.sort, next.as_deref())
     );
 
     Ok(Html(html))
-}
\ No newline at end of file
+}
+
+pub async fn catalog(
+    State(pool): State<DbPool>,
+    Path(slug): Path<String>,
+    Query(query): Query<BoardPageQuery>,
+) -> Result<Html<String>> {
+    let board = BoardRepository::find_by_slug(&pool, &slug)
+        .await?
+        .ok_or(AppError::NotFound)?;
+
+    let (threads, next) = load_page(&pool, board.id, &query, CATALOG_PAGE_SIZE).await?;
+    let base = format!("/boards/{}/catalog", escape_html(&board.slug));
+
+    let html = format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+    <title>[{slug}] Catalog - haich2</title>
+    <style>
+        body {{ 
+            font-family: 'Courier New', monospace; 
+            max-width: 1200px; 
+            margin: 0 auto; 
+            padding: 20px;
+            background-color: #3C4267;
+            color: #2DD2C1;
+            line-height: 1.5;
+        }}
+        a {{ 
+            color: #2DD2C1; 
+            text-decoration: none; 
+        }}
+        a:hover {{ 
+            text-decoration: underline; 
+            color: #ffffff;
+        }}
+        .header {{ 
+            text-align: center; 
+            margin-bottom: 30px; 
+            border-bottom: 2px solid #50589C;
+            padding-bottom: 20px;
+        }}
+        .nav {{ margin: 20px 0; }}
+        .nav a {{ 
+            margin-right: 20px; 
+            padding: 8px 16px;
+            background-color: #50589C;
+            border-radius: 4px;
+        }}
+        .sort-controls {{
+            background-color: #50589C;
+            padding: 15px;
+            border-radius: 4px;
+            margin: 20px 0;
+            display: flex;
+            gap: 10px;
+            align-items: center;
+        }}
+        .sort-btn {{
+            background-color: #3C4267;
+            color: #2DD2C1;
+            border: 1px solid #2DD2C1;
+            padding: 8px 16px;
+            border-radius: 4px;
+            text-decoration: none;
+        }}
+        .sort-btn:hover, .sort-btn.active {{
+            background-color: #2DD2C1;
+            color: #3C4267;
+        }}
+        .catalog {{
+            display: grid;
+            grid-template-columns: repeat(auto-fill, minmax(180px, 1fr));
+            gap: 12px;
+        }}
+        .card {{
+            border: 1px solid #50589C;
+            background-color: #636CCB;
+            border-radius: 4px;
+            padding: 10px;
+            overflow: hidden;
+        }}
+        .card-title {{ font-weight: bold; }}
+        .card .thumb {{
+            display: block;
+            margin: 0 auto 6px;
+            max-width: 100%;
+            height: auto;
+            border-radius: 4px;
+        }}
+        .card-line {{
+            color: #ffffff;
+            font-size: 0.85em;
+            margin: 6px 0;
+            word-wrap: break-word;
+        }}
+        .card-meta {{ font-size: 0.8em; opacity: 0.8; }}
+    </style>
+</head>
+<body>
+    <div class="header">
+        <h1>[{slug}] {name} - Catalog</h1>
+        <div class="nav">
+            <a href="/">Home</a>
+            <a href="/boards">Boards</a>
+            <a href="/boards/{slug}">Back to Board</a>
+            <a href="/boards/{slug}/archive">Archive</a>
+        </div>
+    </div>
+
+    <div class="sort-controls">
+        <strong>Sort by:</strong>
+        {sorts}
+    </div>
+
+    <div class="catalog">
+    {cards}
+    </div>
+    <div class="sort-controls">{next}</div>
+</body>
+</html>"#,
+        slug = escape_html(&board.slug),
+        name = escape_html(&board.name),
+        sorts = sort_links(&base, query.sort),
+        cards = if threads.is_empty() {
+            "<p>No threads yet.</p>".to_string()
+        } else {
+            threads
+                .iter()
+                .map(|thread| format!(
+                    r#"<div class="card">
+                        {}
+                        <div class="card-title">{}<a href="/threads/{}">{}</a></div>
+                        <div class="card-line">{}</div>
+                        <div class="card-meta">R: {} • W: {}</div>
+                    </div>"#,
+                    thumbnail(thread),
+                    if thread.is_pinned { "&#128204; " } else { "" },
+                    thread.id,
+                    escape_html(&thread.title),
+                    escape_html(&thread.content.lines().next().unwrap_or("").chars().take(120).collect::<String>()),
+                    thread.reply_count,
+                    thread.bump_score
+                ))
+                .collect::<Vec<_>>()
+                .join("\n")
+        },
+        next = next_link(&base, query.sort, next.as_deref()),
+    );
+
+    Ok(Html(html))
+}
+
+const ARCHIVE_PAGE_SIZE: i64 = 50;
+
+#[derive(Deserialize)]
+pub struct ArchiveQuery {
+    #[serde(default)]
+    pub page: i64,
+}
+
+pub async fn archive(
+    State(pool): State<DbPool>,
+    Path(slug): Path<String>,
+    Query(query): Query<ArchiveQuery>,
+) -> Result<Html<String>> {
+    let board = BoardRepository::find_by_slug(&pool, &slug)
+        .await?
+        .ok_or(AppError::NotFound)?;
+
+    let total = ThreadRepository::count_archived(&pool, board.id).await?;
+    let last_page = (total - 1).max(0) / ARCHIVE_PAGE_SIZE;
+    // Out-of-range pages land on the nearest real one, which also keeps the offset in range
+    let page = query.page.clamp(0, last_page);
+    let threads =
+        ThreadRepository::list_archived(&pool, board.id, ARCHIVE_PAGE_SIZE, page * ARCHIVE_PAGE_SIZE).await?;
+
+    let mut pager = Vec::new();
+    if page > 0 {
+        pager.push(format!(r#"<a href="/boards/{}/archive?page={}">&laquo; Newer</a>"#, escape_html(&board.slug), page - 1));
+    }
+    pager.push(format!("Page {} of {}", page + 1, last_page + 1));
+    if page < last_page {
+        pager.push(format!(r#"<a href="/boards/{}/archive?page={}">Older &raquo;</a>"#, escape_html(&board.slug), page + 1));
+    }
+
+    let html = format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+    <title>[{slug}] Archive - haich2</title>
+    <style>
+        body {{ 
+            font-family: 'Courier New', monospace; 
+            max-width: 900px; 
+            margin: 0 auto; 
+            padding: 20px;
+            background-color: #3C4267;
+            color: #2DD2C1;
+            line-height: 1.5;
+        }}
+        table {{ width: 100%; border-collapse: collapse; }}
+        th, td {{ 
+            border-bottom: 1px solid #50589C; 
+            padding: 8px; 
+            text-align: left;
+        }}
+        td {{ color: #ffffff; }}
+        a {{ 
+            color: #2DD2C1; 
+            text-decoration: none; 
+        }}
+        a:hover {{ 
+            text-decoration: underline; 
+            color: #ffffff;
+        }}
+        .header {{ 
+            text-align: center; 
+            margin-bottom: 30px; 
+            border-bottom: 2px solid #50589C;
+            padding-bottom: 20px;
+        }}
+        .nav {{ margin: 20px 0; }}
+        .nav a {{ 
+            margin-right: 20px; 
+            padding: 8px 16px;
+            background-color: #50589C;
+            border-radius: 4px;
+        }}
+        .pager {{ 
+            margin: 20px 0; 
+            display: flex; 
+            gap: 20px; 
+        }}
+    </style>
+</head>
+<body>
+    <div class="header">
+        <h1>[{slug}] {name} - Archive</h1>
+        <p>Threads pushed off the board. Archived threads are read-only.</p>
+        <div class="nav">
+            <a href="/">Home</a>
+            <a href="/boards">Boards</a>
+            <a href="/boards/{slug}">Back to Board</a>
+        </div>
+    </div>
+
+    <h3>Archived Threads ({total})</h3>
+    {rows}
+    <div class="pager">{pager}</div>
+</body>
+</html>"#,
+        slug = escape_html(&board.slug),
+        name = escape_html(&board.name),
+        total = total,
+        rows = if threads.is_empty() {
+            "<p>Nothing has been archived yet.</p>".to_string()
+        } else {
+            format!(
+                "<table><tr><th>No.</th><th>Title</th><th>Replies</th><th>Archived</th></tr>{}</table>",
+                threads
+                    .iter()
+                    .map(|thread| format!(
+                        r#"<tr><td>{}</td><td><a href="/threads/{}">{}</a></td><td>{}</td><td>{}</td></tr>"#,
+                        thread.id,
+                        thread.id,
+                        escape_html(&thread.title),
+                        thread.reply_count,
+                        thread
+                            .archived_at
+                            .map(|at| at.format("%Y-%m-%d %H:%M").to_string())
+                            .unwrap_or_default()
+                    ))
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            )
+        },
+        pager = pager.join(" "),
+    );
+
+    Ok(Html(html))
+}
@@ -0,0 +1,242 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::Digest;
+use uuid::Uuid;
+
+use crate::{
+    config::Config,
+    db::{BoostRepository, DbPool, PowRepository, ThreadRepository},
+    error::{AppError, Result},
+    handlers::api::{commit_request_hash, replay_commit, validate_reply_target, validate_timestamp, ThreadBeginResponse},
+    identity::parse_pubkey,
+    models::{BoostContributor, OpReceipt, PowChallenge, PowCommit, ThreadBoost},
+    pow::{calculate_pow_difficulty, canonical_bytes_v1, verify_proof_v1, CanonicalParams, PostDraft, ProofOfWork},
+};
+
+#[derive(Deserialize)]
+pub struct BoostBeginRequest {
+    pub client_op_id: Uuid,
+    pub thread_id: i64,
+    pub user_pubkey_hex: String,
+    pub timestamp_i64: i64,
+}
+
+#[derive(Deserialize)]
+pub struct BoostCommitRequest {
+    pub op_id: Uuid,
+    pub challenge_id: String,
+    pub thread_id: i64,
+    pub proof: ProofOfWork,
+    pub user_pubkey_hex: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct BoostCommitResponse {
+    pub thread_id: i64,
+    pub difficulty: f64,
+}
+
+#[derive(Serialize)]
+pub struct ContributorsResponse {
+    pub thread_id: i64,
+    pub bump_score: i32,
+    pub contributors: Vec<BoostContributor>,
+}
+
+fn canonical_params(pubkey_hex: &str, thread_id: i64, timestamp_i64: i64) -> CanonicalParams {
+    CanonicalParams {
+        user_pubkey_hex: pubkey_hex.to_string(),
+        scope: "b".to_string(), // 'b' for boost
+        board_id: 0,
+        thread_id: thread_id as u64,
+        parent_id: 0,
+        timestamp_i64,
+        post_draft: PostDraft::boost(),
+    }
+}
+
+pub async fn boost_begin(
+    State(pool): State<DbPool>,
+    Json(req): Json<BoostBeginRequest>,
+) -> Result<Json<ThreadBeginResponse>> {
+    if let Some(receipt) = PowRepository::find_op_receipt(&pool, &req.client_op_id.to_string(), "boost_begin").await? {
+        let response: ThreadBeginResponse = serde_json::from_str(&receipt.result_json)
+            .map_err(|_| AppError::Internal)?;
+        return Ok(Json(response));
+    }
+
+    parse_pubkey(&req.user_pubkey_hex).ok_or(AppError::InvalidPublicKey)?;
+    validate_timestamp(req.timestamp_i64, 0)?;
+
+    // Same rules as replying: locked and archived threads can't be boosted
+    let mut conn = pool.acquire().await?;
+    validate_reply_target(&mut conn, req.thread_id, None).await?;
+
+    let config = Config::new().unwrap();
+    let canonical_params = canonical_params(&req.user_pubkey_hex, req.thread_id, req.timestamp_i64);
+    let canonical_bytes = canonical_bytes_v1(&canonical_params);
+    let post_json = serde_json::to_string(&canonical_params.post_draft)?;
+    let post_bytes_hash = sha2::Sha256::digest(post_json.as_bytes()).to_vec();
+
+    // The prefix is a floor: a boost counts whatever difficulty its hash reaches
+    let challenge = PowChallenge::new(
+        req.user_pubkey_hex,
+        "boost".to_string(),
+        0,
+        req.thread_id,
+        0,
+        post_bytes_hash.clone(),
+        config.boost_pow_prefix,
+        canonical_bytes.clone(),
+        config.pow_challenge_ttl_seconds,
+    );
+    PowRepository::create_challenge(&pool, &challenge).await?;
+
+    let response = ThreadBeginResponse {
+        challenge_id: challenge.id.clone(),
+        required_prefix_hex: challenge.required_prefix_hex,
+        challenge_version: 1,
+        op_id: req.client_op_id,
+        expires_at: challenge.expires_at.to_rfc3339(),
+        post_bytes_hash: hex::encode(&post_bytes_hash),
+        canonical_bytes: hex::encode(&canonical_bytes),
+    };
+
+    let receipt = OpReceipt::new(
+        req.client_op_id.to_string(),
+        "boost_begin".to_string(),
+        None,
+        serde_json::to_string(&response)?,
+    );
+    PowRepository::create_op_receipt(&pool, &receipt).await?;
+
+    Ok(Json(response))
+}
+
+pub async fn boost_commit(
+    State(pool): State<DbPool>,
+    Json(req): Json<BoostCommitRequest>,
+) -> Result<Json<BoostCommitResponse>> {
+    let op_id = req.op_id.to_string();
+    let request_hash = commit_request_hash(json!({
+        "challenge_id": req.challenge_id,
+        "thread_id": req.thread_id,
+        "proof": req.proof,
+        "user_pubkey_hex": req.user_pubkey_hex,
+    }))?;
+
+    if let Some(receipt) = PowRepository::find_op_receipt(&pool, &op_id, "boost_commit").await? {
+        return replay_commit(&receipt, &request_hash).map(Json);
+    }
+
+    let challenge = PowRepository::find_challenge(&pool, &req.challenge_id)
+        .await?
+        .ok_or(AppError::ChallengeNotFound)?;
+
+    if challenge.is_expired() {
+        return Err(AppError::ChallengeExpired);
+    }
+
+    // A boost challenge only pays for the thread it was issued for
+    if challenge.scope != "boost"
+        || challenge.user_pubkey_hex != req.user_pubkey_hex
+        || challenge.thread_id != req.thread_id
+    {
+        return Err(AppError::ChallengeNotFound);
+    }
+
+    let config = Config::new().unwrap();
+    validate_timestamp(req.proof.timestamp_i64, config.pow_challenge_ttl_seconds as i64)?;
+
+    let canonical_params = canonical_params(&req.user_pubkey_hex, req.thread_id, req.proof.timestamp_i64);
+    let (is_valid, solved_hash) = verify_proof_v1(
+        &canonical_params,
+        req.proof.nonce_u64,
+        &challenge.required_prefix_hex,
+    );
+
+    if !is_valid {
+        return Err(AppError::InvalidProofOfWork);
+    }
+
+    let difficulty = calculate_pow_difficulty(&solved_hash);
+
+    // The boost, the thread's score and the commit record land together or not at all
+    let mut tx = pool.begin().await?;
+
+    if PowRepository::find_spent_commit(&mut *tx, &req.challenge_id, &solved_hash)
+        .await?
+        .is_some()
+    {
+        return Err(AppError::ChallengeUsed);
+    }
+
+    validate_reply_target(&mut tx, req.thread_id, None).await?;
+
+    let now = Utc::now();
+    BoostRepository::create(
+        &mut *tx,
+        &ThreadBoost {
+            id: 0,
+            thread_id: req.thread_id,
+            booster_pubkey: req.user_pubkey_hex.clone(),
+            pow_nonce: req.proof.nonce_u64 as i64,
+            pow_hash: solved_hash.clone(),
+            pow_challenge_id: req.challenge_id.clone(),
+            pow_difficulty: difficulty,
+            created_at: now,
+        },
+    )
+    .await?;
+    ThreadRepository::record_boost(&mut *tx, req.thread_id, difficulty).await?;
+
+    let commit = PowCommit {
+        id: Uuid::new_v4().to_string(),
+        challenge_id: req.challenge_id,
+        nonce_u64: req.proof.nonce_u64 as i64,
+        miner_version: req.proof.miner_version as i32,
+        timestamp_i64: req.proof.timestamp_i64,
+        solved_hash_hex: solved_hash,
+        thread_id: Some(req.thread_id),
+        post_id: None,
+        verified: true,
+        created_at: now,
+    };
+    PowRepository::create_commit(&mut *tx, &commit).await?;
+
+    let response = BoostCommitResponse {
+        thread_id: req.thread_id,
+        difficulty,
+    };
+    let receipt = OpReceipt::new(
+        op_id,
+        "boost_commit".to_string(),
+        Some(request_hash),
+        serde_json::to_string(&response)?,
+    );
+    PowRepository::create_op_receipt(&mut *tx, &receipt).await?;
+
+    tx.commit().await?;
+
+    Ok(Json(response))
+}
+
+pub async fn contributors(
+    State(pool): State<DbPool>,
+    Path(thread_id): Path<i64>,
+) -> Result<Json<ContributorsResponse>> {
+    let thread = ThreadRepository::find_by_id(&pool, thread_id)
+        .await?
+        .ok_or(AppError::ThreadNotFound)?;
+
+    Ok(Json(ContributorsResponse {
+        thread_id,
+        bump_score: thread.bump_score,
+        contributors: BoostRepository::contributors(&pool, thread_id).await?,
+    }))
+}
\ No newline at end of file
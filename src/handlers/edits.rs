@@ -0,0 +1,583 @@
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::Digest;
+use sqlx::SqliteConnection;
+use uuid::Uuid;
+
+use crate::{
+    config::Config,
+    db::{
+        AttachmentRepository, BoardRepository, ContentHashRepository, DbPool, DeletedContentRepository, PostRepository,
+        PowRepository, RevisionRepository, ThreadRepository,
+    },
+    error::{AppError, Result},
+    handlers::api::{
+        commit_request_hash, replay_commit, validate_draft, validate_reply_target,
+        validate_timestamp, SignedAuth, ThreadBeginResponse,
+    },
+    identity::{edit_message, parse_pubkey, verify_signature},
+    models::{Board, DeletedContent, OpReceipt, Post, PostRevision, PowChallenge, PowCommit, Thread},
+    pow::{calculate_pow_difficulty, canonical_bytes_v1, sha256_hex, verify_proof_v1, CanonicalParams, PostDraft, ProofOfWork},
+    uniqueness::content_hash,
+};
+
+#[derive(Deserialize)]
+pub struct EditBeginRequest {
+    pub client_op_id: Uuid,
+    pub thread_id: i64,
+    pub post_id: Option<i64>, // None to edit the opening post
+    pub post_draft: PostDraft,
+    pub user_pubkey_hex: String,
+    pub timestamp_i64: i64,
+    /// Signature over `identity::edit_message` for this draft and timestamp
+    pub signature_hex: String,
+}
+
+#[derive(Deserialize)]
+pub struct EditCommitRequest {
+    pub op_id: Uuid,
+    pub challenge_id: String,
+    pub thread_id: i64,
+    pub post_id: Option<i64>,
+    pub post_draft: PostDraft,
+    pub proof: ProofOfWork,
+    pub user_pubkey_hex: String,
+    pub signature_hex: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct EditCommitResponse {
+    pub thread_id: i64,
+    pub post_id: Option<i64>,
+    pub revision: i64,
+}
+
+/// Signed with `SignedAuth` for the purpose `delete_<thread_id>_<post_id>`,
+/// with post id 0 for the opening post
+#[derive(Deserialize)]
+pub struct DeleteRequest {
+    #[serde(flatten)]
+    pub auth: SignedAuth,
+    pub thread_id: i64,
+    pub post_id: Option<i64>, // None to delete the opening post
+}
+
+#[derive(Serialize)]
+pub struct DeleteResponse {
+    pub thread_id: i64,
+    pub post_id: Option<i64>,
+    pub deleted_at: String,
+}
+
+#[derive(Deserialize)]
+pub struct HistoryQuery {
+    pub post_id: Option<i64>, // None for the opening post
+}
+
+#[derive(Serialize)]
+pub struct HistoryResponse {
+    pub thread_id: i64,
+    pub post_id: Option<i64>,
+    pub revisions: Vec<PostRevision>,
+}
+
+/// A post as it stands before an edit: a thread's opening post, or one of its replies
+pub(crate) struct EditTarget {
+    pub thread: Thread,
+    pub post: Option<Post>,
+}
+
+impl EditTarget {
+    pub(crate) async fn find(conn: &mut SqliteConnection, thread_id: i64, post_id: Option<i64>) -> Result<Self> {
+        let thread = ThreadRepository::find_by_id(&mut *conn, thread_id)
+            .await?
+            .ok_or(AppError::ThreadNotFound)?;
+
+        let post = match post_id {
+            Some(post_id) => {
+                let post = PostRepository::find_by_id(&mut *conn, post_id)
+                    .await?
+                    .ok_or(AppError::NotFound)?;
+                if post.thread_id != thread.id {
+                    return Err(AppError::NotFound);
+                }
+                Some(post)
+            }
+            None => None,
+        };
+
+        Ok(EditTarget { thread, post })
+    }
+
+    fn post_id(&self) -> Option<i64> {
+        self.post.as_ref().map(|post| post.id)
+    }
+
+    pub(crate) fn is_deleted(&self) -> bool {
+        match &self.post {
+            Some(post) => post.deleted_at.is_some(),
+            None => self.thread.deleted_at.is_some(),
+        }
+    }
+
+    fn author_pubkey(&self) -> Option<&str> {
+        match &self.post {
+            Some(post) => post.author_pubkey.as_deref(),
+            None => self.thread.author_pubkey.as_deref(),
+        }
+    }
+
+    /// What the post says now, to hold for moderators once it's tombstoned
+    fn deleted_content(&self, deleted_at: DateTime<Utc>, purge_after: DateTime<Utc>) -> DeletedContent {
+        let (content, image_path, image_filename) = match &self.post {
+            Some(post) => (&post.content, &post.image_path, &post.image_filename),
+            None => (&self.thread.content, &self.thread.image_path, &self.thread.image_filename),
+        };
+
+        DeletedContent {
+            id: 0,
+            thread_id: self.thread.id,
+            post_id: self.post_id(),
+            title: self.post.is_none().then(|| self.thread.title.clone()),
+            content: content.clone(),
+            image_path: image_path.clone(),
+            image_filename: image_filename.clone(),
+            author_pubkey: self.author_pubkey().map(str::to_string),
+            deleted_at,
+            purge_after,
+        }
+    }
+
+    /// Revision 0: the post as first committed, anchored by its original proof
+    pub(crate) fn original_revision(&self) -> PostRevision {
+        let (content, pow_nonce, pow_hash, pow_challenge_id, pow_difficulty, created_at) = match &self.post {
+            Some(post) => (&post.content, post.pow_nonce, &post.pow_hash, &post.pow_challenge_id, post.pow_difficulty, post.created_at),
+            None => {
+                let thread = &self.thread;
+                (&thread.content, thread.pow_nonce, &thread.pow_hash, &thread.pow_challenge_id, thread.pow_difficulty, thread.created_at)
+            }
+        };
+
+        PostRevision {
+            id: 0,
+            thread_id: self.thread.id,
+            post_id: self.post_id(),
+            revision: 0,
+            title: self.post.is_none().then(|| self.thread.title.clone()),
+            content: content.clone(),
+            author_pubkey: self.author_pubkey().map(str::to_string),
+            signature_hex: None,
+            pow_nonce,
+            pow_hash: pow_hash.clone(),
+            pow_challenge_id: pow_challenge_id.clone(),
+            pow_difficulty,
+            created_at,
+        }
+    }
+}
+
+/// Load the post an edit targets, which must be in an open thread and written by the editor
+async fn editable_target(
+    conn: &mut SqliteConnection,
+    thread_id: i64,
+    post_id: Option<i64>,
+    pubkey_hex: &str,
+) -> Result<EditTarget> {
+    // Same rules as replying: no edits once a thread is locked or archived
+    validate_reply_target(&mut *conn, thread_id, post_id).await?;
+    let target = EditTarget::find(&mut *conn, thread_id, post_id).await?;
+
+    if target.author_pubkey() != Some(pubkey_hex) {
+        return Err(AppError::Unauthorized);
+    }
+
+    if target.is_deleted() {
+        return Err(AppError::PostDeleted);
+    }
+
+    Ok(target)
+}
+
+/// On a board in uniqueness mode, the edited text's content hash, refused if another
+/// post holds it. The text is hashed with the post's own attachments, as it was when
+/// first posted, and the hash this post already holds doesn't count against it.
+/// Returns None when there's nothing new to record.
+async fn check_unique_edit(
+    conn: &mut SqliteConnection,
+    board: &Board,
+    thread_id: i64,
+    post_id: Option<i64>,
+    post_draft: &PostDraft,
+) -> Result<Option<String>> {
+    if !board.unique_content {
+        return Ok(None);
+    }
+
+    let attachments = AttachmentRepository::hashes_for_post(&mut *conn, thread_id, post_id).await?;
+    let hash = content_hash(&board.normalization(), &post_draft.body, &attachments);
+    if ContentHashRepository::owned_by(&mut *conn, board.id, &hash, thread_id, post_id).await? {
+        return Ok(None);
+    }
+    if ContentHashRepository::exists(&mut *conn, board.id, &hash).await? {
+        return Err(AppError::DuplicateContent);
+    }
+
+    Ok(Some(hash))
+}
+
+/// Edits replace text only; attachments and quote edges stay as first posted
+fn validate_edit_draft(config: &Config, post_id: Option<i64>, post_draft: &PostDraft) -> Result<()> {
+    validate_draft(config, post_id.is_none(), post_draft)?;
+
+    if !post_draft.attachments.is_empty() {
+        return Err(AppError::Validation("Edits can't change attachments".to_string()));
+    }
+    if post_draft.poll.is_some() {
+        return Err(AppError::Validation("Edits can't add a poll".to_string()));
+    }
+
+    Ok(())
+}
+
+fn verify_edit_signature(
+    pubkey_hex: &str,
+    thread_id: i64,
+    post_id: Option<i64>,
+    timestamp_i64: i64,
+    post_draft: &PostDraft,
+    signature_hex: &str,
+) -> Result<()> {
+    let draft_hash = sha256_hex(&serde_json::to_vec(post_draft)?);
+    let message = edit_message(pubkey_hex, thread_id, post_id.unwrap_or(0), timestamp_i64, &draft_hash);
+
+    if !verify_signature(pubkey_hex, &message, signature_hex) {
+        return Err(AppError::InvalidSignature);
+    }
+
+    Ok(())
+}
+
+fn canonical_params(
+    pubkey_hex: &str,
+    thread_id: i64,
+    post_id: Option<i64>,
+    timestamp_i64: i64,
+    post_draft: &PostDraft,
+) -> CanonicalParams {
+    CanonicalParams {
+        user_pubkey_hex: pubkey_hex.to_string(),
+        scope: "e".to_string(), // 'e' for edit
+        board_id: 0,
+        thread_id: thread_id as u64,
+        parent_id: post_id.unwrap_or(0) as u64, // the post being edited
+        timestamp_i64,
+        post_draft: post_draft.clone(),
+    }
+}
+
+pub async fn edit_begin(
+    State(pool): State<DbPool>,
+    Json(req): Json<EditBeginRequest>,
+) -> Result<Json<ThreadBeginResponse>> {
+    if let Some(receipt) = PowRepository::find_op_receipt(&pool, &req.client_op_id.to_string(), "edit_begin").await? {
+        let response: ThreadBeginResponse = serde_json::from_str(&receipt.result_json)
+            .map_err(|_| AppError::Internal)?;
+        return Ok(Json(response));
+    }
+
+    parse_pubkey(&req.user_pubkey_hex).ok_or(AppError::InvalidPublicKey)?;
+    validate_timestamp(req.timestamp_i64, 0)?;
+    verify_edit_signature(
+        &req.user_pubkey_hex,
+        req.thread_id,
+        req.post_id,
+        req.timestamp_i64,
+        &req.post_draft,
+        &req.signature_hex,
+    )?;
+
+    let config = Config::new().unwrap();
+    validate_edit_draft(&config, req.post_id, &req.post_draft)?;
+
+    let mut conn = pool.acquire().await?;
+    let target = editable_target(&mut conn, req.thread_id, req.post_id, &req.user_pubkey_hex).await?;
+    let board = BoardRepository::find_by_id(&mut *conn, target.thread.board_id)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    check_unique_edit(&mut conn, &board, req.thread_id, req.post_id, &req.post_draft).await?;
+
+    let canonical_params = canonical_params(
+        &req.user_pubkey_hex,
+        req.thread_id,
+        req.post_id,
+        req.timestamp_i64,
+        &req.post_draft,
+    );
+    let canonical_bytes = canonical_bytes_v1(&canonical_params);
+    let post_json = serde_json::to_string(&req.post_draft)?;
+    let post_bytes_hash = sha2::Sha256::digest(post_json.as_bytes()).to_vec();
+
+    let challenge = PowChallenge::new(
+        req.user_pubkey_hex,
+        "edit".to_string(),
+        0,
+        req.thread_id,
+        req.post_id.unwrap_or(0),
+        post_bytes_hash.clone(),
+        config.edit_pow_prefix,
+        canonical_bytes.clone(),
+        config.pow_challenge_ttl_seconds,
+    );
+    PowRepository::create_challenge(&pool, &challenge).await?;
+
+    let response = ThreadBeginResponse {
+        challenge_id: challenge.id.clone(),
+        required_prefix_hex: challenge.required_prefix_hex,
+        challenge_version: 1,
+        op_id: req.client_op_id,
+        expires_at: challenge.expires_at.to_rfc3339(),
+        post_bytes_hash: hex::encode(&post_bytes_hash),
+        canonical_bytes: hex::encode(&canonical_bytes),
+    };
+
+    let receipt = OpReceipt::new(
+        req.client_op_id.to_string(),
+        "edit_begin".to_string(),
+        None,
+        serde_json::to_string(&response)?,
+    );
+    PowRepository::create_op_receipt(&pool, &receipt).await?;
+
+    Ok(Json(response))
+}
+
+pub async fn edit_commit(
+    State(pool): State<DbPool>,
+    Json(req): Json<EditCommitRequest>,
+) -> Result<Json<EditCommitResponse>> {
+    let op_id = req.op_id.to_string();
+    let request_hash = commit_request_hash(json!({
+        "challenge_id": req.challenge_id,
+        "thread_id": req.thread_id,
+        "post_id": req.post_id,
+        "post_draft": req.post_draft,
+        "proof": req.proof,
+        "user_pubkey_hex": req.user_pubkey_hex,
+        "signature_hex": req.signature_hex,
+    }))?;
+
+    if let Some(receipt) = PowRepository::find_op_receipt(&pool, &op_id, "edit_commit").await? {
+        return replay_commit(&receipt, &request_hash).map(Json);
+    }
+
+    let challenge = PowRepository::find_challenge(&pool, &req.challenge_id)
+        .await?
+        .ok_or(AppError::ChallengeNotFound)?;
+
+    if challenge.is_expired() {
+        return Err(AppError::ChallengeExpired);
+    }
+
+    if challenge.scope != "edit" || challenge.user_pubkey_hex != req.user_pubkey_hex {
+        return Err(AppError::ChallengeNotFound);
+    }
+
+    let config = Config::new().unwrap();
+    validate_timestamp(req.proof.timestamp_i64, config.pow_challenge_ttl_seconds as i64)?;
+    validate_edit_draft(&config, req.post_id, &req.post_draft)?;
+    verify_edit_signature(
+        &req.user_pubkey_hex,
+        req.thread_id,
+        req.post_id,
+        req.proof.timestamp_i64,
+        &req.post_draft,
+        &req.signature_hex,
+    )?;
+
+    let canonical_params = canonical_params(
+        &req.user_pubkey_hex,
+        req.thread_id,
+        req.post_id,
+        req.proof.timestamp_i64,
+        &req.post_draft,
+    );
+    let (is_valid, solved_hash) = verify_proof_v1(
+        &canonical_params,
+        req.proof.nonce_u64,
+        &challenge.required_prefix_hex,
+    );
+
+    if !is_valid {
+        return Err(AppError::InvalidProofOfWork);
+    }
+
+    let difficulty = calculate_pow_difficulty(&solved_hash);
+
+    // The revision log and the post's new text land together or not at all
+    let mut tx = pool.begin().await?;
+
+    if PowRepository::find_spent_commit(&mut *tx, &req.challenge_id, &solved_hash)
+        .await?
+        .is_some()
+    {
+        return Err(AppError::ChallengeUsed);
+    }
+
+    let target = editable_target(&mut tx, req.thread_id, req.post_id, &req.user_pubkey_hex).await?;
+    let board = BoardRepository::find_by_id(&mut *tx, target.thread.board_id)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    let unique_hash = check_unique_edit(&mut tx, &board, req.thread_id, req.post_id, &req.post_draft).await?;
+
+    // The first edit also records the original, so the log always starts at revision 0
+    let mut revision = RevisionRepository::next_revision(&mut *tx, req.thread_id, req.post_id).await?;
+    if revision == 0 {
+        RevisionRepository::create(&mut *tx, &target.original_revision()).await?;
+        revision = 1;
+    }
+
+    let now = Utc::now();
+    RevisionRepository::create(
+        &mut *tx,
+        &PostRevision {
+            id: 0,
+            thread_id: req.thread_id,
+            post_id: req.post_id,
+            revision,
+            title: req.post_id.is_none().then(|| req.post_draft.title.clone()),
+            content: req.post_draft.body.clone(),
+            author_pubkey: Some(req.user_pubkey_hex.clone()),
+            signature_hex: Some(req.signature_hex.clone()),
+            pow_nonce: Some(req.proof.nonce_u64 as i64),
+            pow_hash: Some(solved_hash.clone()),
+            pow_challenge_id: Some(req.challenge_id.clone()),
+            pow_difficulty: Some(difficulty),
+            created_at: now,
+        },
+    )
+    .await?;
+
+    match req.post_id {
+        Some(post_id) => PostRepository::edit(&mut *tx, post_id, &req.post_draft.body).await?,
+        None => ThreadRepository::edit(&mut *tx, req.thread_id, &req.post_draft.title, &req.post_draft.body).await?,
+    }
+
+    if let Some(hash) = &unique_hash {
+        ContentHashRepository::record(&mut *tx, board.id, hash, req.thread_id, req.post_id).await?;
+    }
+
+    // Edit work isn't credited to reputation; it only pays for the edit
+    let commit = PowCommit {
+        id: Uuid::new_v4().to_string(),
+        challenge_id: req.challenge_id,
+        nonce_u64: req.proof.nonce_u64 as i64,
+        miner_version: req.proof.miner_version as i32,
+        timestamp_i64: req.proof.timestamp_i64,
+        solved_hash_hex: solved_hash,
+        thread_id: Some(req.thread_id),
+        post_id: req.post_id,
+        verified: true,
+        created_at: now,
+    };
+    PowRepository::create_commit(&mut *tx, &commit).await?;
+
+    let response = EditCommitResponse {
+        thread_id: req.thread_id,
+        post_id: req.post_id,
+        revision,
+    };
+    let receipt = OpReceipt::new(
+        op_id,
+        "edit_commit".to_string(),
+        Some(request_hash),
+        serde_json::to_string(&response)?,
+    );
+    PowRepository::create_op_receipt(&mut *tx, &receipt).await?;
+
+    tx.commit().await?;
+
+    Ok(Json(response))
+}
+
+/// Tombstone a post at its author's signed request. The proof of work and revision
+/// proofs stay; the text moves to `deleted_content` until the retention window ends.
+/// Deletion works in locked and archived threads too.
+///
+/// Deleting an opening post only tombstones it: the thread stays live, listed and
+/// counted in the board's `thread_count`, because its replies belong to other authors.
+/// The board's `post_count` drops by one either way. Expired content is purged by a
+/// background task in `main`.
+pub async fn delete(
+    State(pool): State<DbPool>,
+    Json(req): Json<DeleteRequest>,
+) -> Result<Json<DeleteResponse>> {
+    req.auth
+        .verify(&format!("delete_{}_{}", req.thread_id, req.post_id.unwrap_or(0)))?;
+
+    let config = Config::new().unwrap();
+    let mut tx = pool.begin().await?;
+
+    let target = EditTarget::find(&mut tx, req.thread_id, req.post_id).await?;
+    if target.author_pubkey() != Some(req.auth.user_pubkey_hex.as_str()) {
+        return Err(AppError::Unauthorized);
+    }
+    if target.is_deleted() {
+        return Err(AppError::PostDeleted);
+    }
+
+    let now = Utc::now();
+    let purge_after = now + Duration::days(config.deleted_retention_days);
+
+    DeletedContentRepository::create(&mut *tx, &target.deleted_content(now, purge_after)).await?;
+
+    match &target.post {
+        Some(post) => {
+            PostRepository::tombstone(&mut *tx, post.id).await?;
+            ThreadRepository::remove_reply(&mut *tx, target.thread.id, post.pow_difficulty.unwrap_or(0.0)).await?;
+        }
+        None => ThreadRepository::tombstone(&mut *tx, target.thread.id).await?,
+    }
+    BoardRepository::remove_post(&mut *tx, target.thread.board_id).await?;
+    AttachmentRepository::unlink(&mut *tx, req.thread_id, req.post_id).await?;
+    RevisionRepository::redact(&mut *tx, req.thread_id, req.post_id).await?;
+
+    tx.commit().await?;
+
+    Ok(Json(DeleteResponse {
+        thread_id: req.thread_id,
+        post_id: req.post_id,
+        deleted_at: now.to_rfc3339(),
+    }))
+}
+
+/// Every version of a post with the proof behind it. A post that was never
+/// edited has just its original as revision 0.
+pub(crate) async fn load_history(pool: &DbPool, thread_id: i64, post_id: Option<i64>) -> Result<(EditTarget, Vec<PostRevision>)> {
+    let target = EditTarget::find(&mut *pool.acquire().await?, thread_id, post_id).await?;
+    let mut revisions = RevisionRepository::list_for_post(pool, thread_id, post_id).await?;
+
+    if revisions.is_empty() {
+        revisions.push(target.original_revision());
+    }
+
+    Ok((target, revisions))
+}
+
+pub async fn history(
+    State(pool): State<DbPool>,
+    Path(thread_id): Path<i64>,
+    Query(query): Query<HistoryQuery>,
+) -> Result<Json<HistoryResponse>> {
+    let (_, revisions) = load_history(&pool, thread_id, query.post_id).await?;
+
+    Ok(Json(HistoryResponse {
+        thread_id,
+        post_id: query.post_id,
+        revisions,
+    }))
+}
\ No newline at end of file
@@ -4,6 +4,7 @@ use axum::response::Html;
 use crate::{
     db::{BoardRepository, DbPool},
     error::Result,
+    markup::escape_html,
 };
 
 pub async fn index(State(pool): State<DbPool>) -> Result<Html<String>> {
@@ -47,10 +48,10 @@ pub async fn index(State(pool): State<DbPool>) -> Result<Html<String>> {
                         {} threads, {} posts
                     </div>
                 </div>"#,
-                board.slug,
-                board.slug,
-                board.name,
-                board.description.as_deref().unwrap_or("No description"),
+                escape_html(&board.slug),
+                escape_html(&board.slug),
+                escape_html(&board.name),
+                escape_html(board.description.as_deref().unwrap_or("No description")),
                 board.thread_count,
                 board.post_count
             ))
@@ -0,0 +1,233 @@
+use axum::{extract::State, Json};
+use serde::{Deserialize, Serialize};
+use sha2::Digest;
+use uuid::Uuid;
+
+use crate::{
+    config::Config,
+    db::{DbPool, DirectMessageRepository, PowRepository},
+    error::{AppError, Result},
+    handlers::api::{required_prefix_for, SignedAuth, ThreadBeginResponse},
+    identity::parse_pubkey,
+    models::{DirectMessage, OpReceipt, PowChallenge, PowCommit},
+    pow::{canonical_bytes_v1, verify_proof_v1, CanonicalParams, PostDraft, ProofOfWork},
+};
+
+#[derive(Deserialize)]
+pub struct DmBeginRequest {
+    pub client_op_id: Uuid,
+    pub sender_pubkey_hex: String,
+    pub recipient_pubkey_hex: String,
+    pub ciphertext_hex: String,
+    pub timestamp_i64: i64,
+    /// Signature over `auth_message("discount", ..)`, required for a reputation discount
+    #[serde(default)]
+    pub discount_signature_hex: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct DmCommitRequest {
+    pub op_id: Uuid,
+    pub challenge_id: String,
+    pub sender_pubkey_hex: String,
+    pub recipient_pubkey_hex: String,
+    pub ciphertext_hex: String,
+    pub proof: ProofOfWork,
+}
+
+#[derive(Serialize)]
+pub struct DmCommitResponse {
+    pub message_id: i64,
+}
+
+#[derive(Deserialize)]
+pub struct InboxRequest {
+    #[serde(flatten)]
+    pub auth: SignedAuth,
+    #[serde(default)]
+    pub after_id: i64,
+}
+
+#[derive(Serialize)]
+pub struct InboxResponse {
+    pub messages: Vec<DirectMessage>,
+}
+
+fn validate_message(config: &Config, sender: &str, recipient: &str, ciphertext_hex: &str) -> Result<()> {
+    if parse_pubkey(sender).is_none() || parse_pubkey(recipient).is_none() {
+        return Err(AppError::InvalidPublicKey);
+    }
+    if ciphertext_hex.is_empty() || hex::decode(ciphertext_hex).is_err() {
+        return Err(AppError::Validation("Ciphertext must be non-empty hex".to_string()));
+    }
+    if ciphertext_hex.len() / 2 > config.dm_max_ciphertext_bytes {
+        return Err(AppError::Validation(format!(
+            "Ciphertext exceeds {} bytes",
+            config.dm_max_ciphertext_bytes
+        )));
+    }
+
+    Ok(())
+}
+
+pub async fn dm_begin(
+    State(pool): State<DbPool>,
+    Json(req): Json<DmBeginRequest>,
+) -> Result<Json<ThreadBeginResponse>> {
+    if let Some(receipt) = PowRepository::find_op_receipt(&pool, &req.client_op_id.to_string(), "dm_begin").await? {
+        let response: ThreadBeginResponse = serde_json::from_str(&receipt.result_json)
+            .map_err(|_| AppError::Internal)?;
+        return Ok(Json(response));
+    }
+
+    let config = Config::new().unwrap();
+    validate_message(&config, &req.sender_pubkey_hex, &req.recipient_pubkey_hex, &req.ciphertext_hex)?;
+
+    let post_draft = PostDraft::direct_message(&req.recipient_pubkey_hex, &req.ciphertext_hex);
+    let canonical_params = CanonicalParams {
+        user_pubkey_hex: req.sender_pubkey_hex.clone(),
+        scope: "d".to_string(), // 'd' for direct message
+        board_id: 0,
+        thread_id: 0,
+        parent_id: 0,
+        timestamp_i64: req.timestamp_i64,
+        post_draft: post_draft.clone(),
+    };
+
+    let canonical_bytes = canonical_bytes_v1(&canonical_params);
+    let post_json = serde_json::to_string(&post_draft)?;
+    let post_bytes_hash = sha2::Sha256::digest(post_json.as_bytes()).to_vec();
+
+    let required_prefix = required_prefix_for(
+        &pool,
+        &config,
+        &req.sender_pubkey_hex,
+        req.timestamp_i64,
+        req.discount_signature_hex.as_deref(),
+    )
+    .await?;
+
+    let challenge = PowChallenge::new(
+        req.sender_pubkey_hex,
+        "dm".to_string(),
+        0,
+        0,
+        0,
+        post_bytes_hash.clone(),
+        required_prefix,
+        canonical_bytes.clone(),
+        config.pow_challenge_ttl_seconds,
+    );
+
+    PowRepository::create_challenge(&pool, &challenge).await?;
+
+    let response = ThreadBeginResponse {
+        challenge_id: challenge.id.clone(),
+        required_prefix_hex: challenge.required_prefix_hex,
+        challenge_version: 1,
+        op_id: req.client_op_id,
+        expires_at: challenge.expires_at.to_rfc3339(),
+        post_bytes_hash: hex::encode(&post_bytes_hash),
+        canonical_bytes: hex::encode(&canonical_bytes),
+    };
+
+    let receipt = OpReceipt::new(
+        req.client_op_id.to_string(),
+        "dm_begin".to_string(),
+        None,
+        serde_json::to_string(&response)?,
+    );
+    PowRepository::create_op_receipt(&pool, &receipt).await?;
+
+    Ok(Json(response))
+}
+
+pub async fn dm_commit(
+    State(pool): State<DbPool>,
+    Json(req): Json<DmCommitRequest>,
+) -> Result<Json<DmCommitResponse>> {
+    let challenge = PowRepository::find_challenge(&pool, &req.challenge_id)
+        .await?
+        .ok_or(AppError::ChallengeNotFound)?;
+
+    if challenge.is_expired() {
+        return Err(AppError::ChallengeExpired);
+    }
+
+    if challenge.scope != "dm" || challenge.user_pubkey_hex != req.sender_pubkey_hex {
+        return Err(AppError::ChallengeNotFound);
+    }
+
+    let canonical_params = CanonicalParams {
+        user_pubkey_hex: req.sender_pubkey_hex.clone(),
+        scope: "d".to_string(),
+        board_id: 0,
+        thread_id: 0,
+        parent_id: 0,
+        timestamp_i64: req.proof.timestamp_i64,
+        post_draft: PostDraft::direct_message(&req.recipient_pubkey_hex, &req.ciphertext_hex),
+    };
+
+    let (is_valid, solved_hash) = verify_proof_v1(
+        &canonical_params,
+        req.proof.nonce_u64,
+        &challenge.required_prefix_hex,
+    );
+
+    if !is_valid {
+        return Err(AppError::InvalidProofOfWork);
+    }
+
+    // The message and its commit record land together or not at all
+    let mut tx = pool.begin().await?;
+
+    // A reused challenge, or one proof replayed under fresh challenges to spam a recipient
+    if PowRepository::find_spent_commit(&mut *tx, &req.challenge_id, &solved_hash)
+        .await?
+        .is_some()
+    {
+        return Err(AppError::ChallengeUsed);
+    }
+
+    let message_id = DirectMessageRepository::create(
+        &mut *tx,
+        &req.sender_pubkey_hex,
+        &req.recipient_pubkey_hex,
+        &req.ciphertext_hex,
+        req.proof.nonce_u64 as i64,
+        &solved_hash,
+        &req.challenge_id,
+    )
+    .await?;
+
+    let commit = PowCommit {
+        id: Uuid::new_v4().to_string(),
+        challenge_id: req.challenge_id,
+        nonce_u64: req.proof.nonce_u64 as i64,
+        miner_version: req.proof.miner_version as i32,
+        timestamp_i64: req.proof.timestamp_i64,
+        solved_hash_hex: solved_hash,
+        thread_id: None,
+        post_id: None,
+        verified: true,
+        created_at: chrono::Utc::now(),
+    };
+    PowRepository::create_commit(&mut *tx, &commit).await?;
+
+    tx.commit().await?;
+
+    Ok(Json(DmCommitResponse { message_id }))
+}
+
+pub async fn inbox(
+    State(pool): State<DbPool>,
+    Json(req): Json<InboxRequest>,
+) -> Result<Json<InboxResponse>> {
+    req.auth.verify("inbox")?;
+
+    let messages =
+        DirectMessageRepository::list_for_recipient(&pool, &req.auth.user_pubkey_hex, req.after_id, 100)
+            .await?;
+
+    Ok(Json(InboxResponse { messages }))
+}
\ No newline at end of file
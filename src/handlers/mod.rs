@@ -1,4 +1,13 @@
 pub mod api;
+pub mod attachments;
 pub mod boards;
+pub mod boosts;
+pub mod edits;
 pub mod home;
+pub mod messages;
+pub mod moderation;
+pub mod notifications;
+pub mod polls;
+pub mod quotes;
+pub mod reactions;
 pub mod threads;
\ No newline at end of file
@@ -0,0 +1,95 @@
+use axum::{
+    extract::{Path, Query, State},
+    http::HeaderMap,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use sha2::Digest;
+
+use crate::{
+    config::Config,
+    db::{DbPool, DeletedContentRepository, UserRepository},
+    error::{AppError, Result},
+    models::DeletedContent,
+};
+
+const DELETED_PAGE_SIZE: i64 = 50;
+
+#[derive(Serialize)]
+pub struct RevokeReputationResponse {
+    pub pubkey_hex: String,
+    pub revoked: bool,
+}
+
+#[derive(Deserialize)]
+pub struct DeletedQuery {
+    #[serde(default)]
+    pub page: i64,
+}
+
+#[derive(Serialize)]
+pub struct DeletedContentResponse {
+    pub deleted: Vec<DeletedContent>,
+}
+
+/// Moderator endpoints are only enabled when MODERATOR_TOKEN is set
+fn require_moderator(headers: &HeaderMap) -> Result<()> {
+    let config = Config::new().unwrap();
+    let expected = config.moderator_token.ok_or(AppError::NotFound)?;
+
+    let provided = headers
+        .get("authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    match provided {
+        Some(provided) if tokens_match(provided, &expected) => Ok(()),
+        _ => Err(AppError::Unauthorized),
+    }
+}
+
+/// Compare digests of both tokens in constant time, so response timing leaks neither
+/// how much of the token matched nor its length
+fn tokens_match(provided: &str, expected: &str) -> bool {
+    let provided = sha2::Sha256::digest(provided.as_bytes());
+    let expected = sha2::Sha256::digest(expected.as_bytes());
+
+    provided
+        .iter()
+        .zip(expected.iter())
+        .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+        == 0
+}
+
+pub async fn revoke_reputation(
+    State(pool): State<DbPool>,
+    headers: HeaderMap,
+    Path(pubkey): Path<String>,
+) -> Result<Json<RevokeReputationResponse>> {
+    require_moderator(&headers)?;
+
+    let revoked = UserRepository::revoke_reputation(&pool, &pubkey).await?;
+    if !revoked {
+        return Err(AppError::NotFound);
+    }
+
+    Ok(Json(RevokeReputationResponse {
+        pubkey_hex: pubkey,
+        revoked,
+    }))
+}
+
+/// What authors deleted within the retention window, newest first
+pub async fn deleted_content(
+    State(pool): State<DbPool>,
+    headers: HeaderMap,
+    Query(query): Query<DeletedQuery>,
+) -> Result<Json<DeletedContentResponse>> {
+    require_moderator(&headers)?;
+
+    DeletedContentRepository::purge_expired(&pool).await?;
+    let offset = query.page.max(0) * DELETED_PAGE_SIZE;
+    let deleted = DeletedContentRepository::list(&pool, DELETED_PAGE_SIZE, offset).await?;
+
+    Ok(Json(DeletedContentResponse { deleted }))
+}
\ No newline at end of file
@@ -0,0 +1,166 @@
+use axum::{
+    extract::{Query, State},
+    response::Html,
+    Json,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    db::{DbPool, NotificationRepository},
+    error::Result,
+    handlers::api::SignedAuth,
+    markup::escape_html,
+    models::Notification,
+};
+
+#[derive(Serialize)]
+pub struct NotificationsResponse {
+    pub unread_count: i64,
+    pub notifications: Vec<Notification>,
+}
+
+#[derive(Deserialize)]
+pub struct MarkReadRequest {
+    #[serde(flatten)]
+    pub auth: SignedAuth,
+    pub up_to_id: i64,
+}
+
+#[derive(Serialize)]
+pub struct MarkReadResponse {
+    pub marked: u64,
+    pub unread_count: i64,
+}
+
+pub async fn list(
+    State(pool): State<DbPool>,
+    Json(auth): Json<SignedAuth>,
+) -> Result<Json<NotificationsResponse>> {
+    auth.verify("notifications")?;
+
+    let unread_count = NotificationRepository::count_unread(&pool, &auth.user_pubkey_hex).await?;
+    let notifications =
+        NotificationRepository::list_for_recipient(&pool, &auth.user_pubkey_hex, 100).await?;
+
+    Ok(Json(NotificationsResponse {
+        unread_count,
+        notifications,
+    }))
+}
+
+pub async fn mark_read(
+    State(pool): State<DbPool>,
+    Json(req): Json<MarkReadRequest>,
+) -> Result<Json<MarkReadResponse>> {
+    req.auth.verify("notifications")?;
+
+    let marked = NotificationRepository::mark_read(&pool, &req.auth.user_pubkey_hex, req.up_to_id).await?;
+    let unread_count = NotificationRepository::count_unread(&pool, &req.auth.user_pubkey_hex).await?;
+
+    Ok(Json(MarkReadResponse {
+        marked,
+        unread_count,
+    }))
+}
+
+/// HTML inbox, authenticated by a signed link (pubkey, timestamp and signature as query params)
+pub async fn page(
+    State(pool): State<DbPool>,
+    Query(auth): Query<SignedAuth>,
+) -> Result<Html<String>> {
+    auth.verify("notifications")?;
+
+    let unread_count = NotificationRepository::count_unread(&pool, &auth.user_pubkey_hex).await?;
+    let notifications =
+        NotificationRepository::list_for_recipient(&pool, &auth.user_pubkey_hex, 100).await?;
+
+    let html = format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+    <title>Notifications ({unread_count}) - haich2</title>
+    <style>
+        body {{ 
+            font-family: 'Courier New', monospace; 
+            max-width: 900px; 
+            margin: 0 auto; 
+            padding: 20px;
+            background-color: #3C4267;
+            color: #2DD2C1;
+            line-height: 1.5;
+        }}
+        .notification {{ 
+            border: 1px solid #50589C; 
+            margin: 10px 0; 
+            padding: 15px;
+            background-color: #636CCB;
+            border-radius: 4px;
+        }}
+        .notification.unread {{
+            border: 2px solid #2DD2C1;
+        }}
+        .notification-meta {{ 
+            color: #2DD2C1; 
+            font-size: 0.85em; 
+            opacity: 0.8;
+        }}
+        a {{ 
+            color: #2DD2C1; 
+            text-decoration: none; 
+        }}
+        a:hover {{ 
+            text-decoration: underline; 
+            color: #ffffff;
+        }}
+        .nav {{ margin: 20px 0; }}
+        .nav a {{ 
+            margin-right: 20px; 
+            padding: 8px 16px;
+            background-color: #50589C;
+            border-radius: 4px;
+        }}
+    </style>
+</head>
+<body>
+    <div class="nav">
+        <a href="/">Home</a>
+        <a href="/boards">Boards</a>
+    </div>
+    
+    <h2>Notifications</h2>
+    <p>{unread_count} unread for {pubkey}</p>
+    {items}
+</body>
+</html>"#,
+        unread_count = unread_count,
+        pubkey = auth.user_pubkey_hex.chars().take(16).collect::<String>(),
+        items = if notifications.is_empty() {
+            "<p>Nobody has replied to you yet.</p>".to_string()
+        } else {
+            notifications
+                .iter()
+                .map(|n| format!(
+                    r#"<div class="notification{}">
+                        <a href="/threads/{}{}">{} your {}</a>
+                        <div class="notification-meta">
+                            {} • from {}
+                        </div>
+                    </div>"#,
+                    if n.read_at.is_none() { " unread" } else { "" },
+                    n.thread_id,
+                    n.post_id.map(|id| format!("#p{}", id)).unwrap_or_default(),
+                    if n.kind == "quote" { "Quote of" } else { "Reply to" },
+                    match n.target_post_id {
+                        Some(id) => format!("post #{}", id),
+                        None => format!("thread #{}", n.target_thread_id),
+                    },
+                    n.created_at.format("%Y-%m-%d %H:%M"),
+                    escape_html(&n.actor_pubkey_hex.chars().take(16).collect::<String>())
+                ))
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+    );
+
+    Ok(Html(html))
+}
\ No newline at end of file
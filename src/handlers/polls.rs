@@ -0,0 +1,312 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::Digest;
+use sqlx::SqliteConnection;
+use uuid::Uuid;
+
+use crate::{
+    config::Config,
+    db::{DbPool, PollRepository, PowRepository},
+    error::{AppError, Result},
+    handlers::api::{commit_request_hash, replay_commit, validate_reply_target, validate_timestamp, ThreadBeginResponse},
+    identity::{parse_pubkey, verify_signature, vote_message},
+    models::{OpReceipt, Poll, PollTally, PollVote, PowChallenge, PowCommit},
+    pow::{calculate_pow_difficulty, canonical_bytes_v1, verify_proof_v1, CanonicalParams, PostDraft, ProofOfWork},
+};
+
+#[derive(Deserialize)]
+pub struct VoteBeginRequest {
+    pub client_op_id: Uuid,
+    pub poll_id: i64,
+    pub option_id: i64,
+    pub user_pubkey_hex: String,
+    pub timestamp_i64: i64,
+    /// Signature over `identity::vote_message` for this option and timestamp
+    pub signature_hex: String,
+}
+
+#[derive(Deserialize)]
+pub struct VoteCommitRequest {
+    pub op_id: Uuid,
+    pub challenge_id: String,
+    pub poll_id: i64,
+    pub option_id: i64,
+    pub proof: ProofOfWork,
+    pub user_pubkey_hex: String,
+    pub signature_hex: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct VoteCommitResponse {
+    pub poll_id: i64,
+    pub option_id: i64,
+    pub difficulty: f64,
+}
+
+#[derive(Serialize)]
+pub struct PollResults {
+    pub poll_id: i64,
+    pub thread_id: i64,
+    pub question: String,
+    pub total_voters: i64,
+    pub total_work: f64,
+    pub options: Vec<PollTally>,
+}
+
+/// Load a poll that can still take votes: its thread must be open and the
+/// voter mustn't have voted yet
+async fn open_poll(conn: &mut SqliteConnection, poll_id: i64, option_id: i64, pubkey_hex: &str) -> Result<Poll> {
+    let poll = PollRepository::find_by_id(&mut *conn, poll_id)
+        .await?
+        .ok_or(AppError::NotFound)?;
+
+    // Same rules as replying: voting closes when the thread is locked or archived
+    validate_reply_target(&mut *conn, poll.thread_id, None).await?;
+
+    if PollRepository::find_option(&mut *conn, poll.id, option_id).await?.is_none() {
+        return Err(AppError::Validation("Not an option in this poll".to_string()));
+    }
+
+    if PollRepository::has_voted(&mut *conn, poll.id, pubkey_hex).await? {
+        return Err(AppError::AlreadyVoted);
+    }
+
+    Ok(poll)
+}
+
+fn verify_vote_signature(
+    pubkey_hex: &str,
+    poll_id: i64,
+    option_id: i64,
+    timestamp_i64: i64,
+    signature_hex: &str,
+) -> Result<()> {
+    let message = vote_message(pubkey_hex, poll_id, option_id, timestamp_i64);
+
+    if !verify_signature(pubkey_hex, &message, signature_hex) {
+        return Err(AppError::InvalidSignature);
+    }
+
+    Ok(())
+}
+
+fn canonical_params(pubkey_hex: &str, poll: &Poll, option_id: i64, timestamp_i64: i64) -> CanonicalParams {
+    CanonicalParams {
+        user_pubkey_hex: pubkey_hex.to_string(),
+        scope: "v".to_string(), // 'v' for poll vote
+        board_id: 0,
+        thread_id: poll.thread_id as u64,
+        parent_id: 0,
+        timestamp_i64,
+        post_draft: PostDraft::poll_vote(poll.id, option_id),
+    }
+}
+
+pub async fn vote_begin(
+    State(pool): State<DbPool>,
+    Json(req): Json<VoteBeginRequest>,
+) -> Result<Json<ThreadBeginResponse>> {
+    if let Some(receipt) = PowRepository::find_op_receipt(&pool, &req.client_op_id.to_string(), "vote_begin").await? {
+        let response: ThreadBeginResponse = serde_json::from_str(&receipt.result_json)
+            .map_err(|_| AppError::Internal)?;
+        return Ok(Json(response));
+    }
+
+    parse_pubkey(&req.user_pubkey_hex).ok_or(AppError::InvalidPublicKey)?;
+    validate_timestamp(req.timestamp_i64, 0)?;
+    verify_vote_signature(
+        &req.user_pubkey_hex,
+        req.poll_id,
+        req.option_id,
+        req.timestamp_i64,
+        &req.signature_hex,
+    )?;
+
+    let mut conn = pool.acquire().await?;
+    let poll = open_poll(&mut conn, req.poll_id, req.option_id, &req.user_pubkey_hex).await?;
+
+    let config = Config::new().unwrap();
+    let canonical_params = canonical_params(&req.user_pubkey_hex, &poll, req.option_id, req.timestamp_i64);
+    let canonical_bytes = canonical_bytes_v1(&canonical_params);
+    let post_json = serde_json::to_string(&canonical_params.post_draft)?;
+    let post_bytes_hash = sha2::Sha256::digest(post_json.as_bytes()).to_vec();
+
+    let challenge = PowChallenge::new(
+        req.user_pubkey_hex,
+        "vote".to_string(),
+        0,
+        poll.thread_id,
+        0,
+        post_bytes_hash.clone(),
+        config.poll_vote_pow_prefix,
+        canonical_bytes.clone(),
+        config.pow_challenge_ttl_seconds,
+    );
+    PowRepository::create_challenge(&pool, &challenge).await?;
+
+    let response = ThreadBeginResponse {
+        challenge_id: challenge.id.clone(),
+        required_prefix_hex: challenge.required_prefix_hex,
+        challenge_version: 1,
+        op_id: req.client_op_id,
+        expires_at: challenge.expires_at.to_rfc3339(),
+        post_bytes_hash: hex::encode(&post_bytes_hash),
+        canonical_bytes: hex::encode(&canonical_bytes),
+    };
+
+    let receipt = OpReceipt::new(
+        req.client_op_id.to_string(),
+        "vote_begin".to_string(),
+        None,
+        serde_json::to_string(&response)?,
+    );
+    PowRepository::create_op_receipt(&pool, &receipt).await?;
+
+    Ok(Json(response))
+}
+
+pub async fn vote_commit(
+    State(pool): State<DbPool>,
+    Json(req): Json<VoteCommitRequest>,
+) -> Result<Json<VoteCommitResponse>> {
+    let op_id = req.op_id.to_string();
+    let request_hash = commit_request_hash(json!({
+        "challenge_id": req.challenge_id,
+        "poll_id": req.poll_id,
+        "option_id": req.option_id,
+        "proof": req.proof,
+        "user_pubkey_hex": req.user_pubkey_hex,
+        "signature_hex": req.signature_hex,
+    }))?;
+
+    if let Some(receipt) = PowRepository::find_op_receipt(&pool, &op_id, "vote_commit").await? {
+        return replay_commit(&receipt, &request_hash).map(Json);
+    }
+
+    let challenge = PowRepository::find_challenge(&pool, &req.challenge_id)
+        .await?
+        .ok_or(AppError::ChallengeNotFound)?;
+
+    if challenge.is_expired() {
+        return Err(AppError::ChallengeExpired);
+    }
+
+    if challenge.scope != "vote" || challenge.user_pubkey_hex != req.user_pubkey_hex {
+        return Err(AppError::ChallengeNotFound);
+    }
+
+    let config = Config::new().unwrap();
+    validate_timestamp(req.proof.timestamp_i64, config.pow_challenge_ttl_seconds as i64)?;
+    verify_vote_signature(
+        &req.user_pubkey_hex,
+        req.poll_id,
+        req.option_id,
+        req.proof.timestamp_i64,
+        &req.signature_hex,
+    )?;
+
+    // The vote and its commit record land together or not at all
+    let mut tx = pool.begin().await?;
+
+    let poll = open_poll(&mut tx, req.poll_id, req.option_id, &req.user_pubkey_hex).await?;
+
+    let canonical_params = canonical_params(&req.user_pubkey_hex, &poll, req.option_id, req.proof.timestamp_i64);
+    let (is_valid, solved_hash) = verify_proof_v1(
+        &canonical_params,
+        req.proof.nonce_u64,
+        &challenge.required_prefix_hex,
+    );
+
+    if !is_valid {
+        return Err(AppError::InvalidProofOfWork);
+    }
+
+    if PowRepository::find_spent_commit(&mut *tx, &req.challenge_id, &solved_hash)
+        .await?
+        .is_some()
+    {
+        return Err(AppError::ChallengeUsed);
+    }
+
+    let difficulty = calculate_pow_difficulty(&solved_hash);
+    let now = Utc::now();
+
+    // Vote work is tallied on the poll; it isn't credited to reputation
+    PollRepository::record_vote(
+        &mut *tx,
+        &PollVote {
+            id: 0,
+            poll_id: poll.id,
+            option_id: req.option_id,
+            voter_pubkey: req.user_pubkey_hex.clone(),
+            signature_hex: req.signature_hex.clone(),
+            pow_nonce: req.proof.nonce_u64 as i64,
+            pow_hash: solved_hash.clone(),
+            pow_challenge_id: req.challenge_id.clone(),
+            pow_difficulty: difficulty,
+            created_at: now,
+        },
+    )
+    .await?;
+
+    let commit = PowCommit {
+        id: Uuid::new_v4().to_string(),
+        challenge_id: req.challenge_id,
+        nonce_u64: req.proof.nonce_u64 as i64,
+        miner_version: req.proof.miner_version as i32,
+        timestamp_i64: req.proof.timestamp_i64,
+        solved_hash_hex: solved_hash,
+        thread_id: Some(poll.thread_id),
+        post_id: None,
+        verified: true,
+        created_at: now,
+    };
+    PowRepository::create_commit(&mut *tx, &commit).await?;
+
+    let response = VoteCommitResponse {
+        poll_id: poll.id,
+        option_id: req.option_id,
+        difficulty,
+    };
+    let receipt = OpReceipt::new(
+        op_id,
+        "vote_commit".to_string(),
+        Some(request_hash),
+        serde_json::to_string(&response)?,
+    );
+    PowRepository::create_op_receipt(&mut *tx, &receipt).await?;
+
+    tx.commit().await?;
+
+    Ok(Json(response))
+}
+
+/// A poll's tallies, for the thread page and API clients alike
+pub(crate) async fn load_results(pool: &DbPool, poll: Poll) -> Result<PollResults> {
+    let options = PollRepository::tally(pool, poll.id).await?;
+
+    Ok(PollResults {
+        poll_id: poll.id,
+        thread_id: poll.thread_id,
+        question: poll.question,
+        total_voters: options.iter().map(|option| option.voters).sum(),
+        total_work: options.iter().map(|option| option.work).sum(),
+        options,
+    })
+}
+
+pub async fn results(
+    State(pool): State<DbPool>,
+    Path(poll_id): Path<i64>,
+) -> Result<Json<PollResults>> {
+    let poll = PollRepository::find_by_id(&pool, poll_id)
+        .await?
+        .ok_or(AppError::NotFound)?;
+
+    Ok(Json(load_results(&pool, poll).await?))
+}
\ No newline at end of file
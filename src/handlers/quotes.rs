@@ -0,0 +1,179 @@
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use serde::{Deserialize, Serialize};
+use sqlx::SqliteConnection;
+use std::collections::HashMap;
+
+use crate::{
+    db::{BoardRepository, DbPool, PostRepository, ThreadRepository},
+    error::{AppError, Result},
+    markup::{quotes_in, render_post, Quote, QuoteLinks, QuoteTarget},
+    models::QuoteLocation,
+};
+
+/// Longest body a hover preview carries, in characters
+const PREVIEW_MAX_CHARS: usize = 500;
+
+#[derive(Deserialize)]
+pub struct QuoteQuery {
+    pub board: Option<String>,
+    pub id: i64,
+}
+
+#[derive(Serialize)]
+pub struct QuotePreview {
+    pub board_slug: String,
+    pub thread_id: i64,
+    pub post_id: Option<i64>, // None for a thread's opening post
+    pub thread_title: String,
+    pub created_at: String,
+    pub body_html: String,
+}
+
+/// Find what quotes point at. `>>123` is a post anywhere; `>>>/slug/123` is
+/// post 123 if it's on that board, otherwise thread 123 on that board. Takes three
+/// queries however many quotes there are; unresolved quotes are left out.
+pub(crate) async fn resolve_quotes(
+    conn: &mut SqliteConnection,
+    quotes: &[Quote],
+) -> Result<HashMap<Quote, QuoteTarget>> {
+    if quotes.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let mut slugs = Vec::new();
+    let mut thread_ids = Vec::new();
+    for quote in quotes {
+        if let Quote::Board { slug, id } = quote {
+            slugs.push(slug.clone());
+            thread_ids.push(*id);
+        }
+    }
+    let post_ids: Vec<i64> = quotes.iter().map(Quote::id).collect();
+
+    let posts: HashMap<i64, QuoteLocation> = PostRepository::locate_many(&mut *conn, &post_ids)
+        .await?
+        .into_iter()
+        .map(|location| (location.id, location))
+        .collect();
+    let boards: HashMap<String, i64> = if slugs.is_empty() {
+        HashMap::new()
+    } else {
+        BoardRepository::find_by_slugs(&mut *conn, &slugs)
+            .await?
+            .into_iter()
+            .map(|board| (board.slug, board.id))
+            .collect()
+    };
+    let threads: HashMap<i64, QuoteLocation> = if thread_ids.is_empty() {
+        HashMap::new()
+    } else {
+        ThreadRepository::locate_many(&mut *conn, &thread_ids)
+            .await?
+            .into_iter()
+            .map(|location| (location.id, location))
+            .collect()
+    };
+
+    let mut targets = HashMap::new();
+    for quote in quotes {
+        let target = match quote {
+            Quote::Post(id) => posts.get(id).map(|post| QuoteTarget {
+                thread_id: post.thread_id,
+                post_id: Some(post.id),
+            }),
+            Quote::Board { slug, id } => boards.get(slug).and_then(|board_id| {
+                match posts.get(id).filter(|post| post.board_id == *board_id) {
+                    Some(post) => Some(QuoteTarget {
+                        thread_id: post.thread_id,
+                        post_id: Some(post.id),
+                    }),
+                    None => threads
+                        .get(id)
+                        .filter(|thread| thread.board_id == *board_id)
+                        .map(|thread| QuoteTarget {
+                            thread_id: thread.id,
+                            post_id: None,
+                        }),
+                }
+            }),
+        };
+        if let Some(target) = target {
+            targets.insert(quote.clone(), target);
+        }
+    }
+
+    Ok(targets)
+}
+
+/// Find what a single quote points at
+pub(crate) async fn resolve_quote(conn: &mut SqliteConnection, quote: &Quote) -> Result<Option<QuoteTarget>> {
+    let mut targets = resolve_quotes(conn, std::slice::from_ref(quote)).await?;
+    Ok(targets.remove(quote))
+}
+
+/// Resolve every quote in a page's post bodies ahead of rendering them
+pub(crate) async fn resolve_links<'a>(
+    pool: &DbPool,
+    thread_id: Option<i64>,
+    bodies: impl Iterator<Item = &'a str>,
+) -> Result<QuoteLinks> {
+    let mut quotes: Vec<Quote> = bodies.flat_map(quotes_in).collect();
+    quotes.sort_unstable();
+    quotes.dedup();
+
+    let targets = if quotes.is_empty() {
+        HashMap::new()
+    } else {
+        resolve_quotes(&mut *pool.acquire().await?, &quotes).await?
+    };
+
+    Ok(QuoteLinks { thread_id, targets })
+}
+
+/// Hover preview for a quote link: where it points and the start of what it says
+pub async fn preview(
+    State(pool): State<DbPool>,
+    Query(query): Query<QuoteQuery>,
+) -> Result<Json<QuotePreview>> {
+    let quote = match query.board {
+        Some(slug) => Quote::Board { slug, id: query.id },
+        None => Quote::Post(query.id),
+    };
+
+    let mut conn = pool.acquire().await?;
+    let target = resolve_quote(&mut conn, &quote)
+        .await?
+        .ok_or(AppError::NotFound)?;
+    let thread = ThreadRepository::find_by_id(&mut *conn, target.thread_id)
+        .await?
+        .ok_or(AppError::ThreadNotFound)?;
+    let board = BoardRepository::find_by_id(&mut *conn, thread.board_id)
+        .await?
+        .ok_or(AppError::NotFound)?;
+
+    let (content, deleted_at, created_at) = match target.post_id {
+        Some(post_id) => {
+            let post = PostRepository::find_by_id(&mut *conn, post_id)
+                .await?
+                .ok_or(AppError::NotFound)?;
+            (post.content, post.deleted_at, post.created_at)
+        }
+        None => (thread.content.clone(), thread.deleted_at, thread.created_at),
+    };
+
+    if deleted_at.is_some() {
+        return Err(AppError::PostDeleted);
+    }
+
+    Ok(Json(QuotePreview {
+        board_slug: board.slug,
+        thread_id: thread.id,
+        post_id: target.post_id,
+        thread_title: thread.title,
+        created_at: created_at.to_rfc3339(),
+        body_html: render_post(&content.chars().take(PREVIEW_MAX_CHARS).collect::<String>()),
+    }))
+}
\ No newline at end of file
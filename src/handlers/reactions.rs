@@ -0,0 +1,315 @@
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use chrono::{Duration, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::Digest;
+use sqlx::SqliteConnection;
+use uuid::Uuid;
+
+use crate::{
+    config::Config,
+    db::{DbPool, PowRepository, ReactionRepository, ThreadRepository},
+    error::{AppError, Result},
+    handlers::{
+        api::{commit_request_hash, replay_commit, validate_reply_target, validate_timestamp, ThreadBeginResponse},
+        edits::EditTarget,
+    },
+    identity::parse_pubkey,
+    models::{OpReceipt, PowChallenge, PowCommit, Reaction},
+    pow::{calculate_pow_difficulty, canonical_bytes_v1, verify_proof_v1, CanonicalParams, PostDraft, ProofOfWork},
+    reactions::{aggregate, reaction_glyph, PostReactions},
+};
+
+#[derive(Deserialize)]
+pub struct ReactBeginRequest {
+    pub client_op_id: Uuid,
+    pub thread_id: i64,
+    pub post_id: Option<i64>, // None to react to the opening post
+    pub kind: String,
+    pub user_pubkey_hex: String,
+    pub timestamp_i64: i64,
+}
+
+#[derive(Deserialize)]
+pub struct ReactCommitRequest {
+    pub op_id: Uuid,
+    pub challenge_id: String,
+    pub thread_id: i64,
+    pub post_id: Option<i64>,
+    pub kind: String,
+    pub proof: ProofOfWork,
+    pub user_pubkey_hex: String,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ReactCommitResponse {
+    pub thread_id: i64,
+    pub post_id: Option<i64>,
+    pub kind: String,
+    pub difficulty: f64,
+}
+
+#[derive(Serialize)]
+pub struct PostReactionsEntry {
+    pub post_id: Option<i64>, // None for the opening post
+    #[serde(flatten)]
+    pub reactions: PostReactions,
+}
+
+#[derive(Serialize)]
+pub struct ThreadReactionsResponse {
+    pub thread_id: i64,
+    pub posts: Vec<PostReactionsEntry>,
+}
+
+/// Check a reaction can land: a known kind, on a live post in an open thread, and
+/// not within the cooldown of the same pubkey's last reaction to that post
+async fn validate_reaction(
+    conn: &mut SqliteConnection,
+    config: &Config,
+    thread_id: i64,
+    post_id: Option<i64>,
+    kind: &str,
+    pubkey_hex: &str,
+) -> Result<()> {
+    if reaction_glyph(kind).is_none() {
+        return Err(AppError::Validation(format!("Unknown reaction \"{}\"", kind)));
+    }
+
+    // Same rules as replying: no reactions once a thread is locked or archived
+    validate_reply_target(&mut *conn, thread_id, post_id).await?;
+    if EditTarget::find(&mut *conn, thread_id, post_id).await?.is_deleted() {
+        return Err(AppError::PostDeleted);
+    }
+
+    let since = Utc::now() - Duration::seconds(config.reaction_cooldown_seconds);
+    if ReactionRepository::reacted_since(&mut *conn, thread_id, post_id, pubkey_hex, since).await? {
+        return Err(AppError::ReactionRateLimited);
+    }
+
+    Ok(())
+}
+
+fn canonical_params(
+    pubkey_hex: &str,
+    thread_id: i64,
+    post_id: Option<i64>,
+    kind: &str,
+    timestamp_i64: i64,
+) -> CanonicalParams {
+    CanonicalParams {
+        user_pubkey_hex: pubkey_hex.to_string(),
+        scope: "x".to_string(), // 'x' for reaction
+        board_id: 0,
+        thread_id: thread_id as u64,
+        parent_id: post_id.unwrap_or(0) as u64, // the post reacted to
+        timestamp_i64,
+        post_draft: PostDraft::reaction(kind),
+    }
+}
+
+pub async fn react_begin(
+    State(pool): State<DbPool>,
+    Json(req): Json<ReactBeginRequest>,
+) -> Result<Json<ThreadBeginResponse>> {
+    if let Some(receipt) = PowRepository::find_op_receipt(&pool, &req.client_op_id.to_string(), "react_begin").await? {
+        let response: ThreadBeginResponse = serde_json::from_str(&receipt.result_json)
+            .map_err(|_| AppError::Internal)?;
+        return Ok(Json(response));
+    }
+
+    parse_pubkey(&req.user_pubkey_hex).ok_or(AppError::InvalidPublicKey)?;
+    validate_timestamp(req.timestamp_i64, 0)?;
+
+    let config = Config::new().unwrap();
+    let mut conn = pool.acquire().await?;
+    validate_reaction(&mut conn, &config, req.thread_id, req.post_id, &req.kind, &req.user_pubkey_hex).await?;
+
+    let canonical_params = canonical_params(
+        &req.user_pubkey_hex,
+        req.thread_id,
+        req.post_id,
+        &req.kind,
+        req.timestamp_i64,
+    );
+    let canonical_bytes = canonical_bytes_v1(&canonical_params);
+    let post_json = serde_json::to_string(&canonical_params.post_draft)?;
+    let post_bytes_hash = sha2::Sha256::digest(post_json.as_bytes()).to_vec();
+
+    let challenge = PowChallenge::new(
+        req.user_pubkey_hex,
+        "reaction".to_string(),
+        0,
+        req.thread_id,
+        req.post_id.unwrap_or(0),
+        post_bytes_hash.clone(),
+        config.reaction_pow_prefix,
+        canonical_bytes.clone(),
+        config.pow_challenge_ttl_seconds,
+    );
+    PowRepository::create_challenge(&pool, &challenge).await?;
+
+    let response = ThreadBeginResponse {
+        challenge_id: challenge.id.clone(),
+        required_prefix_hex: challenge.required_prefix_hex,
+        challenge_version: 1,
+        op_id: req.client_op_id,
+        expires_at: challenge.expires_at.to_rfc3339(),
+        post_bytes_hash: hex::encode(&post_bytes_hash),
+        canonical_bytes: hex::encode(&canonical_bytes),
+    };
+
+    let receipt = OpReceipt::new(
+        req.client_op_id.to_string(),
+        "react_begin".to_string(),
+        None,
+        serde_json::to_string(&response)?,
+    );
+    PowRepository::create_op_receipt(&pool, &receipt).await?;
+
+    Ok(Json(response))
+}
+
+pub async fn react_commit(
+    State(pool): State<DbPool>,
+    Json(req): Json<ReactCommitRequest>,
+) -> Result<Json<ReactCommitResponse>> {
+    let op_id = req.op_id.to_string();
+    let request_hash = commit_request_hash(json!({
+        "challenge_id": req.challenge_id,
+        "thread_id": req.thread_id,
+        "post_id": req.post_id,
+        "kind": req.kind,
+        "proof": req.proof,
+        "user_pubkey_hex": req.user_pubkey_hex,
+    }))?;
+
+    if let Some(receipt) = PowRepository::find_op_receipt(&pool, &op_id, "react_commit").await? {
+        return replay_commit(&receipt, &request_hash).map(Json);
+    }
+
+    let challenge = PowRepository::find_challenge(&pool, &req.challenge_id)
+        .await?
+        .ok_or(AppError::ChallengeNotFound)?;
+
+    if challenge.is_expired() {
+        return Err(AppError::ChallengeExpired);
+    }
+
+    // A reaction challenge only pays for the post and kind it was issued for
+    let post_json = serde_json::to_string(&PostDraft::reaction(&req.kind))?;
+    let post_bytes_hash = sha2::Sha256::digest(post_json.as_bytes()).to_vec();
+    if challenge.scope != "reaction"
+        || challenge.user_pubkey_hex != req.user_pubkey_hex
+        || challenge.thread_id != req.thread_id
+        || challenge.parent_id != req.post_id.unwrap_or(0)
+        || challenge.post_bytes_hash != post_bytes_hash
+    {
+        return Err(AppError::ChallengeNotFound);
+    }
+
+    let config = Config::new().unwrap();
+    validate_timestamp(req.proof.timestamp_i64, config.pow_challenge_ttl_seconds as i64)?;
+
+    let canonical_params = canonical_params(
+        &req.user_pubkey_hex,
+        req.thread_id,
+        req.post_id,
+        &req.kind,
+        req.proof.timestamp_i64,
+    );
+    let (is_valid, solved_hash) = verify_proof_v1(
+        &canonical_params,
+        req.proof.nonce_u64,
+        &challenge.required_prefix_hex,
+    );
+
+    if !is_valid {
+        return Err(AppError::InvalidProofOfWork);
+    }
+
+    let difficulty = calculate_pow_difficulty(&solved_hash);
+
+    // The reaction and its commit record land together or not at all
+    let mut tx = pool.begin().await?;
+
+    if PowRepository::find_spent_commit(&mut *tx, &req.challenge_id, &solved_hash)
+        .await?
+        .is_some()
+    {
+        return Err(AppError::ChallengeUsed);
+    }
+
+    validate_reaction(&mut tx, &config, req.thread_id, req.post_id, &req.kind, &req.user_pubkey_hex).await?;
+
+    // Reaction work shows on the post; it isn't credited to reputation
+    let now = Utc::now();
+    ReactionRepository::create(
+        &mut *tx,
+        &Reaction {
+            id: 0,
+            thread_id: req.thread_id,
+            post_id: req.post_id,
+            kind: req.kind.clone(),
+            reactor_pubkey: req.user_pubkey_hex.clone(),
+            pow_nonce: req.proof.nonce_u64 as i64,
+            pow_hash: solved_hash.clone(),
+            pow_challenge_id: req.challenge_id.clone(),
+            pow_difficulty: difficulty,
+            created_at: now,
+        },
+    )
+    .await?;
+
+    let commit = PowCommit {
+        id: Uuid::new_v4().to_string(),
+        challenge_id: req.challenge_id,
+        nonce_u64: req.proof.nonce_u64 as i64,
+        miner_version: req.proof.miner_version as i32,
+        timestamp_i64: req.proof.timestamp_i64,
+        solved_hash_hex: solved_hash,
+        thread_id: Some(req.thread_id),
+        post_id: req.post_id,
+        verified: true,
+        created_at: now,
+    };
+    PowRepository::create_commit(&mut *tx, &commit).await?;
+
+    let response = ReactCommitResponse {
+        thread_id: req.thread_id,
+        post_id: req.post_id,
+        kind: req.kind,
+        difficulty,
+    };
+    let receipt = OpReceipt::new(
+        op_id,
+        "react_commit".to_string(),
+        Some(request_hash),
+        serde_json::to_string(&response)?,
+    );
+    PowRepository::create_op_receipt(&mut *tx, &receipt).await?;
+
+    tx.commit().await?;
+
+    Ok(Json(response))
+}
+
+pub async fn thread_reactions(
+    State(pool): State<DbPool>,
+    Path(thread_id): Path<i64>,
+) -> Result<Json<ThreadReactionsResponse>> {
+    ThreadRepository::find_by_id(&pool, thread_id)
+        .await?
+        .ok_or(AppError::ThreadNotFound)?;
+
+    let mut posts: Vec<PostReactionsEntry> = aggregate(&ReactionRepository::list_for_thread(&pool, thread_id).await?)
+        .into_iter()
+        .map(|(post_id, reactions)| PostReactionsEntry { post_id, reactions })
+        .collect();
+    posts.sort_by_key(|entry| entry.post_id);
+
+    Ok(Json(ThreadReactionsResponse { thread_id, posts }))
+}
\ No newline at end of file
@@ -1,15 +1,126 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     response::Html,
     Form,
 };
+use chrono::{DateTime, Utc};
 use serde::Deserialize;
+use std::collections::HashMap;
 
 use crate::{
-    db::{BoardRepository, DbPool, PostRepository, ThreadRepository},
+    db::{
+        AttachmentRepository, BoardRepository, BoostRepository, DbPool, PollRepository, PostRefRepository, PostRepository,
+        ReactionRepository, ThreadRepository, UserRepository,
+    },
     error::{AppError, Result},
+    handlers::{
+        api::TreeQuery,
+        edits::{load_history, HistoryQuery},
+        polls::{load_results, PollResults},
+        quotes::resolve_links,
+    },
+    markup::{escape_html, render_post_with, QuoteLinks, MARKUP_CSS, QUOTE_PREVIEW_SCRIPT},
+    models::{BoostContributor, PostAttachment, PostRef},
+    polls,
+    reactions::{aggregate, PostReactions, REACTION_KINDS},
+    tree::{build_reply_tree, ReplyNode, SiblingOrder},
 };
 
+/// Mining, upload and preview helpers shared by the reply form and the new-thread form
+const POST_FORM_SCRIPT: &str = r#"
+    <script>
+        // Simple PoW miner simulation
+        async function minePoW(canonicalBytes, requiredPrefix) {
+            let nonce = 0;
+            while (nonce < 1000000) {
+                const input = canonicalBytes + nonce.toString(16).padStart(16, '0');
+                const hash = await crypto.subtle.digest('SHA-256', new TextEncoder().encode(input));
+                const hashHex = Array.from(new Uint8Array(hash))
+                    .map(b => b.toString(16).padStart(2, '0'))
+                    .join('');
+
+                if (hashHex.startsWith(requiredPrefix)) {
+                    return { nonce, hash: hashHex };
+                }
+                nonce++;
+
+                if (nonce % 10000 === 0) {
+                    document.getElementById('mining-status').textContent = `Mining... tried ${nonce} nonces`;
+                    await new Promise(resolve => setTimeout(resolve, 1));
+                }
+            }
+            throw new Error('Failed to find solution');
+        }
+
+        // Upload the picked files, each paid for with a small proof over its hash;
+        // the server answers with the hashes a draft references
+        async function uploadFiles(input, boardId) {
+            const pubkey = document.getElementById('user-pubkey').value;
+            const hashes = [];
+            for (const file of input.files) {
+                const digest = await crypto.subtle.digest('SHA-256', await file.arrayBuffer());
+                const fileHash = Array.from(new Uint8Array(digest))
+                    .map(b => b.toString(16).padStart(2, '0'))
+                    .join('');
+                const timestamp = Math.floor(Date.now() / 1000);
+
+                const beginResponse = await fetch(`/api/attachments/${boardId}/begin`, {
+                    method: 'POST',
+                    headers: { 'Content-Type': 'application/json' },
+                    body: JSON.stringify({
+                        client_op_id: crypto.randomUUID(),
+                        user_pubkey_hex: pubkey,
+                        file_sha256_hex: fileHash,
+                        timestamp_i64: timestamp
+                    })
+                });
+                const beginData = await beginResponse.json();
+                if (!beginResponse.ok) throw new Error(beginData.error || 'Upload failed');
+                const solution = await minePoW(beginData.canonical_bytes, beginData.required_prefix_hex);
+
+                const params = new URLSearchParams({
+                    filename: file.name,
+                    challenge_id: beginData.challenge_id,
+                    user_pubkey_hex: pubkey,
+                    nonce_u64: solution.nonce,
+                    timestamp_i64: timestamp
+                });
+                const response = await fetch(`/api/attachments/${boardId}?${params}`, {
+                    method: 'POST',
+                    body: file
+                });
+                const data = await response.json();
+                if (!response.ok) throw new Error(data.error || 'Upload failed');
+                hashes.push(data.sha256_hex);
+            }
+            return hashes;
+        }
+
+        // Render the server's preview, listing anything begin or commit would reject
+        function showPreview(el, data) {
+            el.style.display = 'block';
+            if (data.error) {
+                el.textContent = data.error;
+                return;
+            }
+            el.innerHTML = data.body_html;
+            const list = document.createElement('ul');
+            data.errors.forEach(error => {
+                const item = document.createElement('li');
+                item.textContent = error;
+                list.appendChild(item);
+            });
+            if (data.errors.length) el.prepend(list);
+        }
+    </script>
+"#;
+
+#[derive(Deserialize)]
+pub struct ShowQuery {
+    /// Post to reply to, prefilled into the reply form
+    pub parent: Option<i64>,
+}
+
 #[derive(Deserialize)]
 pub struct NewThreadForm {
     pub title: String,
@@ -17,13 +128,249 @@ pub struct NewThreadForm {
     pub user_pubkey_hex: String,
 }
 
-pub async fn show(State(pool): State<DbPool>, Path(id): Path<i64>) -> Result<Html<String>> {
+/// Load verified vanity flairs for every author on the page in one query, keyed by pubkey.
+/// Linked pseudonyms without a flair of their own carry their main identity's.
+async fn load_flairs<'a>(
+    pool: &DbPool,
+    pubkeys: impl Iterator<Item = &'a str>,
+) -> Result<HashMap<String, String>> {
+    let mut pubkeys: Vec<String> = pubkeys.map(str::to_string).collect();
+    pubkeys.sort();
+    pubkeys.dedup();
+
+    let flairs = UserRepository::find_flairs(pool, &pubkeys)
+        .await?
+        .into_iter()
+        .map(|flair| {
+            let html = format!(r#" <span class="flair" title="{}">{}</span>"#, flair.vanity_hash, flair.vanity_prefix);
+            (flair.pubkey_hex, html)
+        })
+        .collect();
+
+    Ok(flairs)
+}
+
+fn flair_for<'a>(flairs: &'a HashMap<String, String>, pubkey: Option<&str>) -> &'a str {
+    pubkey
+        .and_then(|pubkey| flairs.get(pubkey))
+        .map(String::as_str)
+        .unwrap_or("")
+}
+
+/// "Replies to this" links for each post in a thread, from the quote edges ending there,
+/// keyed by post id (`None` for the opening post)
+fn backlinks_by_post(refs: &[PostRef], thread_id: i64) -> HashMap<Option<i64>, String> {
+    let mut links: HashMap<Option<i64>, Vec<String>> = HashMap::new();
+
+    for edge in refs.iter().filter(|edge| edge.to_thread_id == thread_id) {
+        let link = match edge.from_post_id {
+            Some(from) if edge.from_thread_id == thread_id => {
+                format!(r##"<a class="quotelink" href="#p{from}">&gt;&gt;{from}</a>"##)
+            }
+            Some(from) => format!(
+                r#"<a class="quotelink" href="/threads/{}#p{from}">&gt;&gt;{from}</a>"#,
+                edge.from_thread_id
+            ),
+            None => format!(
+                r#"<a class="quotelink" href="/threads/{0}">&gt;&gt;&gt;thread {0}</a>"#,
+                edge.from_thread_id
+            ),
+        };
+        links.entry(edge.to_post_id).or_default().push(link);
+    }
+
+    links
+        .into_iter()
+        .map(|(post_id, links)| (post_id, format!(r#"<div class="backlinks">Replies: {}</div>"#, links.join(" "))))
+        .collect()
+}
+
+/// Image links for each post in a thread, keyed by post id (`None` for the opening post)
+fn attachments_by_post(attachments: &[PostAttachment]) -> HashMap<Option<i64>, String> {
+    let mut images: HashMap<Option<i64>, Vec<String>> = HashMap::new();
+
+    for attachment in attachments {
+        let name = escape_html(attachment.original_filename.as_deref().unwrap_or(&attachment.sha256_hex));
+        images.entry(attachment.post_id).or_default().push(format!(
+            r#"<figure class="attachment">
+                <a href="{}" target="_blank"><img src="{}" width="{}" height="{}" alt="" loading="lazy"></a>
+                <figcaption>{} • {}x{} • {} KB</figcaption>
+            </figure>"#,
+            attachment.url(),
+            attachment.thumbnail_url(),
+            attachment.thumb_width,
+            attachment.thumb_height,
+            name,
+            attachment.width,
+            attachment.height,
+            (attachment.size_bytes + 1023) / 1024,
+        ));
+    }
+
+    images
+        .into_iter()
+        .map(|(post_id, images)| (post_id, format!(r#"<div class="attachments">{}</div>"#, images.join(""))))
+        .collect()
+}
+
+/// A post's rendered body, or its tombstone once the author deleted it
+fn post_body(content: &str, deleted_at: Option<DateTime<Utc>>, links: &QuoteLinks) -> String {
+    match deleted_at {
+        Some(_) => r#"<span class="tombstone">[deleted by author]</span>"#.to_string(),
+        None => render_post_with(content, links),
+    }
+}
+
+/// "(edited)" marker linking to a post's revision log, if it has been edited
+fn edited_link(thread_id: i64, post_id: Option<i64>, edited_at: Option<DateTime<Utc>>) -> String {
+    let Some(edited_at) = edited_at else {
+        return String::new();
+    };
+
+    format!(
+        r#" • <a class="edited" href="/threads/{}/history{}" title="Last edited {}">(edited)</a>"#,
+        thread_id,
+        post_id.map(|id| format!("?post_id={}", id)).unwrap_or_default(),
+        edited_at.format("%Y-%m-%d %H:%M")
+    )
+}
+
+/// A post's reaction counts with their work, followed by buttons to mine a new one
+fn reaction_bar(post_id: Option<i64>, reactions: Option<&PostReactions>) -> String {
+    let counts = reactions
+        .map(|reactions| {
+            reactions
+                .reactions
+                .iter()
+                .map(|count| format!(
+                    r#"<span class="reaction" title="{} • {:.1} work">{} {}</span>"#,
+                    escape_html(&count.kind),
+                    count.work,
+                    count.glyph,
+                    count.count
+                ))
+                .collect::<Vec<_>>()
+                .join("")
+        })
+        .unwrap_or_default();
+    let total = reactions
+        .filter(|reactions| reactions.total_work > 0.0)
+        .map(|reactions| format!(r#"<span class="post-meta">{:.1} work</span>"#, reactions.total_work))
+        .unwrap_or_default();
+    let buttons = REACTION_KINDS
+        .iter()
+        .map(|(kind, glyph)| format!(
+            r#"<button class="react-btn" title="Mine a {} reaction" onclick="react({}, '{}')">{}</button>"#,
+            kind,
+            post_id.map(|id| id.to_string()).unwrap_or_else(|| "null".to_string()),
+            kind,
+            glyph
+        ))
+        .collect::<Vec<_>>()
+        .join("");
+
+    format!(r#"<div class="reactions">{}{} {}</div>"#, counts, total, buttons)
+}
+
+/// The thread's score and who has boosted it, with a button to mine another boost
+fn boost_box(bump_score: i32, contributors: &[BoostContributor], flairs: &HashMap<String, String>) -> String {
+    let list = if contributors.is_empty() {
+        "No boosts yet.".to_string()
+    } else {
+        contributors
+            .iter()
+            .map(|contributor| format!(
+                r#"<span title="{}">{}…{}</span> ({} • {:.1} work)"#,
+                contributor.booster_pubkey,
+                contributor.booster_pubkey.chars().take(10).collect::<String>(),
+                flair_for(flairs, Some(&contributor.booster_pubkey)),
+                contributor.boosts,
+                contributor.work
+            ))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    format!(
+        r#"<div class="pow-info">
+            <strong>Score:</strong> {} • <strong>Boosted by:</strong> {}
+            <button class="react-btn" onclick="boost()" title="Mine extra work into this thread's score">Boost</button>
+        </div>"#,
+        bump_score, list
+    )
+}
+
+/// A poll's question and tallies, each option barred by its share of the work spent
+fn poll_box(results: &PollResults) -> String {
+    let options = results
+        .options
+        .iter()
+        .map(|option| {
+            let share = if results.total_work > 0.0 { option.work / results.total_work * 100.0 } else { 0.0 };
+            format!(
+                r#"<div class="poll-option">
+                    <div>{} <span class="post-meta">#{} • {} voters • {:.1} work</span></div>
+                    <div class="poll-bar"><div style="width: {:.1}%;"></div></div>
+                </div>"#,
+                escape_html(&option.label),
+                option.option_id,
+                option.voters,
+                option.work,
+                share
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("");
+
+    format!(
+        r#"<div class="poll">
+            <strong>{}</strong>
+            {}
+            <div class="post-meta">{} voters • {:.1} total work • poll #{}, one mined vote per key</div>
+        </div>"#,
+        escape_html(&results.question),
+        options,
+        results.total_voters,
+        results.total_work,
+        results.poll_id
+    )
+}
+
+pub async fn show(
+    State(pool): State<DbPool>,
+    Path(id): Path<i64>,
+    Query(query): Query<ShowQuery>,
+) -> Result<Html<String>> {
     let thread = ThreadRepository::find_by_id(&pool, id)
         .await?
         .ok_or(AppError::NotFound)?;
     
-    let board = BoardRepository::find_by_slug(&pool, "b").await?.unwrap(); // TODO: get actual board
+    let board = BoardRepository::find_by_id(&pool, thread.board_id)
+        .await?
+        .ok_or(AppError::NotFound)?;
     let posts = PostRepository::list_by_thread(&pool, id).await?;
+    let backlinks = backlinks_by_post(&PostRefRepository::list_for_thread(&pool, id).await?, id);
+    let links = resolve_links(
+        &pool,
+        Some(id),
+        std::iter::once(thread.content.as_str()).chain(posts.iter().map(|post| post.content.as_str())),
+    )
+    .await?;
+    let images = attachments_by_post(&AttachmentRepository::list_for_thread(&pool, id).await?);
+    let reactions = aggregate(&ReactionRepository::list_for_thread(&pool, id).await?);
+    let contributors = BoostRepository::contributors(&pool, id).await?;
+    let poll = match PollRepository::find_by_thread(&pool, id).await? {
+        Some(poll) => poll_box(&load_results(&pool, poll).await?),
+        None => String::new(),
+    };
+    let flairs = load_flairs(
+        &pool,
+        std::iter::once(thread.author_pubkey.as_deref())
+            .chain(posts.iter().map(|post| post.author_pubkey.as_deref()))
+            .flatten()
+            .chain(contributors.iter().map(|contributor| contributor.booster_pubkey.as_str())),
+    )
+    .await?;
     
     let html = format!(
         r#"<!DOCTYPE html>
@@ -99,6 +446,14 @@ pub async fn show(State(pool): State<DbPool>, Path(id): Path<i64>) -> Result<Htm
             font-weight: bold;
             margin-bottom: 10px;
         }}
+        .flair {{
+            background-color: #3C4267;
+            color: #2DD2C1;
+            border: 1px solid #2DD2C1;
+            border-radius: 3px;
+            padding: 0 4px;
+            font-weight: bold;
+        }}
         .pow-info {{
             background-color: #50589C;
             padding: 10px;
@@ -139,36 +494,210 @@ pub async fn show(State(pool): State<DbPool>, Path(id): Path<i64>) -> Result<Htm
             font-weight: bold;
             cursor: pointer;
         }}
+        .backlinks {{
+            font-size: 0.8em;
+            margin-top: 8px;
+            opacity: 0.8;
+        }}
+        .tombstone {{
+            color: #2DD2C1;
+            font-style: italic;
+            opacity: 0.7;
+        }}
+        .attachments {{
+            display: flex;
+            flex-wrap: wrap;
+            gap: 10px;
+            margin-top: 10px;
+        }}
+        .attachment {{
+            margin: 0;
+            font-size: 0.75em;
+        }}
+        .attachment img {{
+            border: 1px solid #50589C;
+            border-radius: 4px;
+        }}
+        .poll {{
+            background-color: #50589C;
+            padding: 10px;
+            border-radius: 4px;
+            margin: 10px 0;
+        }}
+        .poll-option {{
+            margin: 8px 0;
+        }}
+        .poll-bar {{
+            background-color: #3C4267;
+            border-radius: 3px;
+            height: 8px;
+        }}
+        .poll-bar div {{
+            background-color: #2DD2C1;
+            border-radius: 3px;
+            height: 100%;
+        }}
+        .reactions {{
+            font-size: 0.85em;
+            margin-top: 8px;
+        }}
+        .reaction {{
+            background-color: #50589C;
+            border-radius: 3px;
+            padding: 2px 6px;
+            margin-right: 5px;
+        }}
+        .react-btn {{
+            background: none;
+            border: 1px solid #50589C;
+            border-radius: 3px;
+            cursor: pointer;
+            opacity: 0.6;
+        }}
+        .react-btn:hover {{
+            opacity: 1;
+        }}
+        {markup_css}
     </style>
+    {post_form_script}
     <script>
-        // Simple PoW miner simulation
-        async function minePoW(canonicalBytes, requiredPrefix) {{
-            let nonce = 0;
-            while (nonce < 1000000) {{
-                const input = canonicalBytes + nonce.toString(16).padStart(16, '0');
-                const hash = await crypto.subtle.digest('SHA-256', new TextEncoder().encode(input));
-                const hashHex = Array.from(new Uint8Array(hash))
-                    .map(b => b.toString(16).padStart(2, '0'))
-                    .join('');
+        // Mine a small proof for a reaction; the pubkey is the one in the reply form
+        async function react(postId, kind) {{
+            const pubkey = document.getElementById('user-pubkey').value;
+            if (!pubkey.trim()) {{
+                alert('Enter your public key in the reply form first');
+                return;
+            }}
+            const status = document.getElementById('mining-status');
+            const timestamp = Math.floor(Date.now() / 1000);
+            
+            try {{
+                const beginResponse = await fetch('/api/reactions/begin', {{
+                    method: 'POST',
+                    headers: {{ 'Content-Type': 'application/json' }},
+                    body: JSON.stringify({{
+                        client_op_id: crypto.randomUUID(),
+                        thread_id: {thread_id},
+                        post_id: postId,
+                        kind: kind,
+                        user_pubkey_hex: pubkey,
+                        timestamp_i64: timestamp
+                    }})
+                }});
+                const beginData = await beginResponse.json();
+                if (!beginResponse.ok) throw new Error(beginData.error);
                 
-                if (hashHex.startsWith(requiredPrefix)) {{
-                    return {{ nonce, hash: hashHex }};
-                }}
-                nonce++;
+                status.textContent = 'Mining reaction...';
+                const solution = await minePoW(beginData.canonical_bytes, beginData.required_prefix_hex);
                 
-                if (nonce % 10000 === 0) {{
-                    document.getElementById('mining-status').textContent = `Mining... tried ${{nonce}} nonces`;
-                    await new Promise(resolve => setTimeout(resolve, 1));
-                }}
+                const commitResponse = await fetch('/api/reactions/commit', {{
+                    method: 'POST',
+                    headers: {{ 'Content-Type': 'application/json' }},
+                    body: JSON.stringify({{
+                        op_id: beginData.op_id,
+                        challenge_id: beginData.challenge_id,
+                        thread_id: {thread_id},
+                        post_id: postId,
+                        kind: kind,
+                        proof: {{
+                            nonce_u64: solution.nonce,
+                            miner_version: 1,
+                            timestamp_i64: timestamp
+                        }},
+                        user_pubkey_hex: pubkey
+                    }})
+                }});
+                const commitData = await commitResponse.json();
+                if (!commitResponse.ok) throw new Error(commitData.error);
+                
+                location.reload();
+            }} catch (error) {{
+                status.textContent = 'Error: ' + error.message;
             }}
-            throw new Error('Failed to find solution');
+        }}
+        
+        // Mine work into the thread's score without posting anything
+        async function boost() {{
+            const pubkey = document.getElementById('user-pubkey').value;
+            if (!pubkey.trim()) {{
+                alert('Enter your public key in the reply form first');
+                return;
+            }}
+            const status = document.getElementById('mining-status');
+            const timestamp = Math.floor(Date.now() / 1000);
+            
+            try {{
+                const beginResponse = await fetch('/api/boost/begin', {{
+                    method: 'POST',
+                    headers: {{ 'Content-Type': 'application/json' }},
+                    body: JSON.stringify({{
+                        client_op_id: crypto.randomUUID(),
+                        thread_id: {thread_id},
+                        user_pubkey_hex: pubkey,
+                        timestamp_i64: timestamp
+                    }})
+                }});
+                const beginData = await beginResponse.json();
+                if (!beginResponse.ok) throw new Error(beginData.error);
+                
+                status.textContent = 'Mining boost...';
+                const solution = await minePoW(beginData.canonical_bytes, beginData.required_prefix_hex);
+                
+                const commitResponse = await fetch('/api/boost/commit', {{
+                    method: 'POST',
+                    headers: {{ 'Content-Type': 'application/json' }},
+                    body: JSON.stringify({{
+                        op_id: beginData.op_id,
+                        challenge_id: beginData.challenge_id,
+                        thread_id: {thread_id},
+                        proof: {{
+                            nonce_u64: solution.nonce,
+                            miner_version: 1,
+                            timestamp_i64: timestamp
+                        }},
+                        user_pubkey_hex: pubkey
+                    }})
+                }});
+                const commitData = await commitResponse.json();
+                if (!commitResponse.ok) throw new Error(commitData.error);
+                
+                location.reload();
+            }} catch (error) {{
+                status.textContent = 'Error: ' + error.message;
+            }}
+        }}
+        
+        async function previewReply() {{
+            const body = document.getElementById('reply-body').value;
+            const attachments = await uploadFiles(document.getElementById('reply-files'), {board_id});
+            const response = await fetch('/api/pow/preview', {{
+                method: 'POST',
+                headers: {{ 'Content-Type': 'application/json' }},
+                body: JSON.stringify({{
+                    thread_id: {thread_id},
+                    parent_id: parseInt(document.getElementById('reply-parent').value) || null,
+                    user_pubkey_hex: document.getElementById('user-pubkey').value,
+                    post_draft: {{
+                        body: body,
+                        attachments: attachments,
+                        refs: [...new Set(body.match(/>>>\/[a-z0-9]+\/\d+|>>\d+/g) || [])],
+                        sage: document.getElementById('reply-sage').checked,
+                        title: ""
+                    }}
+                }})
+            }});
+            showPreview(document.getElementById('reply-preview'), await response.json());
         }}
         
         async function submitReply() {{
             const body = document.getElementById('reply-body').value;
             const pubkey = document.getElementById('user-pubkey').value;
+            const sage = document.getElementById('reply-sage').checked;
+            const refs = [...new Set(body.match(/>>>\/[a-z0-9]+\/\d+|>>\d+/g) || [])];
+            const parentId = parseInt(document.getElementById('reply-parent').value) || null;
+            const files = document.getElementById('reply-files');
             
-            if (!body.trim() || !pubkey.trim()) {{
+            if ((!body.trim() && !files.files.length) || !pubkey.trim()) {{
                 alert('Please fill in all fields');
                 return;
             }}
@@ -176,7 +705,12 @@ pub async fn show(State(pool): State<DbPool>, Path(id): Path<i64>) -> Result<Htm
             document.getElementById('mining-status').textContent = 'Starting PoW mining...';
             document.getElementById('reply-btn').disabled = true;
             
+            // Seconds, bound into the canonical bytes and reused at commit
+            const timestamp = Math.floor(Date.now() / 1000);
+            
             try {{
+                const attachments = await uploadFiles(files, {board_id});
+                
                 // Begin challenge
                 const beginResponse = await fetch('/api/pow/reply/begin', {{
                     method: 'POST',
@@ -185,14 +719,15 @@ pub async fn show(State(pool): State<DbPool>, Path(id): Path<i64>) -> Result<Htm
                         client_op_id: crypto.randomUUID(),
                         post_draft: {{ 
                             body: body,
-                            attachments: [],
-                            refs: [],
+                            attachments: attachments,
+                            refs: refs,
+                            sage: sage,
                             title: ""
                         }},
                         user_pubkey_hex: pubkey,
                         thread_id: {thread_id},
-                        parent_id: null,
-                        timestamp_i64: Math.floor(Date.now() / 1000)
+                        parent_id: parentId,
+                        timestamp_i64: timestamp
                     }})
                 }});
                 
@@ -214,18 +749,19 @@ pub async fn show(State(pool): State<DbPool>, Path(id): Path<i64>) -> Result<Htm
                         challenge_id: beginData.challenge_id,
                         post_draft: {{ 
                             body: body,
-                            attachments: [],
-                            refs: [],
+                            attachments: attachments,
+                            refs: refs,
+                            sage: sage,
                             title: ""
                         }},
                         proof: {{
                             nonce_u64: solution.nonce,
                             miner_version: 1,
-                            timestamp_i64: Math.floor(Date.now() / 1000)
+                            timestamp_i64: timestamp
                         }},
                         user_pubkey_hex: pubkey,
                         thread_id: {thread_id},
-                        parent_id: null
+                        parent_id: parentId
                     }})
                 }});
                 
@@ -248,7 +784,8 @@ pub async fn show(State(pool): State<DbPool>, Path(id): Path<i64>) -> Result<Htm
         <div class="nav">
             <a href="/">Home</a>
             <a href="/boards">Boards</a>
-            <a href="/boards/{board_slug}">Back to Board</a>
+            <a href="/boards/{board_slug}">Back to /{board_slug}/</a>
+            <a href="/threads/{thread_id}/tree">Tree view</a>
         </div>
     </div>
     
@@ -256,22 +793,28 @@ pub async fn show(State(pool): State<DbPool>, Path(id): Path<i64>) -> Result<Htm
         <div class="thread-title">{title}</div>
         <div class="post-header">
             <div class="post-meta">
-                Anonymous • {} • Post #{id}
+                Anonymous{op_flair} • {created_at} • Post #{id}{op_edited}
             </div>
             <div class="post-meta">
                 PoW: {pow_hash}
             </div>
         </div>
         <div class="post-content">{content}</div>
+        {op_images}
+        {op_backlinks}
+        {poll}
         <div class="pow-info">
             <strong>Proof of Work:</strong> Hash {pow_hash} • Nonce: {pow_nonce}
         </div>
+        {op_reactions}
+        {boosts}
     </div>
     
-    <h3>Replies ({})</h3>
+    <h3>Replies ({reply_count})</h3>
+    {bump_status}
     {posts}
     
-    <div class="reply-form">
+    <div class="reply-form"{reply_form_hidden}>
         <h3>Post Reply</h3>
         <p><strong>Note:</strong> Posting requires solving a proof-of-work challenge.</p>
         <div>
@@ -282,17 +825,58 @@ pub async fn show(State(pool): State<DbPool>, Path(id): Path<i64>) -> Result<Htm
             <label>Reply:</label><br>
             <textarea id="reply-body" placeholder="Your reply..."></textarea>
         </div>
+        <div>
+            <label>In reply to post # (optional):</label><br>
+            <input type="number" id="reply-parent" value="{parent}" style="width: 120px;">
+        </div>
+        <div>
+            <label>Images (optional):</label><br>
+            <input type="file" id="reply-files" accept="{accept}" multiple>
+        </div>
+        <div>
+            <label><input type="checkbox" id="reply-sage"> sage (don't bump the thread)</label>
+        </div>
+        <button class="reply-btn" onclick="previewReply()">Preview</button>
         <button id="reply-btn" class="reply-btn" onclick="submitReply()">Mine & Post Reply</button>
+        <div id="reply-preview" class="post-content" style="display: none;"></div>
         <div id="mining-status" style="margin-top: 10px; color: #2DD2C1;"></div>
     </div>
+    {quote_preview}
 </body>
 </html>"#,
-        thread.title,
+        escape_html(&thread.title),
+        markup_css = MARKUP_CSS,
+        post_form_script = POST_FORM_SCRIPT,
+        quote_preview = QUOTE_PREVIEW_SCRIPT,
         thread_id = id,
-        board_slug = "b", // TODO: get actual board slug
-        title = thread.title,
+        board_id = board.id,
+        accept = escape_html(&board.allowed_mime_types),
+        parent = query.parent.map(|parent| parent.to_string()).unwrap_or_default(),
+        board_slug = escape_html(&board.slug),
+        title = escape_html(&thread.title),
         id = thread.id,
-        content = thread.content,
+        content = post_body(&thread.content, thread.deleted_at, &links),
+        op_images = images.get(&None).map(String::as_str).unwrap_or(""),
+        op_backlinks = backlinks.get(&None).map(String::as_str).unwrap_or(""),
+        poll = poll,
+        op_reactions = reaction_bar(None, reactions.get(&None)),
+        boosts = boost_box(thread.bump_score, &contributors, &flairs),
+        op_flair = flair_for(&flairs, thread.author_pubkey.as_deref()),
+        op_edited = edited_link(thread.id, None, thread.edited_at),
+        reply_form_hidden = if thread.is_archived || thread.is_locked { " hidden" } else { "" },
+        bump_status = if thread.is_archived {
+            format!(
+                r#"<div class="pow-info">This thread is archived and read-only. <a href="/boards/{}/archive">Browse the archive</a></div>"#,
+                escape_html(&board.slug)
+            )
+        } else if thread.reply_count >= board.bump_limit {
+            format!(
+                r#"<div class="pow-info">Bump limit of {} replies reached: this thread no longer bumps.</div>"#,
+                board.bump_limit
+            )
+        } else {
+            String::new()
+        },
         pow_hash = thread.pow_hash.as_deref().unwrap_or("pending").chars().take(16).collect::<String>(),
         pow_nonce = thread.pow_nonce.unwrap_or(0),
         created_at = thread.created_at.format("%Y-%m-%d %H:%M"),
@@ -303,21 +887,31 @@ pub async fn show(State(pool): State<DbPool>, Path(id): Path<i64>) -> Result<Htm
             posts
                 .iter()
                 .map(|post| format!(
-                    r#"<div class="post">
+                    r##"<div class="post" id="p{}">
                         <div class="post-header">
                             <div class="post-meta">
-                                Anonymous • {} • Post #{}
+                                Anonymous{} • {} • Post #{}{} • <a href="#reply-body" onclick="document.getElementById('reply-parent').value={}">[reply]</a>
                             </div>
                             <div class="post-meta">
                                 PoW: {}
                             </div>
                         </div>
                         <div class="post-content">{}</div>
-                    </div>"#,
+                        {}
+                        {}
+                        {}
+                    </div>"##,
+                    post.id,
+                    flair_for(&flairs, post.author_pubkey.as_deref()),
                     post.created_at.format("%Y-%m-%d %H:%M"),
                     post.id,
+                    edited_link(id, Some(post.id), post.edited_at),
+                    post.id,
                     post.pow_hash.as_deref().unwrap_or("pending").chars().take(8).collect::<String>(),
-                    post.content
+                    post_body(&post.content, post.deleted_at, &links),
+                    images.get(&Some(post.id)).map(String::as_str).unwrap_or(""),
+                    backlinks.get(&Some(post.id)).map(String::as_str).unwrap_or(""),
+                    reaction_bar(Some(post.id), reactions.get(&Some(post.id)))
                 ))
                 .collect::<Vec<_>>()
                 .join("\n")
@@ -327,12 +921,336 @@ pub async fn show(State(pool): State<DbPool>, Path(id): Path<i64>) -> Result<Htm
     Ok(Html(html))
 }
 
+fn render_tree_node(
+    node: &ReplyNode,
+    thread_id: i64,
+    query: &TreeQuery,
+    flairs: &HashMap<String, String>,
+    backlinks: &HashMap<Option<i64>, String>,
+    links: &QuoteLinks,
+) -> String {
+    let post = &node.post;
+    let children = node
+        .children
+        .iter()
+        .map(|child| render_tree_node(child, thread_id, query, flairs, backlinks, links))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let more = if node.hidden_descendants > 0 {
+        format!(
+            r#"<a class="more" href="/threads/{}/tree?order={}&depth={}&root={}">Continue this thread ({} more)</a>"#,
+            thread_id,
+            query.order.as_str(),
+            query.max_depth(),
+            post.id,
+            node.hidden_descendants
+        )
+    } else {
+        String::new()
+    };
+
+    format!(
+        r#"<details class="tree-node" id="p{id}" open>
+            <summary class="post-meta">Anonymous{flair} • {created_at} • Post #{id}{edited} • PoW {work:.1}{count}</summary>
+            <div class="post-content">{content}</div>
+            {backlinks}
+            <a class="reply-link" href="/threads/{thread_id}?parent={id}#reply-body">[reply]</a>
+            <div class="children">{children}{more}</div>
+        </details>"#,
+        id = post.id,
+        flair = flair_for(flairs, post.author_pubkey.as_deref()),
+        created_at = post.created_at.format("%Y-%m-%d %H:%M"),
+        edited = edited_link(thread_id, Some(post.id), post.edited_at),
+        work = post.pow_difficulty.unwrap_or(0.0),
+        count = if node.children.is_empty() { String::new() } else { format!(" • {} replies", node.children.len()) },
+        content = post_body(&post.content, post.deleted_at, links),
+        backlinks = backlinks.get(&Some(post.id)).map(String::as_str).unwrap_or(""),
+        thread_id = thread_id,
+        children = children,
+        more = more,
+    )
+}
+
+/// Replies nested under their parents, with collapsible subtrees
+pub async fn tree(
+    State(pool): State<DbPool>,
+    Path(id): Path<i64>,
+    Query(query): Query<TreeQuery>,
+) -> Result<Html<String>> {
+    let thread = ThreadRepository::find_by_id(&pool, id)
+        .await?
+        .ok_or(AppError::NotFound)?;
+
+    let posts = PostRepository::list_by_thread(&pool, id).await?;
+    let backlinks = backlinks_by_post(&PostRefRepository::list_for_thread(&pool, id).await?, id);
+    let links = resolve_links(&pool, Some(id), posts.iter().map(|post| post.content.as_str())).await?;
+    let flairs = load_flairs(
+        &pool,
+        posts.iter().filter_map(|post| post.author_pubkey.as_deref()),
+    )
+    .await?;
+    let nodes = build_reply_tree(posts, query.order, query.max_depth(), query.root);
+
+    let order_links = [SiblingOrder::Time, SiblingOrder::Work]
+        .iter()
+        .map(|order| format!(
+            r#"<a class="{}" href="/threads/{}/tree?order={}&depth={}">{}</a>"#,
+            if *order == query.order { "active" } else { "" },
+            id,
+            order.as_str(),
+            query.max_depth(),
+            if *order == SiblingOrder::Time { "Oldest first" } else { "Most work first" }
+        ))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let html = format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+    <title>{title} (tree) - haich2</title>
+    <style>
+        body {{ 
+            font-family: 'Courier New', monospace; 
+            max-width: 1000px; 
+            margin: 0 auto; 
+            padding: 20px;
+            background-color: #3C4267;
+            color: #2DD2C1;
+            line-height: 1.5;
+        }}
+        a {{ 
+            color: #2DD2C1; 
+            text-decoration: none; 
+        }}
+        a:hover {{ 
+            text-decoration: underline; 
+            color: #ffffff;
+        }}
+        .nav {{ margin: 20px 0; }}
+        .nav a {{ 
+            margin-right: 20px; 
+            padding: 8px 16px;
+            background-color: #50589C;
+            border-radius: 4px;
+        }}
+        .nav a.active {{
+            background-color: #2DD2C1;
+            color: #3C4267;
+        }}
+        .tree-node {{
+            border-left: 2px solid #50589C;
+            background-color: #636CCB;
+            margin: 8px 0;
+            padding: 8px 12px;
+            border-radius: 4px;
+        }}
+        .tree-node .tree-node {{ background-color: #5a62b8; }}
+        .post-meta {{ 
+            font-size: 0.85em; 
+            cursor: pointer;
+        }}
+        .post-content {{
+            color: #ffffff;
+            margin: 8px 0;
+            white-space: pre-wrap;
+        }}
+        .children {{ margin-left: 20px; }}
+        .reply-link, .more, .backlinks {{ font-size: 0.8em; }}
+        .tombstone {{ font-style: italic; opacity: 0.7; }}
+        .flair {{
+            background-color: #3C4267;
+            border: 1px solid #2DD2C1;
+            border-radius: 3px;
+            padding: 0 4px;
+            font-weight: bold;
+        }}
+        {markup_css}
+    </style>
+</head>
+<body>
+    <div class="nav">
+        <a href="/threads/{thread_id}">Flat view</a>
+        {root_link}
+    </div>
+    <div class="nav">
+        <strong>Siblings:</strong> {order_links}
+    </div>
+
+    <h2>{title}</h2>
+    {nodes}
+    {quote_preview}
+</body>
+</html>"#,
+        title = escape_html(&thread.title),
+        markup_css = MARKUP_CSS,
+        quote_preview = QUOTE_PREVIEW_SCRIPT,
+        thread_id = id,
+        root_link = if query.root.is_some() {
+            format!(r#"<a href="/threads/{}/tree?order={}">Whole thread</a>"#, id, query.order.as_str())
+        } else {
+            String::new()
+        },
+        order_links = order_links,
+        nodes = if nodes.is_empty() {
+            "<p>No replies yet.</p>".to_string()
+        } else {
+            nodes
+                .iter()
+                .map(|node| render_tree_node(node, id, &query, &flairs, &backlinks, &links))
+                .collect::<Vec<_>>()
+                .join("\n")
+        },
+    );
+
+    Ok(Html(html))
+}
+
+/// Revision log of one post: every version with the proof that produced it
+pub async fn history(
+    State(pool): State<DbPool>,
+    Path(id): Path<i64>,
+    Query(query): Query<HistoryQuery>,
+) -> Result<Html<String>> {
+    let (target, revisions) = load_history(&pool, id, query.post_id).await?;
+    // Not the thread page, so even same-thread quotes link back to it
+    let links = resolve_links(&pool, None, revisions.iter().map(|revision| revision.content.as_str())).await?;
+    let anchor = query.post_id.map(|post_id| format!("#p{}", post_id)).unwrap_or_default();
+
+    let html = format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+    <title>{title} (history) - haich2</title>
+    <style>
+        body {{ 
+            font-family: 'Courier New', monospace; 
+            max-width: 900px; 
+            margin: 0 auto; 
+            padding: 20px;
+            background-color: #3C4267;
+            color: #2DD2C1;
+            line-height: 1.5;
+        }}
+        a {{ 
+            color: #2DD2C1; 
+            text-decoration: none; 
+        }}
+        a:hover {{ 
+            text-decoration: underline; 
+            color: #ffffff;
+        }}
+        .nav {{ margin: 20px 0; }}
+        .nav a {{ 
+            margin-right: 20px; 
+            padding: 8px 16px;
+            background-color: #50589C;
+            border-radius: 4px;
+        }}
+        .revision {{
+            border: 1px solid #50589C;
+            margin: 10px 0;
+            padding: 15px;
+            background-color: #636CCB;
+            border-radius: 4px;
+        }}
+        .revision.current {{ border-color: #2DD2C1; }}
+        .post-meta {{ font-size: 0.85em; opacity: 0.8; }}
+        .post-content {{
+            color: #ffffff;
+            margin: 10px 0;
+            white-space: pre-wrap;
+        }}
+        .pow-info {{
+            background-color: #50589C;
+            padding: 10px;
+            border-radius: 4px;
+            font-size: 0.8em;
+            word-break: break-all;
+        }}
+        {markup_css}
+    </style>
+</head>
+<body>
+    <div class="nav">
+        <a href="/threads/{thread_id}{anchor}">Back to thread</a>
+    </div>
+
+    <h2>History of {what}</h2>
+    <p>The original proof of work stays the post's anchor. Each edit was signed by the author and paid for with its own proof.</p>
+    {revisions}
+    {quote_preview}
+</body>
+</html>"#,
+        title = escape_html(&target.thread.title),
+        markup_css = MARKUP_CSS,
+        quote_preview = QUOTE_PREVIEW_SCRIPT,
+        thread_id = id,
+        anchor = anchor,
+        what = match query.post_id {
+            Some(post_id) => format!("post #{}", post_id),
+            None => format!("thread #{}", id),
+        },
+        revisions = revisions
+            .iter()
+            .rev()
+            .enumerate()
+            .map(|(index, revision)| format!(
+                r#"<div class="revision{current}">
+                    <div class="post-meta">Revision {number} • {created_at}{label}</div>
+                    {title}
+                    <div class="post-content">{content}</div>
+                    <div class="pow-info">
+                        Proof: hash {pow_hash} • nonce {pow_nonce} • work {work:.1} • challenge {challenge}<br>
+                        {signature}
+                    </div>
+                </div>"#,
+                current = if index == 0 { " current" } else { "" },
+                number = revision.revision,
+                created_at = revision.created_at.format("%Y-%m-%d %H:%M:%S"),
+                label = match (index, revision.revision) {
+                    (0, 0) => " • original, never edited",
+                    (0, _) => " • current",
+                    (_, 0) => " • original",
+                    _ => "",
+                },
+                title = revision
+                    .title
+                    .as_deref()
+                    .map(|title| format!(r#"<h3>{}</h3>"#, escape_html(title)))
+                    .unwrap_or_default(),
+                content = render_post_with(&revision.content, &links),
+                pow_hash = escape_html(revision.pow_hash.as_deref().unwrap_or("none")),
+                pow_nonce = revision.pow_nonce.unwrap_or(0),
+                work = revision.pow_difficulty.unwrap_or(0.0),
+                challenge = escape_html(revision.pow_challenge_id.as_deref().unwrap_or("none")),
+                signature = match &revision.signature_hex {
+                    Some(signature) => format!(
+                        "Signed by {}: {}",
+                        escape_html(revision.author_pubkey.as_deref().unwrap_or("")),
+                        escape_html(signature)
+                    ),
+                    None => "Original post, anchored by its own proof".to_string(),
+                },
+            ))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    );
+
+    Ok(Html(html))
+}
+
 pub async fn new_form(State(pool): State<DbPool>, Path(board_id): Path<i64>) -> Result<Html<String>> {
+    let board = BoardRepository::find_by_id(&pool, board_id)
+        .await?
+        .filter(|board| board.is_active)
+        .ok_or(AppError::NotFound)?;
+
     let html = format!(
         r#"<!DOCTYPE html>
 <html>
 <head>
-    <title>New Thread - haich2</title>
+    <title>New Thread on /{board_slug}/ - haich2</title>
     <style>
         body {{ 
             font-family: 'Courier New', monospace; 
@@ -414,36 +1332,50 @@ pub async fn new_form(State(pool): State<DbPool>, Path(board_id): Path<i64>) ->
             background-color: #50589C;
             display: none;
         }}
+        {markup_css}
     </style>
+    {post_form_script}
     <script>
-        async function minePoW(canonicalBytes, requiredPrefix) {{
-            let nonce = 0;
-            while (nonce < 1000000) {{
-                const input = canonicalBytes + nonce.toString(16).padStart(16, '0');
-                const hash = await crypto.subtle.digest('SHA-256', new TextEncoder().encode(input));
-                const hashHex = Array.from(new Uint8Array(hash))
-                    .map(b => b.toString(16).padStart(2, '0'))
-                    .join('');
-                
-                if (hashHex.startsWith(requiredPrefix)) {{
-                    return {{ nonce, hash: hashHex }};
-                }}
-                nonce++;
-                
-                if (nonce % 10000 === 0) {{
-                    document.getElementById('mining-status').textContent = `Mining... tried ${{nonce}} nonces`;
-                    await new Promise(resolve => setTimeout(resolve, 1));
-                }}
-            }}
-            throw new Error('Failed to find solution');
+        // The poll to open with the thread, or null if no question was asked
+        function readPoll() {{
+            const question = document.getElementById('poll-question').value;
+            if (!question.trim()) return null;
+            const options = document.getElementById('poll-options').value
+                .split('\n')
+                .filter(option => option.trim());
+            return {{ question: question, options: options }};
+        }}
+        
+        async function previewThread() {{
+            const body = document.getElementById('body').value;
+            const attachments = await uploadFiles(document.getElementById('files'), {board_id});
+            const response = await fetch('/api/pow/preview', {{
+                method: 'POST',
+                headers: {{ 'Content-Type': 'application/json' }},
+                body: JSON.stringify({{
+                    board_id: {board_id},
+                    user_pubkey_hex: document.getElementById('user-pubkey').value,
+                    post_draft: {{
+                        title: document.getElementById('title').value,
+                        body: body,
+                        attachments: attachments,
+                        refs: [...new Set(body.match(/>>>\/[a-z0-9]+\/\d+|>>\d+/g) || [])],
+                        poll: readPoll()
+                    }}
+                }})
+            }});
+            showPreview(document.getElementById('thread-preview'), await response.json());
         }}
         
         async function submitThread() {{
             const title = document.getElementById('title').value;
             const body = document.getElementById('body').value;
+            const refs = [...new Set(body.match(/>>>\/[a-z0-9]+\/\d+|>>\d+/g) || [])];
             const pubkey = document.getElementById('user-pubkey').value;
+            const files = document.getElementById('files');
+            const poll = readPoll();
             
-            if (!title.trim() || !body.trim() || !pubkey.trim()) {{
+            if (!title.trim() || (!body.trim() && !files.files.length) || !pubkey.trim()) {{
                 alert('Please fill in all fields');
                 return;
             }}
@@ -453,21 +1385,28 @@ pub async fn new_form(State(pool): State<DbPool>, Path(board_id): Path<i64>) ->
             statusEl.textContent = 'Starting PoW mining...';
             document.getElementById('submit-btn').disabled = true;
             
+            // Seconds, bound into the canonical bytes and reused at commit
+            const timestamp = Math.floor(Date.now() / 1000);
+            
             try {{
+                const attachments = await uploadFiles(files, {board_id});
+                
                 // Begin challenge
                 const beginResponse = await fetch('/api/pow/thread/begin', {{
                     method: 'POST',
                     headers: {{ 'Content-Type': 'application/json' }},
                     body: JSON.stringify({{
                         client_op_id: crypto.randomUUID(),
+                        board_id: {board_id},
                         post_draft: {{ 
                             title: title,
                             body: body,
-                            attachments: [],
-                            refs: []
+                            attachments: attachments,
+                            refs: refs,
+                            poll: poll
                         }},
                         user_pubkey_hex: pubkey,
-                        timestamp_i64: Math.floor(Date.now() / 1000)
+                        timestamp_i64: timestamp
                     }})
                 }});
                 
@@ -490,13 +1429,14 @@ pub async fn new_form(State(pool): State<DbPool>, Path(board_id): Path<i64>) ->
                         post_draft: {{ 
                             title: title,
                             body: body,
-                            attachments: [],
-                            refs: []
+                            attachments: attachments,
+                            refs: refs,
+                            poll: poll
                         }},
                         proof: {{
                             nonce_u64: solution.nonce,
                             miner_version: 1,
-                            timestamp_i64: Math.floor(Date.now() / 1000)
+                            timestamp_i64: timestamp
                         }},
                         user_pubkey_hex: pubkey
                     }})
@@ -520,11 +1460,11 @@ pub async fn new_form(State(pool): State<DbPool>, Path(board_id): Path<i64>) ->
     <div class="nav">
         <a href="/">Home</a>
         <a href="/boards">Boards</a>
-        <a href="/boards/b">Back to Board</a>
+        <a href="/boards/{board_slug}">Back to /{board_slug}/</a>
     </div>
     
     <div class="form-container">
-        <h2>Create New Thread</h2>
+        <h2>Create New Thread on /{board_slug}/ - {board_name}</h2>
         
         <div class="pow-info">
             <strong>Proof of Work Required:</strong> Creating a thread requires solving a cryptographic puzzle. 
@@ -547,14 +1487,42 @@ pub async fn new_form(State(pool): State<DbPool>, Path(board_id): Path<i64>) ->
             <textarea id="body" required></textarea>
         </div>
         
+        <div class="form-group">
+            <label for="files">Images (optional):</label>
+            <input type="file" id="files" accept="{accept}" multiple>
+            <small>Up to {max_upload_bytes} bytes each</small>
+        </div>
+        
+        <div class="form-group">
+            <label for="poll-question">Poll (optional):</label>
+            <input type="text" id="poll-question" maxlength="{max_question}" placeholder="Question">
+            <textarea id="poll-options" placeholder="One option per line, {min_options} to {max_options}"></textarea>
+            <small>Each key gets one vote, mined and signed; tallies show voters and work</small>
+        </div>
+        
+        <button class="submit-btn" onclick="previewThread()">Preview</button>
         <button id="submit-btn" class="submit-btn" onclick="submitThread()">
             Mine & Create Thread
         </button>
         
+        <div id="thread-preview" style="display: none;"></div>
+        
         <div id="mining-status"></div>
     </div>
+    {quote_preview}
 </body>
-</html>"#
+</html>"#,
+        markup_css = MARKUP_CSS,
+        post_form_script = POST_FORM_SCRIPT,
+        quote_preview = QUOTE_PREVIEW_SCRIPT,
+        board_id = board.id,
+        board_slug = escape_html(&board.slug),
+        board_name = escape_html(&board.name),
+        accept = escape_html(&board.allowed_mime_types),
+        max_upload_bytes = board.max_upload_bytes,
+        max_question = polls::MAX_QUESTION_CHARS,
+        min_options = polls::MIN_POLL_OPTIONS,
+        max_options = polls::MAX_POLL_OPTIONS,
     );
 
     Ok(Html(html))
@@ -0,0 +1,230 @@
+//! Key identity helpers: signature checks and pseudonym linkage proofs.
+//!
+//! Per-board and per-thread pseudonyms are derived client-side with
+//! [`derive_child_secret`], as `SHA256("HC1_PSEUDO_" || main_secret || context)`
+//! where the context is `"b:<slug>"` for a board key or `"t:<thread_id>"` for a
+//! thread key. The server only ever sees the child
+//! pubkey, which posts and mines like any other key. A user may later publish
+//! a linkage proof, signed by both keys, to tie a pseudonym to their main
+//! identity.
+
+use secp256k1::{ecdsa::Signature, Message, PublicKey, Secp256k1, SecretKey};
+use std::str::FromStr;
+
+use crate::pow::sha256_bytes;
+
+/// Parse a compressed secp256k1 public key in hex
+pub fn parse_pubkey(pubkey_hex: &str) -> Option<PublicKey> {
+    if pubkey_hex.len() != 66 {
+        return None;
+    }
+    PublicKey::from_str(pubkey_hex).ok()
+}
+
+/// Verify a compact ECDSA signature by `pubkey_hex` over SHA256(message)
+pub fn verify_signature(pubkey_hex: &str, message: &[u8], signature_hex: &str) -> bool {
+    let Some(pubkey) = parse_pubkey(pubkey_hex) else {
+        return false;
+    };
+    let Ok(signature_bytes) = hex::decode(signature_hex) else {
+        return false;
+    };
+    let Ok(signature) = Signature::from_compact(&signature_bytes) else {
+        return false;
+    };
+    let message = Message::from_digest(sha256_bytes(message));
+
+    Secp256k1::verification_only()
+        .verify_ecdsa(&message, &signature, &pubkey)
+        .is_ok()
+}
+
+/// Derive the pseudonym secret for a context (`"b:<slug>"` or `"t:<thread_id>"`) from a
+/// main secret. None only if the digest falls outside the curve order, which in practice
+/// never happens.
+pub fn derive_child_secret(main_secret: &SecretKey, context: &str) -> Option<SecretKey> {
+    let mut input = Vec::new();
+    input.extend_from_slice(b"HC1_PSEUDO_");
+    input.extend_from_slice(&main_secret.secret_bytes());
+    input.extend_from_slice(context.as_bytes());
+    SecretKey::from_slice(&sha256_bytes(&input)).ok()
+}
+
+/// Message signed to authenticate a request as the owner of a key
+pub fn auth_message(purpose: &str, pubkey_hex: &str, timestamp_i64: i64) -> Vec<u8> {
+    let mut message = Vec::new();
+    message.extend_from_slice(b"HC1_AUTH_");
+    message.extend_from_slice(purpose.as_bytes());
+    message.extend_from_slice(b"_");
+    message.extend_from_slice(pubkey_hex.as_bytes());
+    message.extend_from_slice(b"_");
+    message.extend_from_slice(timestamp_i64.to_string().as_bytes());
+    message
+}
+
+/// Message an author signs to replace the text of their post. `post_id` is 0 for a
+/// thread's opening post; `draft_hash_hex` is the SHA-256 of the new draft's JSON.
+pub fn edit_message(
+    pubkey_hex: &str,
+    thread_id: i64,
+    post_id: i64,
+    timestamp_i64: i64,
+    draft_hash_hex: &str,
+) -> Vec<u8> {
+    let mut message = Vec::new();
+    message.extend_from_slice(b"HC1_EDIT_");
+    message.extend_from_slice(pubkey_hex.as_bytes());
+    message.extend_from_slice(format!("_{}_{}_{}_", thread_id, post_id, timestamp_i64).as_bytes());
+    message.extend_from_slice(draft_hash_hex.as_bytes());
+    message
+}
+
+/// Message a voter signs to cast their one vote in a poll
+pub fn vote_message(pubkey_hex: &str, poll_id: i64, option_id: i64, timestamp_i64: i64) -> Vec<u8> {
+    let mut message = Vec::new();
+    message.extend_from_slice(b"HC1_VOTE_");
+    message.extend_from_slice(pubkey_hex.as_bytes());
+    message.extend_from_slice(format!("_{}_{}_{}", poll_id, option_id, timestamp_i64).as_bytes());
+    message
+}
+
+/// Message a key signs to claim a mined vanity pattern as its flair
+pub fn flair_message(pubkey_hex: &str, pattern: &str, nonce_u64: u64, timestamp_i64: i64) -> Vec<u8> {
+    let mut message = Vec::new();
+    message.extend_from_slice(b"HC1_FLAIR_");
+    message.extend_from_slice(pubkey_hex.as_bytes());
+    message.extend_from_slice(format!("_{}_{}_{}", pattern, nonce_u64, timestamp_i64).as_bytes());
+    message
+}
+
+/// Message both keys sign to prove a pseudonym belongs to a main identity
+pub fn link_message(main_pubkey_hex: &str, child_pubkey_hex: &str) -> Vec<u8> {
+    let mut message = Vec::new();
+    message.extend_from_slice(b"HC1_LINK_");
+    message.extend_from_slice(main_pubkey_hex.as_bytes());
+    message.extend_from_slice(b"_");
+    message.extend_from_slice(child_pubkey_hex.as_bytes());
+    message
+}
+
+/// A linkage proof needs both signatures, so nobody can claim someone else's key
+pub fn verify_link_proof(
+    main_pubkey_hex: &str,
+    child_pubkey_hex: &str,
+    main_signature_hex: &str,
+    child_signature_hex: &str,
+) -> bool {
+    if main_pubkey_hex == child_pubkey_hex {
+        return false;
+    }
+
+    let message = link_message(main_pubkey_hex, child_pubkey_hex);
+    verify_signature(main_pubkey_hex, &message, main_signature_hex)
+        && verify_signature(child_pubkey_hex, &message, child_signature_hex)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keypair() -> (SecretKey, String) {
+        let secp = Secp256k1::new();
+        let (secret, public) = secp.generate_keypair(&mut rand::thread_rng());
+        (secret, public.to_string())
+    }
+
+    fn sign(secret: &SecretKey, message: &[u8]) -> String {
+        let digest = Message::from_digest(sha256_bytes(message));
+        let signature = Secp256k1::new().sign_ecdsa(&digest, secret);
+        hex::encode(signature.serialize_compact())
+    }
+
+    #[test]
+    fn test_link_proof_requires_both_keys() {
+        let (main_secret, main_pubkey) = keypair();
+        let (child_secret, child_pubkey) = keypair();
+        let message = link_message(&main_pubkey, &child_pubkey);
+
+        let main_sig = sign(&main_secret, &message);
+        let child_sig = sign(&child_secret, &message);
+        assert!(verify_link_proof(&main_pubkey, &child_pubkey, &main_sig, &child_sig));
+
+        // Signing with the same key twice doesn't prove control of the child
+        let forged = sign(&main_secret, &message);
+        assert!(!verify_link_proof(&main_pubkey, &child_pubkey, &main_sig, &forged));
+
+        // The proof is directional
+        assert!(!verify_link_proof(&child_pubkey, &main_pubkey, &child_sig, &main_sig));
+    }
+
+    #[test]
+    fn test_child_secret_is_stable_per_context() {
+        let (main_secret, main_pubkey) = keypair();
+        let board_key = derive_child_secret(&main_secret, "b:tech").unwrap();
+
+        assert_eq!(derive_child_secret(&main_secret, "b:tech"), Some(board_key));
+        assert_ne!(derive_child_secret(&main_secret, "b:art"), Some(board_key));
+        assert_ne!(derive_child_secret(&main_secret, "t:42"), Some(board_key));
+
+        // Another main identity gets unrelated pseudonyms
+        let (other_secret, _) = keypair();
+        assert_ne!(derive_child_secret(&other_secret, "b:tech"), Some(board_key));
+
+        let child_pubkey = board_key.public_key(&Secp256k1::new()).to_string();
+        assert_ne!(child_pubkey, main_pubkey);
+    }
+
+    #[test]
+    fn test_derived_child_can_prove_link() {
+        let (main_secret, main_pubkey) = keypair();
+        let child_secret = derive_child_secret(&main_secret, "t:7").unwrap();
+        let child_pubkey = child_secret.public_key(&Secp256k1::new()).to_string();
+        let message = link_message(&main_pubkey, &child_pubkey);
+
+        assert!(verify_link_proof(
+            &main_pubkey,
+            &child_pubkey,
+            &sign(&main_secret, &message),
+            &sign(&child_secret, &message),
+        ));
+    }
+
+    #[test]
+    fn test_edit_signature_binds_target() {
+        let (secret, pubkey) = keypair();
+        let hash = "ab".repeat(32);
+        let signature = sign(&secret, &edit_message(&pubkey, 7, 12, 1_700_000_000, &hash));
+
+        assert!(verify_signature(&pubkey, &edit_message(&pubkey, 7, 12, 1_700_000_000, &hash), &signature));
+        // Not reusable for another post or another draft
+        assert!(!verify_signature(&pubkey, &edit_message(&pubkey, 7, 13, 1_700_000_000, &hash), &signature));
+        assert!(!verify_signature(&pubkey, &edit_message(&pubkey, 7, 12, 1_700_000_000, &"cd".repeat(32)), &signature));
+    }
+
+    #[test]
+    fn test_vote_signature_binds_option() {
+        let (secret, pubkey) = keypair();
+        let signature = sign(&secret, &vote_message(&pubkey, 3, 9, 1_700_000_000));
+
+        assert!(verify_signature(&pubkey, &vote_message(&pubkey, 3, 9, 1_700_000_000), &signature));
+        assert!(!verify_signature(&pubkey, &vote_message(&pubkey, 3, 10, 1_700_000_000), &signature));
+        assert!(!verify_signature(&pubkey, &vote_message(&pubkey, 4, 9, 1_700_000_000), &signature));
+    }
+
+    #[test]
+    fn test_flair_signature_binds_pattern() {
+        let (secret, pubkey) = keypair();
+        let signature = sign(&secret, &flair_message(&pubkey, "21e8", 42, 1_700_000_000));
+
+        assert!(verify_signature(&pubkey, &flair_message(&pubkey, "21e8", 42, 1_700_000_000), &signature));
+        assert!(!verify_signature(&pubkey, &flair_message(&pubkey, "21e", 42, 1_700_000_000), &signature));
+        assert!(!verify_signature(&pubkey, &flair_message(&pubkey, "21e8", 43, 1_700_000_000), &signature));
+    }
+
+    #[test]
+    fn test_verify_signature_rejects_garbage() {
+        let (_, pubkey) = keypair();
+        assert!(!verify_signature(&pubkey, b"msg", "zz"));
+        assert!(!verify_signature("02abc", b"msg", &"00".repeat(64)));
+    }
+}
\ No newline at end of file
@@ -1,18 +1,27 @@
+mod attachments;
 mod config;
 mod db;
 mod error;
 mod handlers;
+mod identity;
+mod markup;
 mod models;
+mod polls;
 mod pow;
+mod reactions;
+mod reputation;
 mod templates;
+mod tree;
+mod uniqueness;
 
 use axum::{
+    extract::DefaultBodyLimit,
     routing::{get, post},
     Router,
 };
 use sqlx::sqlite::SqlitePoolOptions;
 use std::net::SocketAddr;
-use tower_http::{cors::CorsLayer, trace::TraceLayer};
+use tower_http::{cors::CorsLayer, services::ServeDir, trace::TraceLayer};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 use crate::{config::Config, handlers::*};
@@ -32,17 +41,73 @@ async fn main() -> anyhow::Result<()> {
     
     sqlx::migrate!("./migrations").run(&pool).await?;
 
+    // Drop deleted content past its retention window and files no post kept, at startup and hourly after
+    let purge_pool = pool.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(3600));
+        loop {
+            interval.tick().await;
+            match db::DeletedContentRepository::purge_expired(&purge_pool).await {
+                Ok(0) => {}
+                Ok(purged) => tracing::info!("Purged {} expired deleted posts", purged),
+                Err(error) => tracing::warn!("Failed to purge deleted content: {}", error),
+            }
+            match handlers::attachments::purge_orphans(&purge_pool).await {
+                Ok(0) => {}
+                Ok(purged) => tracing::info!("Purged {} unlinked uploads", purged),
+                Err(error) => tracing::warn!("Failed to purge unlinked uploads: {}", error),
+            }
+        }
+    });
+
     let app = Router::new()
         .route("/", get(home::index))
         .route("/boards", get(boards::list))
         .route("/boards/:slug", get(boards::show))
+        .route("/boards/:slug/catalog", get(boards::catalog))
+        .route("/boards/:slug/archive", get(boards::archive))
         .route("/threads/new/:board_id", get(threads::new_form).post(threads::create_begin))
         .route("/threads/:id", get(threads::show))
+        .route("/threads/:id/tree", get(threads::tree))
+        .route("/threads/:id/history", get(threads::history))
+        .route("/notifications", get(notifications::page))
         .route("/api/pow/params", get(api::pow_params))
         .route("/api/pow/thread/begin", post(api::thread_begin))
         .route("/api/pow/thread/commit", post(api::thread_commit))
         .route("/api/pow/reply/begin", post(api::reply_begin))
         .route("/api/pow/reply/commit", post(api::reply_commit))
+        .route("/api/pow/preview", post(api::preview))
+        .route("/api/attachments/:board_id/begin", post(handlers::attachments::upload_begin))
+        .route(
+            "/api/attachments/:board_id",
+            post(handlers::attachments::upload).layer(DefaultBodyLimit::max(config.upload_max_bytes)),
+        )
+        .route("/api/threads/:id/graph", get(api::thread_graph))
+        .route("/api/threads/:id/tree", get(api::thread_tree))
+        .route("/api/threads/:id/history", get(edits::history))
+        .route("/api/edit/begin", post(edits::edit_begin))
+        .route("/api/edit/commit", post(edits::edit_commit))
+        .route("/api/posts/delete", post(edits::delete))
+        .route("/api/polls/:id", get(handlers::polls::results))
+        .route("/api/polls/vote/begin", post(handlers::polls::vote_begin))
+        .route("/api/polls/vote/commit", post(handlers::polls::vote_commit))
+        .route("/api/threads/:id/reactions", get(handlers::reactions::thread_reactions))
+        .route("/api/reactions/begin", post(handlers::reactions::react_begin))
+        .route("/api/reactions/commit", post(handlers::reactions::react_commit))
+        .route("/api/quote", get(quotes::preview))
+        .route("/api/threads/:id/boosts", get(boosts::contributors))
+        .route("/api/boost/begin", post(boosts::boost_begin))
+        .route("/api/boost/commit", post(boosts::boost_commit))
+        .route("/api/dm/begin", post(messages::dm_begin))
+        .route("/api/dm/commit", post(messages::dm_commit))
+        .route("/api/dm/inbox", post(messages::inbox))
+        .route("/api/notifications", post(notifications::list))
+        .route("/api/notifications/read", post(notifications::mark_read))
+        .route("/api/user/vanity", post(api::vanity_flair))
+        .route("/api/user/link", post(api::link_pseudonym))
+        .route("/api/mod/users/:pubkey/revoke-reputation", post(moderation::revoke_reputation))
+        .route("/api/mod/deleted", get(moderation::deleted_content))
+        .nest_service("/uploads", ServeDir::new(&config.upload_dir))
         .layer(TraceLayer::new_for_http())
         .layer(CorsLayer::permissive())
         .with_state(pool);
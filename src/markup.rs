@@ -0,0 +1,412 @@
+//! Post markup. Everything a user writes is escaped first; the only HTML that
+//! reaches a page is what this module emits for the syntax below.
+//!
+//! - lines starting with `>` are greentext (unless they open with a `>>123` quote)
+//! - `>>123` links to post 123, in whichever thread it lives
+//! - `>>>/tech/42` links to post 42 on /tech/, or to thread 42 there if no such post
+//! - `[spoiler]...[/spoiler]` hides text until hovered
+//! - `` `code` `` inline, and ```` ``` ```` fences for blocks
+//! - bare `http://` and `https://` URLs become `rel="nofollow"` links
+
+use std::collections::HashMap;
+
+/// A quote as written in a post body or a draft's refs
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Quote {
+    /// `>>123`
+    Post(i64),
+    /// `>>>/slug/123`
+    Board { slug: String, id: i64 },
+}
+
+impl Quote {
+    /// Parse a draft ref: "123", ">>123" or ">>>/slug/123"
+    pub fn parse_ref(r: &str) -> Option<Self> {
+        let r = r.trim();
+        let quote = match quote_at(r) {
+            Some((quote, len)) if len == r.len() => quote,
+            Some(_) => return None,
+            None => Quote::Post(r.parse().ok()?),
+        };
+        Some(quote).filter(|quote| quote.id() > 0)
+    }
+
+    pub fn id(&self) -> i64 {
+        match self {
+            Quote::Post(id) | Quote::Board { id, .. } => *id,
+        }
+    }
+
+    /// Query string for the quote preview endpoint
+    pub fn preview_query(&self) -> String {
+        match self {
+            Quote::Post(id) => format!("id={}", id),
+            Quote::Board { slug, id } => format!("board={}&id={}", slug, id),
+        }
+    }
+}
+
+/// Where a quote resolved to; `post_id` is None for a thread's opening post
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QuoteTarget {
+    pub thread_id: i64,
+    pub post_id: Option<i64>,
+}
+
+/// Quote targets looked up before rendering, so links can leave the thread they're in
+#[derive(Debug, Default)]
+pub struct QuoteLinks {
+    /// Thread the page shows; quotes into it link by anchor
+    pub thread_id: Option<i64>,
+    pub targets: HashMap<Quote, QuoteTarget>,
+}
+
+impl QuoteLinks {
+    fn href(&self, target: &QuoteTarget) -> String {
+        let anchor = target.post_id.map(|id| format!("#p{}", id)).unwrap_or_default();
+        if Some(target.thread_id) == self.thread_id && target.post_id.is_some() {
+            anchor
+        } else {
+            format!("/threads/{}{}", target.thread_id, anchor)
+        }
+    }
+}
+
+/// Every quote in a post body, in order of first appearance
+pub fn quotes_in(body: &str) -> Vec<Quote> {
+    let mut quotes = Vec::new();
+    let mut rest = body;
+
+    while let Some(start) = rest.find(">>") {
+        rest = &rest[start..];
+        match quote_at(rest) {
+            Some((quote, len)) => {
+                if !quotes.contains(&quote) {
+                    quotes.push(quote);
+                }
+                rest = &rest[len..];
+            }
+            None => rest = &rest[1..],
+        }
+    }
+
+    quotes
+}
+
+/// Escape text for use in HTML bodies and quoted attribute values
+pub fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        push_escaped(&mut out, c);
+    }
+    out
+}
+
+fn push_escaped(out: &mut String, c: char) {
+    match c {
+        '&' => out.push_str("&amp;"),
+        '<' => out.push_str("&lt;"),
+        '>' => out.push_str("&gt;"),
+        '"' => out.push_str("&quot;"),
+        '\'' => out.push_str("&#39;"),
+        _ => out.push(c),
+    }
+}
+
+const FENCE: &str = "```";
+const SPOILER_OPEN: &str = "[spoiler]";
+const SPOILER_CLOSE: &str = "[/spoiler]";
+
+/// Render a post body to HTML, with quotes linking within the thread
+pub fn render_post(body: &str) -> String {
+    render_post_with(body, &QuoteLinks::default())
+}
+
+/// Render a post body to HTML, linking quotes to wherever `links` resolved them
+pub fn render_post_with(body: &str, links: &QuoteLinks) -> String {
+    let mut out = String::with_capacity(body.len() + body.len() / 4);
+    let mut lines = Vec::new();
+    let mut code: Option<Vec<&str>> = None;
+
+    for line in body.lines() {
+        match code.as_mut() {
+            Some(block) => {
+                if line.trim_end() == FENCE {
+                    lines.push(render_code_block(block));
+                    code = None;
+                } else {
+                    block.push(line);
+                }
+            }
+            None if line.trim_start().starts_with(FENCE) => code = Some(Vec::new()),
+            None => lines.push(render_line(line, links)),
+        }
+    }
+
+    // An unclosed fence runs to the end of the post
+    if let Some(block) = code {
+        lines.push(render_code_block(&block));
+    }
+
+    out.push_str(&lines.join("<br>"));
+    out
+}
+
+fn render_code_block(lines: &[&str]) -> String {
+    format!("<pre><code>{}</code></pre>", escape_html(&lines.join("\n")))
+}
+
+fn render_line(line: &str, links: &QuoteLinks) -> String {
+    if line.starts_with('>') && quote_at(line).is_none() {
+        format!(r#"<span class="greentext">{}</span>"#, render_inline(line, links))
+    } else {
+        render_inline(line, links)
+    }
+}
+
+/// The `>>123` or `>>>/slug/123` quote at the start of `text`, and its length in bytes
+fn quote_at(text: &str) -> Option<(Quote, usize)> {
+    if let Some(board) = text.strip_prefix(">>>/") {
+        let slug_len = board.bytes().take_while(|b| b.is_ascii_lowercase() || b.is_ascii_digit()).count();
+        let digits = board[slug_len..].strip_prefix('/').filter(|_| slug_len > 0)?;
+        let len = digits.bytes().take_while(u8::is_ascii_digit).count();
+        let id = digits[..len].parse().ok()?;
+        let quote = Quote::Board { slug: board[..slug_len].to_string(), id };
+        return Some((quote, 4 + slug_len + 1 + len));
+    }
+
+    let digits = text.strip_prefix(">>")?;
+    let len = digits.bytes().take_while(u8::is_ascii_digit).count();
+    let id = digits[..len].parse().ok()?;
+    Some((Quote::Post(id), 2 + len))
+}
+
+fn render_quote(quote: &Quote, links: &QuoteLinks) -> String {
+    let text = match quote {
+        Quote::Post(id) => format!("&gt;&gt;{}", id),
+        Quote::Board { slug, id } => format!("&gt;&gt;&gt;/{}/{}", slug, id),
+    };
+
+    match (links.targets.get(quote), quote) {
+        (Some(target), _) => format!(
+            r#"<a class="quotelink" href="{}" data-quote="{}">{}</a>"#,
+            links.href(target),
+            escape_html(&quote.preview_query()),
+            text
+        ),
+        // Unresolved in-thread quotes still anchor, as they did before lookups
+        (None, Quote::Post(id)) => format!(r##"<a class="quotelink" href="#p{id}">{text}</a>"##),
+        (None, Quote::Board { .. }) => format!(r#"<span class="deadlink">{}</span>"#, text),
+    }
+}
+
+/// Length in bytes of a URL at the start of `text`, without trailing punctuation
+fn url_at(text: &str) -> Option<usize> {
+    if !(text.starts_with("http://") || text.starts_with("https://")) {
+        return None;
+    }
+
+    let end = text
+        .find(|c: char| c.is_whitespace() || matches!(c, '<' | '>' | '"' | '\'' | '`' | '[' | ']'))
+        .unwrap_or(text.len());
+    let url = text[..end].trim_end_matches(['.', ',', ';', ':', '!', '?', ')']);
+
+    // Require something after the scheme
+    if url.ends_with("//") {
+        None
+    } else {
+        Some(url.len())
+    }
+}
+
+fn render_inline(text: &str, links: &QuoteLinks) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    let mut at_word_start = true;
+
+    while let Some(c) = rest.chars().next() {
+        if c == '`' {
+            if let Some(end) = rest[1..].find('`') {
+                out.push_str("<code>");
+                out.push_str(&escape_html(&rest[1..1 + end]));
+                out.push_str("</code>");
+                rest = &rest[end + 2..];
+                at_word_start = false;
+                continue;
+            }
+        }
+
+        if rest.starts_with(SPOILER_OPEN) {
+            if let Some(end) = rest.find(SPOILER_CLOSE) {
+                out.push_str(r#"<span class="spoiler">"#);
+                out.push_str(&render_inline(&rest[SPOILER_OPEN.len()..end], links));
+                out.push_str("</span>");
+                rest = &rest[end + SPOILER_CLOSE.len()..];
+                at_word_start = false;
+                continue;
+            }
+        }
+
+        if let Some((quote, len)) = quote_at(rest) {
+            out.push_str(&render_quote(&quote, links));
+            rest = &rest[len..];
+            at_word_start = false;
+            continue;
+        }
+
+        if at_word_start {
+            if let Some(len) = url_at(rest) {
+                let url = escape_html(&rest[..len]);
+                out.push_str(&format!(
+                    r#"<a href="{url}" rel="nofollow noopener noreferrer" target="_blank">{url}</a>"#
+                ));
+                rest = &rest[len..];
+                at_word_start = false;
+                continue;
+            }
+        }
+
+        push_escaped(&mut out, c);
+        at_word_start = !c.is_alphanumeric();
+        rest = &rest[c.len_utf8()..];
+    }
+
+    out
+}
+
+/// Styles for the classes `render_post` emits, spliced into a page's `<style>` as a format argument
+pub const MARKUP_CSS: &str = r#"
+        .greentext { color: #8fd694; }
+        .quotelink { color: #ffffff; text-decoration: underline; }
+        .deadlink { color: #ffffff; text-decoration: line-through; opacity: 0.7; }
+        .quote-preview { position: absolute; max-width: 500px; z-index: 10; background-color: #636CCB; border: 1px solid #2DD2C1; border-radius: 4px; padding: 10px; color: #ffffff; white-space: pre-wrap; }
+        .spoiler { background-color: #000000; color: #000000; }
+        .spoiler:hover { color: #ffffff; }
+        pre { background-color: #3C4267; padding: 10px; border-radius: 4px; overflow-x: auto; }
+        code { background-color: #3C4267; padding: 0 3px; color: #2DD2C1; }
+"#;
+
+/// Hover previews for resolved quote links, spliced into a page after its posts
+pub const QUOTE_PREVIEW_SCRIPT: &str = r#"
+    <script>
+        document.addEventListener('mouseover', async (event) => {
+            const link = event.target.closest('a.quotelink[data-quote]');
+            if (!link || link.dataset.previewing) return;
+            link.dataset.previewing = '1';
+            const response = await fetch('/api/quote?' + link.dataset.quote);
+            const data = await response.json();
+            const box = document.createElement('div');
+            box.className = 'quote-preview';
+            if (response.ok) {
+                box.innerHTML = `<div class="post-meta">/${data.board_slug}/ • Post #${data.post_id ?? data.thread_id}</div>` + data.body_html;
+            } else {
+                box.textContent = data.error;
+            }
+            const rect = link.getBoundingClientRect();
+            box.style.left = (rect.left + window.scrollX) + 'px';
+            box.style.top = (rect.bottom + window.scrollY + 4) + 'px';
+            document.body.appendChild(box);
+            link.addEventListener('mouseleave', () => {
+                box.remove();
+                delete link.dataset.previewing;
+            }, { once: true });
+        });
+    </script>
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escapes_script_and_attributes() {
+        let html = render_post(r#"<script>alert("x")</script> <img src=x onerror='y'>"#);
+        assert_eq!(
+            html,
+            "&lt;script&gt;alert(&quot;x&quot;)&lt;/script&gt; &lt;img src=x onerror=&#39;y&#39;&gt;"
+        );
+        assert_eq!(escape_html("a & b"), "a &amp; b");
+    }
+
+    #[test]
+    fn test_greentext_and_quotes() {
+        assert_eq!(
+            render_post(">be me\n>>12 no"),
+            r##"<span class="greentext">&gt;be me</span><br><a class="quotelink" href="#p12">&gt;&gt;12</a> no"##
+        );
+        assert_eq!(render_post(">>abc"), r#"<span class="greentext">&gt;&gt;abc</span>"#);
+    }
+
+    #[test]
+    fn test_cross_thread_and_board_quotes() {
+        let mut links = QuoteLinks { thread_id: Some(1), ..Default::default() };
+        links.targets.insert(Quote::Post(5), QuoteTarget { thread_id: 1, post_id: Some(5) });
+        links.targets.insert(Quote::Post(9), QuoteTarget { thread_id: 3, post_id: Some(9) });
+        let tech = Quote::Board { slug: "tech".to_string(), id: 42 };
+        links.targets.insert(tech.clone(), QuoteTarget { thread_id: 42, post_id: None });
+
+        assert_eq!(
+            render_post_with(">>5 >>9", &links),
+            r##"<a class="quotelink" href="#p5" data-quote="id=5">&gt;&gt;5</a> <a class="quotelink" href="/threads/3#p9" data-quote="id=9">&gt;&gt;9</a>"##
+        );
+        assert_eq!(
+            render_post_with(">>>/tech/42 and >>>/b/7", &links),
+            r#"<a class="quotelink" href="/threads/42" data-quote="board=tech&amp;id=42">&gt;&gt;&gt;/tech/42</a> and <span class="deadlink">&gt;&gt;&gt;/b/7</span>"#
+        );
+        assert_eq!(render_post(">>>/x"), r#"<span class="greentext">&gt;&gt;&gt;/x</span>"#);
+        assert_eq!(quotes_in(">>>/tech/42 >>9 `>>9`"), vec![tech, Quote::Post(9)]);
+    }
+
+    #[test]
+    fn test_parse_quote_refs() {
+        assert_eq!(Quote::parse_ref(" >>>/tech/42 "), Some(Quote::Board { slug: "tech".to_string(), id: 42 }));
+        assert_eq!(Quote::parse_ref(">>7"), Some(Quote::Post(7)));
+        assert_eq!(Quote::parse_ref("7"), Some(Quote::Post(7)));
+        assert_eq!(Quote::parse_ref(">>>/tech/0"), None);
+        assert_eq!(Quote::parse_ref(">>>/Tech/4"), None);
+        assert_eq!(Quote::parse_ref(">>7x"), None);
+    }
+
+    #[test]
+    fn test_links_cannot_break_out_of_attributes() {
+        let html = render_post(r#"see https://example.com/"onmouseover="alert(1) now"#);
+        assert!(html.contains(r#"href="https://example.com/""#));
+        assert!(!html.contains(r#"" onmouseover"#));
+        assert!(!html.contains("onmouseover=\"alert"));
+
+        assert_eq!(render_post("javascript:alert(1)"), "javascript:alert(1)");
+        assert_eq!(render_post("xhttps://a.b"), "xhttps://a.b");
+        assert_eq!(
+            render_post("(https://a.b/c)."),
+            r#"(<a href="https://a.b/c" rel="nofollow noopener noreferrer" target="_blank">https://a.b/c</a>)."#
+        );
+    }
+
+    #[test]
+    fn test_code_is_literal() {
+        assert_eq!(
+            render_post("`<b>` and >>5"),
+            r##"<code>&lt;b&gt;</code> and <a class="quotelink" href="#p5">&gt;&gt;5</a>"##
+        );
+        assert_eq!(
+            render_post("```\n>not green\n<i>x</i> https://a.b\n```\nafter"),
+            "<pre><code>&gt;not green\n&lt;i&gt;x&lt;/i&gt; https://a.b</code></pre><br>after"
+        );
+        // Unclosed fences and backticks don't swallow or leak markup
+        assert_eq!(render_post("```\n<p>"), "<pre><code>&lt;p&gt;</code></pre>");
+        assert_eq!(render_post("`<p>"), "`&lt;p&gt;");
+    }
+
+    #[test]
+    fn test_spoilers() {
+        assert_eq!(
+            render_post("[spoiler]<b>[/spoiler]"),
+            r#"<span class="spoiler">&lt;b&gt;</span>"#
+        );
+        assert_eq!(render_post("[spoiler]open"), "[spoiler]open");
+        assert_eq!(
+            render_post("[spoiler][spoiler]x[/spoiler][/spoiler]"),
+            r#"<span class="spoiler">[spoiler]x</span>[/spoiler]"#
+        );
+    }
+}
@@ -3,6 +3,8 @@ use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
 
+use crate::{attachments, uniqueness::Normalization};
+
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
 pub struct Board {
     pub id: i64,
@@ -12,6 +14,12 @@ pub struct Board {
     pub is_active: bool,
     pub thread_count: i32,
     pub post_count: i32,
+    pub bump_limit: i32,
+    pub max_threads: i32,
+    pub max_upload_bytes: i64,
+    pub allowed_mime_types: String, // comma separated
+    pub unique_content: bool,
+    pub unique_normalization: String, // comma separated rules, see uniqueness::Normalization
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -26,9 +34,17 @@ pub struct Thread {
     pub author_pubkey: Option<String>,
     pub image_path: Option<String>,
     pub image_filename: Option<String>,
+    pub image_width: Option<i64>,
+    pub image_height: Option<i64>,
+    pub image_size_bytes: Option<i64>,
+    pub thumb_path: Option<String>,
+    pub thumb_width: Option<i64>,
+    pub thumb_height: Option<i64>,
     pub reply_count: i32,
     pub is_pinned: bool,
     pub is_locked: bool,
+    pub is_archived: bool,
+    pub archived_at: Option<DateTime<Utc>>,
     pub bump_score: i32,
     pub bumped_at: DateTime<Utc>,
     pub pow_nonce: Option<i64>,
@@ -36,6 +52,8 @@ pub struct Thread {
     pub pow_challenge_id: Option<String>,
     pub pow_difficulty: Option<f64>,
     pub pow_verified_at: Option<DateTime<Utc>>,
+    pub edited_at: Option<DateTime<Utc>>,
+    pub deleted_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -50,11 +68,19 @@ pub struct Post {
     pub author_pubkey: Option<String>,
     pub image_path: Option<String>,
     pub image_filename: Option<String>,
+    pub image_width: Option<i64>,
+    pub image_height: Option<i64>,
+    pub image_size_bytes: Option<i64>,
+    pub thumb_path: Option<String>,
+    pub thumb_width: Option<i64>,
+    pub thumb_height: Option<i64>,
     pub pow_nonce: Option<i64>,
     pub pow_hash: Option<String>,
     pub pow_challenge_id: Option<String>,
     pub pow_difficulty: Option<f64>,
     pub pow_verified_at: Option<DateTime<Utc>>,
+    pub edited_at: Option<DateTime<Utc>>,
+    pub deleted_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -63,7 +89,8 @@ pub struct Post {
 pub struct PowChallenge {
     pub id: String, // UUID
     pub user_pubkey_hex: String,
-    pub scope: String, // 'thread' or 'reply'
+    pub scope: String, // 'thread', 'reply', 'dm', 'edit', 'vote', 'reaction', 'boost' or 'upload'
+    pub board_id: i64,
     pub thread_id: i64,
     pub parent_id: i64,
     pub post_bytes_hash: Vec<u8>,
@@ -96,9 +123,20 @@ pub struct User {
     pub personal_21e8_hash: Option<String>,
     pub personal_21e8_nonce: Option<i64>,
     pub personal_21e8_achieved_at: Option<DateTime<Utc>>,
+    pub vanity_prefix: Option<String>,
+    pub vanity_hash: Option<String>,
+    pub vanity_nonce: Option<i64>,
+    pub vanity_achieved_at: Option<DateTime<Utc>>,
     pub post_count: i32,
     pub thread_count: i32,
     pub total_pow_difficulty: f64,
+    pub reputation_work: f64,
+    pub reputation_updated_at: Option<DateTime<Utc>>,
+    pub reputation_revoked_at: Option<DateTime<Utc>>,
+    pub linked_pubkey_hex: Option<String>,
+    pub link_main_sig_hex: Option<String>,
+    pub link_child_sig_hex: Option<String>,
+    pub linked_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -107,14 +145,237 @@ pub struct User {
 pub struct OpReceipt {
     pub id: String, // UUID - matches client_op_id
     pub operation_type: String, // 'thread_begin', 'thread_commit', etc.
+    pub request_hash: Option<String>, // fingerprint of the request, checked on replay
     pub result_json: String,
     pub created_at: DateTime<Utc>,
 }
 
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct DirectMessage {
+    pub id: i64,
+    pub sender_pubkey_hex: String,
+    pub recipient_pubkey_hex: String,
+    pub ciphertext_hex: String, // nonce || ciphertext || tag, encrypted client-side
+    pub pow_nonce: i64,
+    pub pow_hash: String,
+    pub pow_challenge_id: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct Notification {
+    pub id: i64,
+    pub recipient_pubkey_hex: String,
+    pub kind: String, // 'reply' or 'quote'
+    pub thread_id: i64,
+    pub post_id: Option<i64>, // None when a new thread's opening post did the quoting
+    pub target_thread_id: i64,
+    pub target_post_id: Option<i64>, // None for the target thread's opening post
+    pub actor_pubkey_hex: String,
+    pub read_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// The vanity flair shown next to a key, its own or its main identity's
+#[derive(Debug, Clone, FromRow)]
+pub struct Flair {
+    pub pubkey_hex: String,
+    pub vanity_prefix: String,
+    pub vanity_hash: String,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct PostRef {
+    pub id: i64,
+    pub from_thread_id: i64,
+    pub from_post_id: Option<i64>, // None for a thread's opening post
+    pub to_thread_id: i64,
+    pub to_post_id: Option<i64>, // None when quoting a thread's opening post
+    pub created_at: DateTime<Utc>,
+}
+
+/// Where a post or thread lives, looked up in bulk to resolve a page's quotes
+#[derive(Debug, Clone, FromRow)]
+pub struct QuoteLocation {
+    pub id: i64,
+    pub thread_id: i64, // the id itself for a thread
+    pub board_id: i64,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct Attachment {
+    pub sha256_hex: String,
+    pub mime_type: String,
+    pub size_bytes: i64,
+    pub original_filename: Option<String>,
+    pub width: i64,
+    pub height: i64,
+    pub thumb_width: i64,
+    pub thumb_height: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// One version of an edited post. Revision 0 is the original with the post's own
+/// proof; later ones are signed edits with the proof mined for the edit.
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct PostRevision {
+    pub id: i64,
+    pub thread_id: i64,
+    pub post_id: Option<i64>, // None for a thread's opening post
+    pub revision: i64,
+    pub title: Option<String>, // opening posts only
+    pub content: String,
+    pub author_pubkey: Option<String>,
+    pub signature_hex: Option<String>, // None for revision 0
+    pub pow_nonce: Option<i64>,
+    pub pow_hash: Option<String>,
+    pub pow_challenge_id: Option<String>,
+    pub pow_difficulty: Option<f64>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A deleted post's content, held for moderators until `purge_after`
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct DeletedContent {
+    pub id: i64,
+    pub thread_id: i64,
+    pub post_id: Option<i64>, // None for a thread's opening post
+    pub title: Option<String>, // opening posts only
+    pub content: String,
+    pub image_path: Option<String>,
+    pub image_filename: Option<String>,
+    pub author_pubkey: Option<String>,
+    pub deleted_at: DateTime<Utc>,
+    pub purge_after: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct Poll {
+    pub id: i64,
+    pub thread_id: i64,
+    pub question: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct PollOption {
+    pub id: i64,
+    pub poll_id: i64,
+    pub position: i64,
+    pub label: String,
+}
+
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct PollVote {
+    pub id: i64,
+    pub poll_id: i64,
+    pub option_id: i64,
+    pub voter_pubkey: String,
+    pub signature_hex: String,
+    pub pow_nonce: i64,
+    pub pow_hash: String,
+    pub pow_challenge_id: String,
+    pub pow_difficulty: f64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// One option's standing: how many pubkeys chose it and the work they spent
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct PollTally {
+    pub option_id: i64,
+    pub position: i64,
+    pub label: String,
+    pub voters: i64,
+    pub work: f64,
+}
+
+/// A work-backed reaction to a post
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct Reaction {
+    pub id: i64,
+    pub thread_id: i64,
+    pub post_id: Option<i64>, // None for a thread's opening post
+    pub kind: String,
+    pub reactor_pubkey: String,
+    pub pow_nonce: i64,
+    pub pow_hash: String,
+    pub pow_challenge_id: String,
+    pub pow_difficulty: f64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Work contributed to a thread without posting
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct ThreadBoost {
+    pub id: i64,
+    pub thread_id: i64,
+    pub booster_pubkey: String,
+    pub pow_nonce: i64,
+    pub pow_hash: String,
+    pub pow_challenge_id: String,
+    pub pow_difficulty: f64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// One pubkey's boosts to a thread, summed
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct BoostContributor {
+    pub booster_pubkey: String,
+    pub boosts: i64,
+    pub work: f64,
+}
+
+/// An attachment as linked to a post, with the file's details
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize)]
+pub struct PostAttachment {
+    pub thread_id: i64,
+    pub post_id: Option<i64>, // None for a thread's opening post
+    pub position: i64,
+    pub sha256_hex: String,
+    pub mime_type: String,
+    pub size_bytes: i64,
+    pub original_filename: Option<String>,
+    pub width: i64,
+    pub height: i64,
+    pub thumb_width: i64,
+    pub thumb_height: i64,
+}
+
+impl Attachment {
+    pub fn url(&self) -> String {
+        attachments::public_url(&self.sha256_hex, &self.mime_type)
+    }
+
+    pub fn thumbnail_url(&self) -> String {
+        attachments::thumbnail_url(&self.sha256_hex, &self.mime_type)
+    }
+}
+
+impl PostAttachment {
+    pub fn url(&self) -> String {
+        attachments::public_url(&self.sha256_hex, &self.mime_type)
+    }
+
+    pub fn thumbnail_url(&self) -> String {
+        attachments::thumbnail_url(&self.sha256_hex, &self.mime_type)
+    }
+}
+
+impl Board {
+    pub fn accepts_mime_type(&self, mime_type: &str) -> bool {
+        self.allowed_mime_types.split(',').any(|allowed| allowed.trim() == mime_type)
+    }
+
+    pub fn normalization(&self) -> Normalization {
+        Normalization::parse(&self.unique_normalization)
+    }
+}
+
 impl PowChallenge {
     pub fn new(
         user_pubkey_hex: String,
         scope: String,
+        board_id: i64,
         thread_id: i64,
         parent_id: i64,
         post_bytes_hash: Vec<u8>,
@@ -127,6 +388,7 @@ impl PowChallenge {
             id: Uuid::new_v4().to_string(),
             user_pubkey_hex,
             scope,
+            board_id,
             thread_id,
             parent_id,
             post_bytes_hash,
@@ -144,12 +406,100 @@ impl PowChallenge {
 }
 
 impl OpReceipt {
-    pub fn new(id: String, operation_type: String, result_json: String) -> Self {
+    pub fn new(
+        id: String,
+        operation_type: String,
+        request_hash: Option<String>,
+        result_json: String,
+    ) -> Self {
         Self {
             id,
             operation_type,
+            request_hash,
             result_json,
             created_at: Utc::now(),
         }
     }
-}
\ No newline at end of file
+}
+
+/// Server-side orderings for a board's live threads. Pinned threads always sort first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThreadSort {
+    #[default]
+    Bump,
+    Created,
+    Replies,
+    Work,
+}
+
+impl ThreadSort {
+    pub const ALL: [ThreadSort; 4] = [
+        ThreadSort::Bump,
+        ThreadSort::Created,
+        ThreadSort::Replies,
+        ThreadSort::Work,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ThreadSort::Bump => "bump",
+            ThreadSort::Created => "created",
+            ThreadSort::Replies => "replies",
+            ThreadSort::Work => "work",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ThreadSort::Bump => "Bump Order",
+            ThreadSort::Created => "Newest",
+            ThreadSort::Replies => "Replies",
+            ThreadSort::Work => "Work",
+        }
+    }
+}
+
+/// Keyset position just past a thread in a given sort: (is_pinned, sort key, id).
+/// Bump order keys on `bumped_at` in nanoseconds; the other sorts key on an integer column.
+/// Travels in query strings as `pinned.key.id`, so a page doesn't shift when threads are added.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ThreadCursor {
+    pub is_pinned: bool,
+    pub key: i64,
+    pub id: i64,
+}
+
+impl ThreadCursor {
+    pub fn after(thread: &Thread, sort: ThreadSort) -> Self {
+        let key = match sort {
+            ThreadSort::Bump => thread.bumped_at.timestamp_nanos_opt().unwrap_or(i64::MAX),
+            ThreadSort::Created => thread.id,
+            ThreadSort::Replies => thread.reply_count as i64,
+            ThreadSort::Work => thread.bump_score as i64,
+        };
+
+        Self {
+            is_pinned: thread.is_pinned,
+            key,
+            id: thread.id,
+        }
+    }
+
+    pub fn encode(&self) -> String {
+        format!("{}.{}.{}", self.is_pinned as u8, self.key, self.id)
+    }
+
+    pub fn decode(s: &str) -> Option<Self> {
+        let mut parts = s.splitn(3, '.');
+        let is_pinned = match parts.next()? {
+            "0" => false,
+            "1" => true,
+            _ => return None,
+        };
+        let key = parts.next()?.parse().ok()?;
+        let id = parts.next()?.parse().ok()?;
+
+        Some(Self { is_pinned, key, id })
+    }
+}
@@ -0,0 +1,73 @@
+//! Polls opened with a thread. Votes are mined and signed like any other
+//! submission, and tallied both by voter count and by the work behind them.
+
+use crate::pow::PollDraft;
+
+pub const MIN_POLL_OPTIONS: usize = 2;
+pub const MAX_POLL_OPTIONS: usize = 10;
+pub const MAX_QUESTION_CHARS: usize = 200;
+pub const MAX_OPTION_CHARS: usize = 100;
+
+/// Everything wrong with a poll draft, empty if it can be opened
+pub fn poll_errors(poll: &PollDraft) -> Vec<String> {
+    let mut errors = Vec::new();
+
+    let question = poll.question.trim();
+    if question.is_empty() {
+        errors.push("Poll question is empty".to_string());
+    } else if question.chars().count() > MAX_QUESTION_CHARS {
+        errors.push(format!("Poll question is over {} characters", MAX_QUESTION_CHARS));
+    }
+
+    if poll.options.len() < MIN_POLL_OPTIONS || poll.options.len() > MAX_POLL_OPTIONS {
+        errors.push(format!(
+            "Polls need {} to {} options",
+            MIN_POLL_OPTIONS, MAX_POLL_OPTIONS
+        ));
+    }
+
+    let mut seen = Vec::new();
+    for option in &poll.options {
+        let option = option.trim();
+        if option.is_empty() {
+            errors.push("Poll options can't be empty".to_string());
+        } else if option.chars().count() > MAX_OPTION_CHARS {
+            errors.push(format!("Poll options are limited to {} characters", MAX_OPTION_CHARS));
+        } else if seen.contains(&option.to_lowercase()) {
+            errors.push(format!("Poll option \"{}\" is listed twice", option));
+        } else {
+            seen.push(option.to_lowercase());
+        }
+    }
+
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn poll(question: &str, options: &[&str]) -> PollDraft {
+        PollDraft {
+            question: question.to_string(),
+            options: options.iter().map(|option| option.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_valid_poll() {
+        assert!(poll_errors(&poll("Best prefix?", &["21e8", "0000"])).is_empty());
+    }
+
+    #[test]
+    fn test_poll_errors() {
+        assert_eq!(poll_errors(&poll(" ", &["a", "b"])), vec!["Poll question is empty"]);
+        assert_eq!(poll_errors(&poll("q", &["only"])).len(), 1);
+        assert_eq!(poll_errors(&poll("q", &["a"; MAX_POLL_OPTIONS + 1])).len(), 1 + MAX_POLL_OPTIONS);
+        assert_eq!(
+            poll_errors(&poll("q", &["Yes", "yes "])),
+            vec!["Poll option \"yes\" is listed twice"]
+        );
+        assert_eq!(poll_errors(&poll("q", &["a", ""])), vec!["Poll options can't be empty"]);
+    }
+}
\ No newline at end of file
@@ -2,6 +2,8 @@ use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use std::collections::BTreeMap;
 
+use crate::markup::Quote;
+
 pub const MINER_VERSION: u32 = 1;
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -10,16 +12,101 @@ pub struct PostDraft {
     pub body: String,
     #[serde(default)]
     pub refs: Vec<String>,
+    /// Reply without bumping the thread; only hashed when set, so other drafts are unchanged
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub sage: bool,
     pub title: String,
+    /// Poll to open with a new thread; like `sage`, only hashed when present
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub poll: Option<PollDraft>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PollDraft {
+    pub question: String,
+    pub options: Vec<String>,
+}
+
+impl PostDraft {
+    /// Every quote in `refs`, board quotes included, or None if any ref isn't one
+    pub fn parse_refs(&self) -> Option<Vec<Quote>> {
+        let mut quotes = self.refs.iter().map(|r| Quote::parse_ref(r)).collect::<Option<Vec<Quote>>>()?;
+        quotes.sort_unstable();
+        quotes.dedup();
+        Some(quotes)
+    }
+
+    /// Draft used to stamp a direct message: the recipient is bound as the title
+    /// and the client-side ciphertext as the body
+    pub fn direct_message(recipient_pubkey_hex: &str, ciphertext_hex: &str) -> Self {
+        Self {
+            attachments: vec![],
+            body: ciphertext_hex.to_string(),
+            refs: vec![],
+            sage: false,
+            title: recipient_pubkey_hex.to_string(),
+            poll: None,
+        }
+    }
+
+    /// Draft used to stamp a boost, which carries no content of its own
+    pub fn boost() -> Self {
+        Self {
+            attachments: vec![],
+            body: String::new(),
+            refs: vec![],
+            sage: false,
+            title: String::new(),
+            poll: None,
+        }
+    }
+
+    /// Draft used to stamp a reaction: the kind is bound as the body
+    pub fn reaction(kind: &str) -> Self {
+        Self {
+            attachments: vec![],
+            body: kind.to_string(),
+            refs: vec![],
+            sage: false,
+            title: String::new(),
+            poll: None,
+        }
+    }
+
+    /// Draft used to stamp an upload: the SHA-256 of the file as sent is bound as the body
+    pub fn upload(file_sha256_hex: &str) -> Self {
+        Self {
+            attachments: vec![],
+            body: file_sha256_hex.to_string(),
+            refs: vec![],
+            sage: false,
+            title: String::new(),
+            poll: None,
+        }
+    }
+
+    /// Draft used to stamp a poll vote: the poll is bound as the title and the
+    /// chosen option as the body
+    pub fn poll_vote(poll_id: i64, option_id: i64) -> Self {
+        Self {
+            attachments: vec![],
+            body: option_id.to_string(),
+            refs: vec![],
+            sage: false,
+            title: poll_id.to_string(),
+            poll: None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CanonicalParams {
     pub user_pubkey_hex: String,
-    pub scope: String, // 't' for thread, 'r' for reply
+    pub scope: String, // 't' thread, 'r' reply, 'd' direct message, 'e' edit, 'v' poll vote, 'x' reaction, 'b' boost, 'u' upload
+    pub board_id: u64, // target board for threads, 0 otherwise
     pub thread_id: u64,
     pub parent_id: u64,
-    pub timestamp_i64: i64,
+    pub timestamp_i64: i64, // unix seconds
     pub post_draft: PostDraft,
 }
 
@@ -27,10 +114,14 @@ pub struct CanonicalParams {
 pub struct ProofOfWork {
     pub nonce_u64: u64,
     pub miner_version: u32,
-    pub timestamp_i64: i64,
+    pub timestamp_i64: i64, // unix seconds, same as the begin request
 }
 
-/// Generate canonical bytes following the HC1 format from haichan
+/// Generate canonical bytes following the HC1 format from haichan.
+///
+/// Only the key that mined the post is encoded, never its main identity. A later
+/// pseudonym link is an overlay in `users` that leaves existing proofs valid, and
+/// encoding the link here would reveal it to the server at mining time.
 pub fn canonical_bytes_v1(params: &CanonicalParams) -> Vec<u8> {
     let mut bytes = Vec::new();
     
@@ -40,9 +131,12 @@ pub fn canonical_bytes_v1(params: &CanonicalParams) -> Vec<u8> {
     // User public key hex (66 bytes for secp256k1)
     bytes.extend_from_slice(params.user_pubkey_hex.as_bytes());
     
-    // Scope ('t' or 'r')
+    // Scope ('t', 'r', 'd', 'e', 'v', 'x', 'b' or 'u')
     bytes.extend_from_slice(params.scope.as_bytes());
     
+    // Board ID as u64 little endian
+    bytes.extend_from_slice(&params.board_id.to_le_bytes());
+    
     // Thread ID as u64 little endian
     bytes.extend_from_slice(&params.thread_id.to_le_bytes());
     
@@ -66,7 +160,13 @@ fn minify_post_json(post: &PostDraft) -> String {
     map.insert("attachments", serde_json::to_value(&post.attachments).unwrap());
     map.insert("body", serde_json::to_value(&post.body).unwrap());
     map.insert("refs", serde_json::to_value(&post.refs).unwrap());
+    if post.sage {
+        map.insert("sage", serde_json::Value::Bool(true));
+    }
     map.insert("title", serde_json::to_value(&post.title).unwrap());
+    if let Some(poll) = &post.poll {
+        map.insert("poll", serde_json::to_value(poll).unwrap());
+    }
     
     serde_json::to_string(&map).unwrap()
 }
@@ -78,7 +178,7 @@ pub fn sha256_hex(input: &[u8]) -> String {
 }
 
 /// Compute SHA256 hash and return raw bytes
-fn sha256_bytes(input: &[u8]) -> [u8; 32] {
+pub fn sha256_bytes(input: &[u8]) -> [u8; 32] {
     let mut hasher = Sha256::new();
     hasher.update(input);
     hasher.finalize().into()
@@ -149,6 +249,57 @@ pub fn mine_personal_21e8(pubkey_hex: &str, max_iterations: u64) -> Option<(u64,
     None
 }
 
+/// Canonical input for a user's vanity flair, in the same HC1_USER_ style as the personal 21e8 hash
+fn personal_vanity_input(pubkey_hex: &str, pattern: &str) -> Vec<u8> {
+    let mut input = Vec::new();
+    input.extend_from_slice(b"HC1_USER_");
+    input.extend_from_slice(pubkey_hex.as_bytes());
+    input.extend_from_slice(b"_VANITY_");
+    input.extend_from_slice(pattern.as_bytes());
+    input
+}
+
+/// Check that a vanity pattern is lowercase hex within the allowed length bounds
+pub fn is_valid_vanity_pattern(pattern: &str, min_len: usize, max_len: usize) -> bool {
+    (min_len..=max_len).contains(&pattern.len())
+        && pattern.chars().all(|c| matches!(c, '0'..='9' | 'a'..='f'))
+}
+
+/// Mine a personal vanity hash with a user-chosen prefix for a public key
+pub fn mine_personal_vanity(
+    pubkey_hex: &str,
+    pattern: &str,
+    max_iterations: u64,
+) -> Option<(u64, String)> {
+    let input_base = personal_vanity_input(pubkey_hex, pattern);
+
+    for nonce in 0..max_iterations {
+        let mut input = input_base.clone();
+        input.extend_from_slice(&nonce.to_le_bytes());
+
+        let hash_hex = sha256_hex(&input);
+        if verify_prefix(&hash_hex, pattern) {
+            return Some((nonce, hash_hex));
+        }
+
+        if nonce % 50_000 == 0 {
+            tracing::debug!("Vanity {} mining progress for {}: {} iterations", pattern, pubkey_hex, nonce);
+        }
+    }
+    None
+}
+
+/// Verify a personal vanity solution, returning validity and the resulting hash
+pub fn verify_personal_vanity(pubkey_hex: &str, pattern: &str, nonce: u64) -> (bool, String) {
+    let mut input = personal_vanity_input(pubkey_hex, pattern);
+    input.extend_from_slice(&nonce.to_le_bytes());
+
+    let hash_hex = sha256_hex(&input);
+    let valid = verify_prefix(&hash_hex, pattern);
+
+    (valid, hash_hex)
+}
+
 /// Check for extended 21e8 patterns
 fn detect_21e8_extension(hash: &str) -> (bool, usize) {
     if !hash.starts_with("21e8") {
@@ -279,15 +430,18 @@ mod tests {
             attachments: vec![],
             body: "test body".to_string(),
             refs: vec![],
+            sage: false,
             title: "test title".to_string(),
+            poll: None,
         };
         
         let params = CanonicalParams {
             user_pubkey_hex: "03".repeat(33), // 66 char hex string
             scope: "t".to_string(),
+            board_id: 2,
             thread_id: 0,
             parent_id: 0,
-            timestamp_i64: 1640995200000,
+            timestamp_i64: 1640995200,
             post_draft: post,
         };
         
@@ -301,6 +455,9 @@ mod tests {
         
         // Should contain scope
         assert_eq!(&bytes[69..70], b"t");
+        
+        // Should bind the target board
+        assert_eq!(&bytes[70..78], &2u64.to_le_bytes());
     }
 
     #[test]
@@ -324,7 +481,9 @@ mod tests {
             attachments: vec!["file1.jpg".to_string()],
             body: "Hello\nWorld!".to_string(),
             refs: vec!["ref1".to_string()],
+            sage: false,
             title: "My Title".to_string(),
+            poll: None,
         };
         
         let minified = minify_post_json(&post);
@@ -337,5 +496,83 @@ mod tests {
         assert!(minified.contains(r#""body""#));
         assert!(minified.contains(r#""refs""#));
         assert!(minified.contains(r#""title""#));
+        assert!(!minified.contains(r#""sage""#));
+    }
+
+    #[test]
+    fn test_sage_is_bound_into_post_hash() {
+        let post = PostDraft {
+            attachments: vec![],
+            body: "no bump".to_string(),
+            refs: vec![],
+            sage: false,
+            title: String::new(),
+            poll: None,
+        };
+        let saged = PostDraft { sage: true, ..post.clone() };
+
+        assert!(minify_post_json(&saged).contains(r#""sage":true"#));
+        assert_ne!(minify_post_json(&post), minify_post_json(&saged));
+    }
+
+    #[test]
+    fn test_poll_is_bound_into_post_hash() {
+        let post = PostDraft::poll_vote(0, 0);
+        let with_poll = PostDraft {
+            poll: Some(PollDraft {
+                question: "Best hash prefix?".to_string(),
+                options: vec!["21e8".to_string(), "0000".to_string()],
+            }),
+            ..post.clone()
+        };
+
+        assert!(!minify_post_json(&post).contains(r#""poll""#));
+        assert!(minify_post_json(&with_poll).contains(r#""poll":{"options":["21e8","0000"],"question":"Best hash prefix?"}"#));
+    }
+
+    #[test]
+    fn test_parse_refs() {
+        let mut post = PostDraft {
+            attachments: vec![],
+            body: String::new(),
+            refs: vec![">>12".to_string(), "7".to_string(), " >>12 ".to_string()],
+            sage: false,
+            title: String::new(),
+            poll: None,
+        };
+        assert_eq!(post.parse_refs(), Some(vec![Quote::Post(7), Quote::Post(12)]));
+
+        post.refs.push(">>>/tech/3".to_string());
+        assert_eq!(post.parse_refs().map(|quotes| quotes.len()), Some(3));
+
+        post.refs.push(">>x".to_string());
+        assert_eq!(post.parse_refs(), None);
+
+        post.refs = vec!["0".to_string()];
+        assert_eq!(post.parse_refs(), None);
+    }
+
+    #[test]
+    fn test_personal_vanity_roundtrip() {
+        let pubkey = "02".repeat(33);
+        let (nonce, hash) = mine_personal_vanity(&pubkey, "b0", 1_000_000).unwrap();
+        assert!(hash.starts_with("b0"));
+        assert_eq!(verify_personal_vanity(&pubkey, "b0", nonce), (true, hash.clone()));
+
+        // The pubkey is bound into the input, so a solution doesn't carry over to another key
+        assert!(!verify_personal_vanity(&"03".repeat(33), "b0", nonce).0);
+
+        // So is the pattern: the same nonce hashes differently when claimed for a shorter one
+        assert_ne!(verify_personal_vanity(&pubkey, "b", nonce).1, hash);
+    }
+
+    #[test]
+    fn test_vanity_pattern_bounds() {
+        assert!(is_valid_vanity_pattern("cafe", 3, 8));
+        assert!(is_valid_vanity_pattern("b00b5", 3, 8));
+        assert!(!is_valid_vanity_pattern("ab", 3, 8));
+        assert!(!is_valid_vanity_pattern("abcdef012", 3, 8));
+        assert!(!is_valid_vanity_pattern("CAFE", 3, 8));
+        assert!(!is_valid_vanity_pattern("beer", 3, 8));
     }
 }
\ No newline at end of file
@@ -0,0 +1,143 @@
+//! Lightweight reactions, each paid for with its own small proof of work. A
+//! reaction whose hash happens to reach a 21e8 tier also shows that tier's glyph.
+
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::{models::Reaction, pow::get_21e8_emoji};
+
+/// Reaction kinds a post can receive, with the glyph each renders as
+pub const REACTION_KINDS: &[(&str, &str)] = &[
+    ("up", "👍"),
+    ("heart", "❤️"),
+    ("fire", "🔥"),
+    ("laugh", "😂"),
+    ("think", "🤔"),
+];
+
+/// Kind recorded for the special reaction a 21e8-tier hash earns
+pub const SPECIAL_KIND: &str = "21e8";
+
+pub fn reaction_glyph(kind: &str) -> Option<&'static str> {
+    REACTION_KINDS
+        .iter()
+        .find(|(name, _)| *name == kind)
+        .map(|(_, glyph)| *glyph)
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ReactionCount {
+    pub kind: String,
+    pub glyph: String,
+    pub count: i64,
+    pub work: f64,
+}
+
+/// Everything a post has been reacted with
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct PostReactions {
+    pub total_work: f64,
+    pub reactions: Vec<ReactionCount>,
+}
+
+impl PostReactions {
+    fn add(&mut self, kind: &str, glyph: &str, work: f64) {
+        match self.reactions.iter_mut().find(|count| count.kind == kind && count.glyph == glyph) {
+            Some(count) => {
+                count.count += 1;
+                count.work += work;
+            }
+            None => self.reactions.push(ReactionCount {
+                kind: kind.to_string(),
+                glyph: glyph.to_string(),
+                count: 1,
+                work,
+            }),
+        }
+    }
+}
+
+/// Group a thread's reactions by post (`None` for the opening post). Kinds keep
+/// their `REACTION_KINDS` order and 21e8 glyphs follow; each reaction's work
+/// counts once toward the post's total.
+pub fn aggregate(reactions: &[Reaction]) -> HashMap<Option<i64>, PostReactions> {
+    let mut by_post: HashMap<Option<i64>, PostReactions> = HashMap::new();
+
+    for (kind, glyph) in REACTION_KINDS {
+        for reaction in reactions.iter().filter(|reaction| reaction.kind == *kind) {
+            let post = by_post.entry(reaction.post_id).or_default();
+            post.total_work += reaction.pow_difficulty;
+            post.add(kind, glyph, reaction.pow_difficulty);
+        }
+    }
+
+    for reaction in reactions {
+        if let Some(glyph) = get_21e8_emoji(&reaction.pow_hash) {
+            by_post
+                .entry(reaction.post_id)
+                .or_default()
+                .add(SPECIAL_KIND, glyph, reaction.pow_difficulty);
+        }
+    }
+
+    by_post
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn reaction(post_id: Option<i64>, kind: &str, pow_hash: &str, pow_difficulty: f64) -> Reaction {
+        Reaction {
+            id: 0,
+            thread_id: 1,
+            post_id,
+            kind: kind.to_string(),
+            reactor_pubkey: "02".to_string() + &"ab".repeat(32),
+            pow_nonce: 0,
+            pow_hash: pow_hash.to_string(),
+            pow_challenge_id: String::new(),
+            pow_difficulty,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_reaction_glyph() {
+        assert_eq!(reaction_glyph("fire"), Some("🔥"));
+        assert_eq!(reaction_glyph(SPECIAL_KIND), None);
+        assert_eq!(reaction_glyph("nope"), None);
+    }
+
+    #[test]
+    fn test_aggregate_per_post() {
+        let plain = "00".repeat(32);
+        let reactions = vec![
+            reaction(None, "fire", &plain, 2.0),
+            reaction(Some(5), "fire", &plain, 1.0),
+            reaction(None, "up", &plain, 3.0),
+            reaction(None, "fire", &plain, 4.0),
+        ];
+        let by_post = aggregate(&reactions);
+
+        let op = &by_post[&None];
+        assert_eq!(op.total_work, 9.0);
+        assert_eq!(op.reactions.len(), 2);
+        assert_eq!((op.reactions[0].kind.as_str(), op.reactions[0].count, op.reactions[0].work), ("up", 1, 3.0));
+        assert_eq!((op.reactions[1].kind.as_str(), op.reactions[1].count, op.reactions[1].work), ("fire", 2, 6.0));
+        assert_eq!(by_post[&Some(5)].total_work, 1.0);
+    }
+
+    #[test]
+    fn test_21e8_hash_adds_special_reaction() {
+        let hash = format!("21e800{}", "ab".repeat(29));
+        let by_post = aggregate(&[reaction(None, "heart", &hash, 5.0)]);
+
+        let op = &by_post[&None];
+        assert_eq!(op.total_work, 5.0);
+        assert_eq!(op.reactions.len(), 2);
+        assert_eq!(op.reactions[1].kind, SPECIAL_KIND);
+        assert_eq!(Some(op.reactions[1].glyph.as_str()), get_21e8_emoji(&hash));
+    }
+}
\ No newline at end of file
@@ -0,0 +1,155 @@
+use chrono::{DateTime, Utc};
+
+use crate::{config::Config, models::User};
+
+/// Policy for turning a key's accumulated verified work into a difficulty discount
+#[derive(Debug, Clone)]
+pub struct ReputationPolicy {
+    pub half_life_days: f64,
+    pub work_per_level: f64,
+    pub min_age_days: i64,
+    pub max_discount: usize,
+    pub min_prefix_len: usize,
+}
+
+impl ReputationPolicy {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            half_life_days: config.reputation_half_life_days,
+            work_per_level: config.reputation_work_per_level,
+            min_age_days: config.reputation_min_age_days,
+            max_discount: config.reputation_max_discount,
+            min_prefix_len: config.pow_min_prefix_len,
+        }
+    }
+
+    /// Decay work accumulated at `since` down to its value at `now`
+    pub fn decayed_work(&self, work: f64, since: Option<DateTime<Utc>>, now: DateTime<Utc>) -> f64 {
+        let Some(since) = since else {
+            return work;
+        };
+        if self.half_life_days <= 0.0 {
+            return work;
+        }
+
+        let elapsed_days = (now - since).num_seconds().max(0) as f64 / 86_400.0;
+        work * 0.5_f64.powf(elapsed_days / self.half_life_days)
+    }
+
+    /// Reputation work after adding a newly verified proof
+    pub fn accrue(&self, user: Option<&User>, difficulty: f64, now: DateTime<Utc>) -> f64 {
+        let prior = user
+            .map(|u| self.decayed_work(u.reputation_work, u.reputation_updated_at, now))
+            .unwrap_or(0.0);
+
+        prior + difficulty
+    }
+
+    /// Number of hex characters to drop from the required prefix for this user
+    pub fn discount_level(&self, user: &User, now: DateTime<Utc>) -> usize {
+        // Age restarts from the last moderator action
+        let standing_since = user
+            .reputation_revoked_at
+            .map_or(user.created_at, |revoked| revoked.max(user.created_at));
+        if (now - standing_since).num_days() < self.min_age_days {
+            return 0;
+        }
+        if self.work_per_level <= 0.0 {
+            return 0;
+        }
+
+        let work = self.decayed_work(user.reputation_work, user.reputation_updated_at, now);
+        ((work / self.work_per_level).floor() as usize).min(self.max_discount)
+    }
+
+    /// Shorten a required prefix by the discount level, never below the minimum length
+    pub fn apply_discount(&self, prefix: &str, level: usize) -> String {
+        let keep = prefix
+            .len()
+            .saturating_sub(level)
+            .max(self.min_prefix_len)
+            .min(prefix.len());
+
+        prefix[..keep].to_string()
+    }
+
+    pub fn required_prefix(&self, default_prefix: &str, user: Option<&User>, now: DateTime<Utc>) -> String {
+        match user {
+            Some(user) => self.apply_discount(default_prefix, self.discount_level(user, now)),
+            None => default_prefix.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> ReputationPolicy {
+        ReputationPolicy {
+            half_life_days: 30.0,
+            work_per_level: 100.0,
+            min_age_days: 7,
+            max_discount: 1,
+            min_prefix_len: 2,
+        }
+    }
+
+    fn user(age_days: i64, work: f64) -> User {
+        let now = Utc::now();
+        User {
+            id: 1,
+            pubkey_hex: "02".repeat(33),
+            btc_address: None,
+            personal_21e8_hash: None,
+            personal_21e8_nonce: None,
+            personal_21e8_achieved_at: None,
+            vanity_prefix: None,
+            vanity_hash: None,
+            vanity_nonce: None,
+            vanity_achieved_at: None,
+            post_count: 0,
+            thread_count: 0,
+            total_pow_difficulty: work,
+            reputation_work: work,
+            reputation_updated_at: Some(now),
+            reputation_revoked_at: None,
+            linked_pubkey_hex: None,
+            link_main_sig_hex: None,
+            link_child_sig_hex: None,
+            linked_at: None,
+            created_at: now - chrono::Duration::days(age_days),
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn test_discount_requires_age_and_work() {
+        let now = Utc::now();
+        assert_eq!(policy().discount_level(&user(1, 1000.0), now), 0);
+        assert_eq!(policy().discount_level(&user(30, 50.0), now), 0);
+        assert_eq!(policy().discount_level(&user(30, 1000.0), now), 1);
+    }
+
+    #[test]
+    fn test_discount_lost_on_revocation() {
+        let now = Utc::now();
+        let mut u = user(30, 1000.0);
+        u.reputation_revoked_at = Some(now - chrono::Duration::days(1));
+        assert_eq!(policy().discount_level(&u, now), 0);
+    }
+
+    #[test]
+    fn test_work_decays_by_half_life() {
+        let now = Utc::now();
+        let decayed = policy().decayed_work(100.0, Some(now - chrono::Duration::days(30)), now);
+        assert!((decayed - 50.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_apply_discount_is_bounded() {
+        assert_eq!(policy().apply_discount("21e8", 1), "21e");
+        assert_eq!(policy().apply_discount("21e8", 5), "21");
+        assert_eq!(policy().apply_discount("2", 1), "2");
+    }
+}
\ No newline at end of file
@@ -0,0 +1,212 @@
+//! Reply trees built from `posts.parent_id`. Replies without a parent (or whose
+//! parent isn't in the thread) are top-level nodes under the opening post.
+
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::Post;
+
+/// Upper bound on requested depth, which also bounds recursion when rendering
+pub const MAX_TREE_DEPTH: u32 = 32;
+pub const DEFAULT_TREE_DEPTH: u32 = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SiblingOrder {
+    /// Oldest first, like the flat view
+    #[default]
+    Time,
+    /// Most proof-of-work first, ties oldest first
+    Work,
+}
+
+impl SiblingOrder {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SiblingOrder::Time => "time",
+            SiblingOrder::Work => "work",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplyNode {
+    pub post: Post,
+    pub depth: u32,
+    pub children: Vec<ReplyNode>,
+    /// Replies below the depth limit that were left out of `children`
+    pub hidden_descendants: usize,
+}
+
+/// Build the reply forest of a thread, or of the subtree under `root` when given.
+/// Nodes at `max_depth` keep no children; their replies are only counted.
+pub fn build_reply_tree(
+    posts: Vec<Post>,
+    order: SiblingOrder,
+    max_depth: u32,
+    root: Option<i64>,
+) -> Vec<ReplyNode> {
+    let ids: HashSet<i64> = posts.iter().map(|post| post.id).collect();
+    let mut children: HashMap<Option<i64>, Vec<Post>> = HashMap::new();
+
+    for post in posts {
+        let parent = post.parent_id.filter(|id| ids.contains(id) && *id != post.id);
+        children.entry(parent).or_default().push(post);
+    }
+
+    for siblings in children.values_mut() {
+        sort_siblings(siblings, order);
+    }
+
+    let roots = match root {
+        Some(root_id) => {
+            let found = children
+                .values_mut()
+                .find_map(|siblings| {
+                    let index = siblings.iter().position(|post| post.id == root_id)?;
+                    Some(siblings.remove(index))
+                });
+            found.into_iter().collect()
+        }
+        None => children.remove(&None).unwrap_or_default(),
+    };
+
+    roots
+        .into_iter()
+        .map(|post| build_node(post, 0, max_depth, &mut children))
+        .collect()
+}
+
+fn sort_siblings(siblings: &mut [Post], order: SiblingOrder) {
+    match order {
+        SiblingOrder::Time => siblings.sort_by_key(|post| (post.created_at, post.id)),
+        SiblingOrder::Work => siblings.sort_by(|a, b| {
+            let work_a = a.pow_difficulty.unwrap_or(0.0);
+            let work_b = b.pow_difficulty.unwrap_or(0.0);
+            work_b
+                .total_cmp(&work_a)
+                .then_with(|| (a.created_at, a.id).cmp(&(b.created_at, b.id)))
+        }),
+    }
+}
+
+fn build_node(
+    post: Post,
+    depth: u32,
+    max_depth: u32,
+    children: &mut HashMap<Option<i64>, Vec<Post>>,
+) -> ReplyNode {
+    let replies = children.remove(&Some(post.id)).unwrap_or_default();
+
+    if depth >= max_depth {
+        // Count the rest of the subtree without recursing into it
+        let mut hidden = 0;
+        let mut stack = replies;
+        while let Some(reply) = stack.pop() {
+            hidden += 1;
+            stack.extend(children.remove(&Some(reply.id)).unwrap_or_default());
+        }
+
+        return ReplyNode {
+            post,
+            depth,
+            children: Vec::new(),
+            hidden_descendants: hidden,
+        };
+    }
+
+    ReplyNode {
+        post,
+        depth,
+        children: replies
+            .into_iter()
+            .map(|reply| build_node(reply, depth + 1, max_depth, children))
+            .collect(),
+        hidden_descendants: 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone, Utc};
+
+    fn post(id: i64, parent_id: Option<i64>, difficulty: f64) -> Post {
+        let at = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap() + Duration::seconds(id);
+        Post {
+            id,
+            thread_id: 1,
+            parent_id,
+            content: String::new(),
+            author_name: None,
+            author_pubkey: None,
+            image_path: None,
+            image_filename: None,
+            image_width: None,
+            image_height: None,
+            image_size_bytes: None,
+            thumb_path: None,
+            thumb_width: None,
+            thumb_height: None,
+            pow_nonce: None,
+            pow_hash: None,
+            pow_challenge_id: None,
+            pow_difficulty: Some(difficulty),
+            pow_verified_at: None,
+            edited_at: None,
+            deleted_at: None,
+            created_at: at,
+            updated_at: at,
+        }
+    }
+
+    fn ids(nodes: &[ReplyNode]) -> Vec<i64> {
+        nodes.iter().map(|node| node.post.id).collect()
+    }
+
+    #[test]
+    fn test_builds_tree_from_parents() {
+        let posts = vec![
+            post(1, None, 1.0),
+            post(2, Some(1), 1.0),
+            post(3, None, 1.0),
+            post(4, Some(2), 1.0),
+            post(5, Some(99), 1.0), // parent outside the thread
+        ];
+        let tree = build_reply_tree(posts, SiblingOrder::Time, DEFAULT_TREE_DEPTH, None);
+
+        assert_eq!(ids(&tree), vec![1, 3, 5]);
+        assert_eq!(ids(&tree[0].children), vec![2]);
+        assert_eq!(ids(&tree[0].children[0].children), vec![4]);
+        assert_eq!(tree[0].children[0].children[0].depth, 2);
+    }
+
+    #[test]
+    fn test_sibling_order_by_work() {
+        let posts = vec![post(1, None, 2.0), post(2, None, 9.0), post(3, None, 2.0)];
+        let tree = build_reply_tree(posts, SiblingOrder::Work, DEFAULT_TREE_DEPTH, None);
+        assert_eq!(ids(&tree), vec![2, 1, 3]);
+    }
+
+    #[test]
+    fn test_depth_limit_counts_hidden_replies() {
+        let posts = vec![
+            post(1, None, 1.0),
+            post(2, Some(1), 1.0),
+            post(3, Some(2), 1.0),
+            post(4, Some(3), 1.0),
+            post(5, Some(2), 1.0),
+        ];
+        let tree = build_reply_tree(posts.clone(), SiblingOrder::Time, 1, None);
+        let cut = &tree[0].children[0];
+        assert!(cut.children.is_empty());
+        assert_eq!(cut.hidden_descendants, 3);
+
+        // Continuing from the cut node shows its subtree
+        let subtree = build_reply_tree(posts, SiblingOrder::Time, 1, Some(2));
+        assert_eq!(ids(&subtree), vec![2]);
+        assert_eq!(ids(&subtree[0].children), vec![3, 5]);
+        assert_eq!(subtree[0].children[0].hidden_descendants, 1);
+    }
+}
@@ -0,0 +1,102 @@
+//! ROBOT9000-style duplicate detection. A post's text is normalized with its
+//! board's rules and hashed together with its attachments; boards in uniqueness
+//! mode refuse any hash they have seen before.
+
+use crate::pow::sha256_hex;
+
+/// Which differences between two posts are ignored when comparing them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Normalization {
+    /// Compare case-insensitively
+    pub case: bool,
+    /// Collapse runs of whitespace and trim the ends
+    pub whitespace: bool,
+    /// Drop punctuation and symbols
+    pub punctuation: bool,
+}
+
+impl Normalization {
+    /// Parse a comma-separated list of rules, e.g. `case,whitespace`. Unknown rules are ignored.
+    pub fn parse(rules: &str) -> Self {
+        let mut normalization = Normalization::default();
+        for rule in rules.split(',').map(str::trim) {
+            match rule {
+                "case" => normalization.case = true,
+                "whitespace" => normalization.whitespace = true,
+                "punctuation" => normalization.punctuation = true,
+                _ => {}
+            }
+        }
+        normalization
+    }
+
+    pub fn apply(&self, text: &str) -> String {
+        let mut normalized: String = text
+            .chars()
+            .filter(|c| !self.punctuation || c.is_alphanumeric() || c.is_whitespace())
+            .collect();
+
+        if self.case {
+            normalized = normalized.to_lowercase();
+        }
+
+        if self.whitespace {
+            normalized = normalized.split_whitespace().collect::<Vec<_>>().join(" ");
+        }
+
+        normalized
+    }
+}
+
+/// Hash of a post's content as compared for uniqueness. Attachments count in
+/// any order, so an image-only repost is caught as well as a text one.
+pub fn content_hash(normalization: &Normalization, body: &str, attachments: &[String]) -> String {
+    let mut attachments = attachments.to_vec();
+    attachments.sort();
+    attachments.dedup();
+
+    let mut input = normalization.apply(body);
+    for sha256 in &attachments {
+        input.push('\n');
+        input.push_str(sha256);
+    }
+
+    sha256_hex(input.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalization_rules() {
+        let all = Normalization::parse("case, whitespace,punctuation");
+        assert_eq!(all.apply("  Hello,   WORLD!!\n"), "hello world");
+
+        let case_only = Normalization::parse("case");
+        assert_eq!(case_only.apply("Hello,  World"), "hello,  world");
+
+        assert_eq!(Normalization::parse("bogus,"), Normalization::default());
+    }
+
+    #[test]
+    fn test_content_hash_ignores_normalized_differences() {
+        let rules = Normalization::parse("case,whitespace,punctuation");
+        let a = "ab".repeat(32);
+        let b = "cd".repeat(32);
+
+        assert_eq!(
+            content_hash(&rules, "Buy my coin!", &[]),
+            content_hash(&rules, "buy   my coin", &[])
+        );
+        assert_ne!(
+            content_hash(&rules, "buy my coin", &[]),
+            content_hash(&rules, "buy my coins", &[])
+        );
+        assert_eq!(
+            content_hash(&rules, "", &[a.clone(), b.clone()]),
+            content_hash(&rules, "", &[b.clone(), a.clone()])
+        );
+        assert_ne!(content_hash(&rules, "", &[a]), content_hash(&rules, "", &[b]));
+    }
+}
\ No newline at end of file